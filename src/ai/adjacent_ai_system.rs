@@ -1,5 +1,6 @@
 use specs::prelude::*;
-use crate::{MyTurn, Faction, Position, Map, raws::Reaction, WantsToMelee};
+use crate::{MyTurn, Faction, Position, Map, raws::Reaction, WantsToMelee, Chasing, Stealth, Behavior, AIState, Routed, StatusEffect};
+use std::collections::HashSet;
 
 pub struct AdjacentAI {}
 
@@ -12,15 +13,25 @@ impl<'a> System<'a> for AdjacentAI {
         ReadExpect<'a, Map>,
         WriteStorage<'a, WantsToMelee>,
         Entities<'a>,
-        ReadExpect<'a, Entity>
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Chasing>,
+        ReadStorage<'a, Stealth>,
+        WriteStorage<'a, Behavior>,
+        ReadStorage<'a, Routed>,
+        ReadStorage<'a, StatusEffect>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, factions, positions, map, mut want_melee, entities, player) = data;
+        let (mut turns, factions, positions, map, mut want_melee, entities, player, chasing, stealth, mut behaviors, routed, statuses) = data;
+
+        let mut routed_entities : HashSet<Entity> = HashSet::new();
+        for (status, _routed) in (&statuses, &routed).join() {
+            routed_entities.insert(status.target);
+        }
 
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, _turn, my_faction, pos) in (&entities, &turns, &factions, &positions).join() {
-            if entity != *player {
+            if entity != *player && !routed_entities.contains(&entity) {
                 let mut reactions : Vec<(Entity, Reaction)> = Vec::new();
                 let idx = map.xy_idx(pos.x, pos.y);
                 let w = map.width;
@@ -38,12 +49,20 @@ impl<'a> System<'a> for AdjacentAI {
                 let mut done = false;
                 for reaction in reactions.iter() {
                     if let Reaction::Attack = reaction.1 {
+                        // We can't attack a stealthy target we haven't noticed yet
+                        let aware = if let Some(chase) = chasing.get(entity) { chase.target == reaction.0 } else { false };
+                        if !aware && stealth.get(reaction.0).is_some() { continue; }
                         want_melee.insert(entity, WantsToMelee{ target: reaction.0 }).expect("Error inserting melee");
                         done = true;
                     }
                 }
 
-                if done { turn_done.push(entity); }
+                if done {
+                    turn_done.push(entity);
+                    if let Some(behavior) = behaviors.get_mut(entity) {
+                        behavior.state = AIState::Hunting;
+                    }
+                }
             }
         }
 
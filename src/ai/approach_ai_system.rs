@@ -1,6 +1,7 @@
-use bracket_lib::prelude::a_star_search;
+use bracket_lib::prelude::{a_star_search, Point};
 use specs::prelude::*;
-use crate::{MyTurn, WantsToApproach, Position, Map, ApplyMove};
+use crate::{MyTurn, WantsToApproach, Position, Map, ApplyMove, Swimmer, Flying};
+use super::{DijkstraMaps, DijkstraTarget, safe_footing};
 
 pub struct ApproachAI {}
 
@@ -12,25 +13,39 @@ impl<'a> System<'a> for ApproachAI {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
-        WriteStorage<'a, ApplyMove>
+        WriteStorage<'a, ApplyMove>,
+        ReadExpect<'a, DijkstraMaps>,
+        ReadExpect<'a, Point>,
+        ReadStorage<'a, Swimmer>,
+        ReadStorage<'a, Flying>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut turns, mut want_approach, positions, mut map,
-            entities, mut apply_move) = data;
+            entities, mut apply_move, dijkstra, player_pos, swimmers, flying) = data;
 
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y) as i32;
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, pos, approach, _myturn) in
             (&entities, &positions, &want_approach, &turns).join()
         {
             turn_done.push(entity);
-            let path = a_star_search(
-                map.xy_idx(pos.x, pos.y),
-                map.xy_idx(approach.idx % map.width, approach.idx / map.width),
-                &mut *map
-            );
-            if path.success && path.steps.len()>1 {
-                apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let can_swim = swimmers.get(entity).is_some() || flying.get(entity).is_some();
+            if approach.idx == player_idx {
+                // Everyone heading for the player shares the same map
+                if let Some(dest_idx) = dijkstra.next_step(DijkstraTarget::Player, my_idx, &map, can_swim) {
+                    apply_move.insert(entity, ApplyMove{ dest_idx }).expect("Unable to insert");
+                }
+            } else {
+                let path = a_star_search(
+                    my_idx,
+                    map.xy_idx(approach.idx % map.width, approach.idx / map.width),
+                    &mut *map
+                );
+                if path.success && path.steps.len()>1 && safe_footing(&map, path.steps[1], can_swim) {
+                    apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
+                }
             }
         }
 
@@ -1,7 +1,9 @@
 use specs::prelude::*;
-use crate::{MyTurn, Chasing, Position, Map, ApplyMove};
+use crate::{MyTurn, Chasing, Position, Map, ApplyMove, Behavior, AIState, Tunneler, Swimmer, Flying};
+use crate::effects::{add_effect, EffectType, Targets};
 use std::collections::HashMap;
-use bracket_lib::prelude::a_star_search;
+use bracket_lib::prelude::{a_star_search, line2d, LineAlg, DistanceAlg, Point};
+use super::{DijkstraMaps, DijkstraTarget, safe_footing};
 
 pub struct ChaseAI {}
 
@@ -13,12 +15,18 @@ impl<'a> System<'a> for ChaseAI {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
-        WriteStorage<'a, ApplyMove>
+        WriteStorage<'a, ApplyMove>,
+        WriteStorage<'a, Behavior>,
+        ReadExpect<'a, DijkstraMaps>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Tunneler>,
+        ReadStorage<'a, Swimmer>,
+        ReadStorage<'a, Flying>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut turns, mut chasing, positions, mut map,
-            entities, mut apply_move) = data;
+            entities, mut apply_move, mut behaviors, dijkstra, player, tunnelers, swimmers, flying) = data;
 
         let mut targets : HashMap<Entity, (i32, i32)> = HashMap::new();
         let mut end_chase : Vec<Entity> = Vec::new();
@@ -33,22 +41,63 @@ impl<'a> System<'a> for ChaseAI {
 
         for done in end_chase.iter() {
             chasing.remove(*done);
+            // The quarry is gone, so go back to what we were doing
+            if let Some(behavior) = behaviors.get_mut(*done) {
+                behavior.state = behavior.resting_state.clone();
+            }
         }
         end_chase.clear();
 
         let mut turn_done : Vec<Entity> = Vec::new();
-        for (entity, pos, _chase, _myturn) in
+        for (entity, pos, chase, _myturn) in
             (&entities, &positions, &chasing, &turns).join()
         {
             turn_done.push(entity);
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let can_swim = swimmers.get(entity).is_some() || flying.get(entity).is_some();
+
+            if tunnelers.get(entity).is_some() {
+                // Tunnelers don't bother finding a way around, they go straight through the rock
+                let start = Point::new(pos.x, pos.y);
+                let target = Point::new(targets[&entity].0, targets[&entity].1);
+                let line = line2d(LineAlg::Bresenham, start, target);
+                if DistanceAlg::Pythagoras.distance2d(start, target) >= 14.0 || line.len() < 2 {
+                    end_chase.push(entity);
+                    continue;
+                }
+                let next = map.xy_idx(line[1].x, line[1].y);
+                if map.diggable(next) {
+                    add_effect(Some(entity), EffectType::DestroyTerrain, Targets::Tile{ tile_idx : next as i32 });
+                    add_effect(Some(entity), EffectType::Noise{ radius : 6 }, Targets::Tile{ tile_idx : next as i32 });
+                } else if line[1] != target && !crate::spatial::is_blocked(next) {
+                    apply_move.insert(entity, ApplyMove{ dest_idx: next }).expect("Unable to insert");
+                }
+                continue;
+            }
+
+            if chase.target == *player {
+                // Chasing the player follows the shared map; give up if they're too far away
+                match dijkstra.distance(DijkstraTarget::Player, my_idx) {
+                    Some(distance) if distance < 14.0 => {
+                        if let Some(dest_idx) = dijkstra.next_step(DijkstraTarget::Player, my_idx, &map, can_swim) {
+                            apply_move.insert(entity, ApplyMove{ dest_idx }).expect("Unable to insert");
+                        }
+                    }
+                    _ => end_chase.push(entity)
+                }
+                continue;
+            }
+
             let target_pos = targets[&entity];
             let path = a_star_search(
-                map.xy_idx(pos.x, pos.y),
+                my_idx,
                 map.xy_idx(target_pos.0, target_pos.1),
                 &mut *map
             );
             if path.success && path.steps.len()>1 && path.steps.len()<15 {
-                apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
+                if safe_footing(&map, path.steps[1], can_swim) {
+                    apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
+                }
                 turn_done.push(entity);
             } else {
                 end_chase.push(entity);
@@ -57,6 +106,11 @@ impl<'a> System<'a> for ChaseAI {
 
         for done in end_chase.iter() {
             chasing.remove(*done);
+            // We lost track of them, so go and look where they were last seen
+            if let Some(behavior) = behaviors.get_mut(*done) {
+                let last_seen = targets[done];
+                behavior.state = AIState::Searching{ target : Point::new(last_seen.0, last_seen.1), turns : 10 };
+            }
         }
         for done in turn_done.iter() {
             turns.remove(*done);
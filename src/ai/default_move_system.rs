@@ -1,6 +1,7 @@
-use bracket_lib::prelude::{a_star_search, RandomNumberGenerator};
+use bracket_lib::prelude::{a_star_search, RandomNumberGenerator, Point};
 use specs::prelude::*;
-use crate::{MyTurn, MoveMode, Movement, Position, Map, map::tile_walkable, ApplyMove};
+use crate::{MyTurn, MoveMode, Movement, Position, Map, ApplyMove, Behavior, AIState, Swimmer, Flying};
+use super::safe_footing;
 
 pub struct DefaultMoveAI {}
 
@@ -13,18 +14,33 @@ impl<'a> System<'a> for DefaultMoveAI {
         WriteExpect<'a, Map>,
         WriteExpect<'a, RandomNumberGenerator>,
         WriteStorage<'a, ApplyMove>,
-        Entities<'a>
+        Entities<'a>,
+        WriteStorage<'a, Behavior>,
+        ReadStorage<'a, Swimmer>,
+        ReadStorage<'a, Flying>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut turns, mut move_mode, positions, mut map,
-            mut rng, mut apply_move, entities) = data;
+            mut rng, mut apply_move, entities, mut behaviors, swimmers, flying) = data;
 
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, pos, mut mode, _myturn) in
             (&entities, &positions, &mut move_mode, &turns).join()
         {
             turn_done.push(entity);
+            let can_swim = swimmers.get(entity).is_some() || flying.get(entity).is_some();
+
+            if let Some(behavior) = behaviors.get_mut(entity) {
+                if let Some(dest_idx) = follow_behavior(behavior, pos, &map, &mut rng, can_swim) {
+                    if !crate::spatial::is_blocked(dest_idx) && !crate::spatial::is_known_trap(dest_idx)
+                        && safe_footing(&map, dest_idx, can_swim)
+                    {
+                        apply_move.insert(entity, ApplyMove{ dest_idx }).expect("Unable to insert");
+                    }
+                }
+                if behavior.state != AIState::Wandering { continue; }
+            }
 
             match &mut mode.mode {
                 Movement::Static => {},
@@ -43,7 +59,9 @@ impl<'a> System<'a> for DefaultMoveAI {
 
                     if x > 0 && x < map.width-1 && y > 0 && y < map.height-1 {
                         let dest_idx = map.xy_idx(x, y);
-                        if !crate::spatial::is_blocked(dest_idx) {
+                        if !crate::spatial::is_blocked(dest_idx) && !crate::spatial::is_known_trap(dest_idx)
+                            && safe_footing(&map, dest_idx, can_swim)
+                        {
                             apply_move.insert(entity, ApplyMove{ dest_idx })
                                 .expect("Unable to insert");
                             turn_done.push(entity);
@@ -54,7 +72,10 @@ impl<'a> System<'a> for DefaultMoveAI {
                 Movement::RandomWaypoint{path} => {
                     if let Some(path) = path {
                         // We have a target - go there
-                        if path.len()>1 {
+                        if path.len()>1 && (crate::spatial::is_known_trap(path[1]) || !safe_footing(&map, path[1], can_swim)) {
+                            // Not going that way - pick somewhere else to go
+                            mode.mode = Movement::RandomWaypoint{ path : None };
+                        } else if path.len()>1 {
                             if !crate::spatial::is_blocked(path[1] as usize) {
                                 apply_move.insert(entity, ApplyMove{ dest_idx : path[1] })
                                     .expect("Unable to insert");
@@ -69,7 +90,7 @@ impl<'a> System<'a> for DefaultMoveAI {
                         let target_x = rng.roll_dice(1, map.width-2);
                         let target_y = rng.roll_dice(1, map.height-2);
                         let idx = map.xy_idx(target_x, target_y);
-                        if tile_walkable(map.tiles[idx]) {
+                        if map.walkable(idx) && safe_footing(&map, idx, can_swim) {
                             let path = a_star_search(
                                 map.xy_idx(pos.x, pos.y),
                                 map.xy_idx(target_x, target_y),
@@ -92,3 +113,65 @@ impl<'a> System<'a> for DefaultMoveAI {
         }
     }
 }
+
+/// Handles the idle AI states, returning a tile to move to if the state wants one.
+/// Wandering entities fall back to their regular movement mode.
+fn follow_behavior(behavior : &mut Behavior, pos : &Position, map : &Map, rng : &mut RandomNumberGenerator, can_swim : bool) -> Option<usize> {
+    let here = Point::new(pos.x, pos.y);
+    match &mut behavior.state {
+        AIState::Asleep | AIState::Wandering => None,
+
+        AIState::Hunting | AIState::Fleeing => {
+            // Nothing left to hunt or flee from; settle down
+            behavior.state = behavior.resting_state.clone();
+            None
+        }
+
+        AIState::Guarding{post} => {
+            let post = *post;
+            if here != post { step_towards(here, post, map) } else { None }
+        }
+
+        AIState::Patrolling{route, next} => {
+            if route.len() < 3 {
+                // Pick some more places to visit on our rounds
+                let x = rng.roll_dice(1, map.width-2);
+                let y = rng.roll_dice(1, map.height-2);
+                let idx = map.xy_idx(x, y);
+                if map.walkable(idx) && safe_footing(map, idx, can_swim) { route.push(Point::new(x, y)); }
+                return None;
+            }
+            if here == route[*next] {
+                *next = (*next + 1) % route.len();
+            }
+            let target = route[*next];
+            let step = step_towards(here, target, map).filter(|idx| safe_footing(map, *idx, can_swim));
+            if step.is_none() {
+                // Unreachable waypoint - skip it
+                *next = (*next + 1) % route.len();
+            }
+            step
+        }
+
+        AIState::Searching{target, turns} => {
+            *turns -= 1;
+            let step = if here != *target { step_towards(here, *target, map) } else { None };
+            let step = step.or_else(|| {
+                // Look around the area
+                let x = pos.x + rng.roll_dice(1, 3) - 2;
+                let y = pos.y + rng.roll_dice(1, 3) - 2;
+                if x > 0 && x < map.width-1 && y > 0 && y < map.height-1 { Some(map.xy_idx(x, y)) } else { None }
+            });
+            let step = step.filter(|idx| safe_footing(map, *idx, can_swim));
+            if *turns < 1 {
+                behavior.state = behavior.resting_state.clone();
+            }
+            step
+        }
+    }
+}
+
+fn step_towards(start : Point, end : Point, map : &Map) -> Option<usize> {
+    let path = a_star_search(map.xy_idx(start.x, start.y), map.xy_idx(end.x, end.y), map);
+    if path.success && path.steps.len()>1 { Some(path.steps[1]) } else { None }
+}
@@ -0,0 +1,285 @@
+use bracket_lib::prelude::Point;
+use specs::prelude::*;
+use crate::{Map, Position, Item, Door, TileType, LevelId};
+use std::collections::{BinaryHeap, HashSet};
+use std::cmp::Ordering;
+
+const UNREACHABLE : f32 = f32::MAX;
+const DIAGONAL_COST : f32 = 1.5;
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum DijkstraTarget { Player, AwayFromPlayer, Items, Exits }
+
+/// Level-wide distance maps shared by every monster. Each one is only rebuilt when the
+/// things it depends upon change.
+pub struct DijkstraMaps {
+    to_player : Vec<f32>,
+    from_player : Vec<f32>,
+    to_items : Vec<f32>,
+    to_exits : Vec<f32>,
+    width : i32,
+    player_key : (LevelId, u32, usize, Vec<usize>),
+    items_key : (LevelId, u32, Vec<usize>, Vec<usize>),
+    exits_key : (LevelId, u32, Vec<usize>)
+}
+
+impl DijkstraMaps {
+    pub fn new() -> DijkstraMaps {
+        DijkstraMaps{
+            to_player : Vec::new(),
+            from_player : Vec::new(),
+            to_items : Vec::new(),
+            to_exits : Vec::new(),
+            width : 0,
+            player_key : (LevelId::default(), 0, 0, Vec::new()),
+            items_key : (LevelId::default(), 0, Vec::new(), Vec::new()),
+            exits_key : (LevelId::default(), 0, Vec::new())
+        }
+    }
+
+    fn grid(&self, target : DijkstraTarget) -> &Vec<f32> {
+        match target {
+            DijkstraTarget::Player => &self.to_player,
+            DijkstraTarget::AwayFromPlayer => &self.from_player,
+            DijkstraTarget::Items => &self.to_items,
+            DijkstraTarget::Exits => &self.to_exits
+        }
+    }
+
+    /// How far (in movement cost) a tile is from the nearest goal, if it can reach one at all.
+    pub fn distance(&self, target : DijkstraTarget, idx : usize) -> Option<f32> {
+        let grid = self.grid(target);
+        if idx < grid.len() && grid[idx] < UNREACHABLE { Some(grid[idx]) } else { None }
+    }
+
+    /// Rolls downhill from a tile, returning the best unoccupied neighbor that improves on it.
+    /// Anything that can't swim steers around deep water.
+    pub fn next_step(&self, target : DijkstraTarget, idx : usize, map : &Map, can_swim : bool) -> Option<usize> {
+        let grid = self.grid(target);
+        if idx >= grid.len() { return None; }
+        let mut best = (idx, grid[idx]);
+        for (neighbor, _cost) in neighbors(self.width, grid.len(), idx) {
+            // Nobody gets to walk into the player's square
+            if target == DijkstraTarget::Player && grid[neighbor] <= 0.0 { continue; }
+            if grid[neighbor] < best.1 && !crate::spatial::is_blocked(neighbor) && safe_footing(map, neighbor, can_swim) {
+                best = (neighbor, grid[neighbor]);
+            }
+        }
+        if best.0 != idx { Some(best.0) } else { None }
+    }
+}
+
+/// Deep water is no place to wander into for anything that can neither swim nor fly.
+pub fn safe_footing(map : &Map, idx : usize, can_swim : bool) -> bool {
+    can_swim || map.tiles[idx] != TileType::DeepWater
+}
+
+pub struct DijkstraMapSystem {}
+
+impl<'a> System<'a> for DijkstraMapSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        WriteExpect<'a, DijkstraMaps>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Door>
+    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (map, player_pos, mut maps, positions, items, doors) = data;
+
+        let mut closed_doors : Vec<usize> = (&positions, &doors).join()
+            .filter(|(_, door)| !door.open)
+            .map(|(pos, _)| map.xy_idx(pos.x, pos.y))
+            .collect();
+        // Traps that monsters know about are as good as a shut door
+        closed_doors.extend(crate::spatial::known_traps());
+        closed_doors.sort_unstable();
+        let mut item_tiles : Vec<usize> = (&positions, &items).join()
+            .map(|(pos, _)| map.xy_idx(pos.x, pos.y))
+            .collect();
+        item_tiles.sort_unstable();
+        item_tiles.dedup();
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        let tile_count = map.tiles.len();
+        maps.width = map.width;
+
+        let player_key = (map.level_id(), map.revision, player_idx, closed_doors.clone());
+        if maps.player_key != player_key || maps.to_player.len() != tile_count {
+            maps.to_player = build(&map, &closed_doors, &[player_idx]);
+
+            // Fleeing is a walk towards wherever is furthest from the player, re-flowed so
+            // that monsters will happily run past the player to reach a distant exit
+            let mut from_player : Vec<f32> = maps.to_player.iter()
+                .map(|v| if *v < UNREACHABLE { *v * -1.2 } else { UNREACHABLE })
+                .collect();
+            relax(&map, &closed_doors, &mut from_player);
+            maps.from_player = from_player;
+            maps.player_key = player_key;
+        }
+
+        let items_key = (map.level_id(), map.revision, item_tiles.clone(), closed_doors.clone());
+        if maps.items_key != items_key || maps.to_items.len() != tile_count {
+            maps.to_items = build(&map, &closed_doors, &item_tiles);
+            maps.items_key = items_key;
+        }
+
+        let exits_key = (map.level_id(), map.revision, closed_doors.clone());
+        if maps.exits_key != exits_key || maps.to_exits.len() != tile_count {
+            let exits : Vec<usize> = map.tiles.iter().enumerate()
+                .filter(|(_, tt)| **tt == TileType::DownStairs || **tt == TileType::UpStairs)
+                .map(|(idx, _)| idx)
+                .collect();
+            maps.to_exits = build(&map, &closed_doors, &exits);
+            maps.exits_key = exits_key;
+        }
+    }
+}
+
+fn build(map : &Map, closed_doors : &[usize], starts : &[usize]) -> Vec<f32> {
+    let mut values = vec![UNREACHABLE; map.tiles.len()];
+    for start in starts.iter() {
+        if *start < values.len() { values[*start] = 0.0; }
+    }
+    relax(map, closed_doors, &mut values);
+    values
+}
+
+#[derive(PartialEq)]
+struct Node { cost : f32, idx : usize }
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the heap gives us the cheapest node first
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Runs Dijkstra's algorithm outward from every tile that already has a value.
+fn relax(map : &Map, closed_doors : &[usize], values : &mut [f32]) {
+    let blocked : HashSet<usize> = closed_doors.iter().cloned().collect();
+    let mut open : BinaryHeap<Node> = values.iter().enumerate()
+        .filter(|(_, v)| **v < UNREACHABLE)
+        .map(|(idx, v)| Node{ cost: *v, idx })
+        .collect();
+
+    while let Some(node) = open.pop() {
+        if node.cost > values[node.idx] { continue; }
+        for (neighbor, step) in neighbors(map.width, map.tiles.len(), node.idx) {
+            if !map.walkable(neighbor) || blocked.contains(&neighbor) { continue; }
+            // Moving from the neighbor onto this tile costs whatever leaving the neighbor does
+            let cost = node.cost + map.cost(neighbor) * step;
+            if cost < values[neighbor] {
+                values[neighbor] = cost;
+                open.push(Node{ cost, idx: neighbor });
+            }
+        }
+    }
+}
+
+fn neighbors(width : i32, tile_count : usize, idx : usize) -> Vec<(usize, f32)> {
+    let mut result = Vec::new();
+    if width < 1 { return result; }
+    let height = tile_count as i32 / width;
+    let x = idx as i32 % width;
+    let y = idx as i32 / width;
+    for dy in -1 ..= 1 {
+        for dx in -1 ..= 1 {
+            if dx == 0 && dy == 0 { continue; }
+            let nx = x + dx;
+            let ny = y + dy;
+            if nx < 1 || nx > width-1 || ny < 1 || ny > height-1 { continue; }
+            let step = if dx != 0 && dy != 0 { DIAGONAL_COST } else { 1.0 };
+            result.push(((ny * width + nx) as usize, step));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A walled room with open floor inside.
+    fn room() -> Map {
+        let mut map = Map::new(1, 10, 10, "Test");
+        for y in 1 .. 9 {
+            for x in 1 .. 9 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn relax_spreads_out_from_the_start() {
+        let map = room();
+        let start = map.xy_idx(1, 1);
+        let values = build(&map, &[], &[start]);
+        assert_eq!(values[start], 0.0);
+        assert_eq!(values[map.xy_idx(2, 1)], 1.0);
+        assert_eq!(values[map.xy_idx(2, 2)], DIAGONAL_COST);
+        assert_eq!(values[map.xy_idx(8, 1)], 7.0);
+        assert_eq!(values[map.xy_idx(0, 0)], UNREACHABLE);
+    }
+
+    #[test]
+    fn relax_keeps_the_cheapest_of_several_starts() {
+        let map = room();
+        let values = build(&map, &[], &[map.xy_idx(1, 4), map.xy_idx(8, 4)]);
+        assert_eq!(values[map.xy_idx(2, 4)], 1.0);
+        assert_eq!(values[map.xy_idx(7, 4)], 1.0);
+    }
+
+    #[test]
+    fn relax_does_not_pass_closed_doors() {
+        let mut map = room();
+        for y in 1 .. 9 {
+            let idx = map.xy_idx(4, y);
+            map.tiles[idx] = TileType::Wall;
+        }
+        let door = map.xy_idx(4, 4);
+        map.tiles[door] = TileType::Floor;
+        let start = map.xy_idx(1, 4);
+
+        let open = build(&map, &[], &[start]);
+        assert_eq!(open[map.xy_idx(7, 4)], 6.0);
+        let closed = build(&map, &[door], &[start]);
+        assert_eq!(closed[map.xy_idx(7, 4)], UNREACHABLE);
+    }
+
+    #[test]
+    fn next_step_keeps_non_swimmers_out_of_deep_water() {
+        let mut map = room();
+        for y in 1 .. 9 {
+            let idx = map.xy_idx(2, y);
+            map.tiles[idx] = TileType::DeepWater;
+        }
+        let mut maps = DijkstraMaps::new();
+        maps.width = map.width;
+        maps.to_player = build(&map, &[], &[map.xy_idx(1, 4)]);
+
+        let start = map.xy_idx(3, 4);
+        let step = maps.next_step(DijkstraTarget::Player, start, &map, true).unwrap();
+        assert!(map.tiles[step] == TileType::DeepWater);
+        assert_eq!(maps.next_step(DijkstraTarget::Player, start, &map, false), None);
+    }
+
+    #[test]
+    fn neighbors_stay_off_the_map_edge() {
+        assert_eq!(neighbors(10, 100, 11).len(), 3);
+        assert_eq!(neighbors(10, 100, 55).len(), 8);
+        assert!(neighbors(0, 0, 0).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use specs::prelude::*;
-use crate::{EquipmentChanged, Item, InBackpack, Equipped, Pools, Attributes, gamelog::GameLog, AttributeBonus, gamesystem::attr_bonus, StatusEffect, Slow};
+use crate::{EquipmentChanged, Item, InBackpack, Equipped, Pools, Attributes, gamelog::{GameLog, LogCategory}, AttributeBonus, gamesystem::{attr_bonus, stealth_value, perception_value},
+    StatusEffect, Slow, Wearable, Skills, Stealth, Perception};
 use std::collections::HashMap;
 
 pub struct EncumbranceSystem {}
@@ -19,18 +20,24 @@ impl<'a> System<'a> for EncumbranceSystem {
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, AttributeBonus>,
         ReadStorage<'a, StatusEffect>,
-        ReadStorage<'a, Slow>
+        ReadStorage<'a, Slow>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, Skills>,
+        WriteStorage<'a, Stealth>,
+        WriteStorage<'a, Perception>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut equip_dirty, entities, items, backpacks, wielded,
-            mut pools, mut attributes, player, mut gamelog, attrbonus, statuses, slowed) = data;
+            mut pools, mut attributes, player, mut gamelog, attrbonus, statuses, slowed,
+            wearables, skills, mut stealth, mut perception) = data;
 
         if equip_dirty.is_empty() { return; }
 
         struct ItemUpdate {
             weight : f32,
             initiative : f32,
+            armor_weight : f32,
             might : i32,
             fitness : i32,
             quickness : i32,
@@ -40,7 +47,7 @@ impl<'a> System<'a> for EncumbranceSystem {
         // Build the map of who needs updating
         let mut to_update : HashMap<Entity, ItemUpdate> = HashMap::new(); // (weight, intiative)
         for (entity, _dirty) in (&entities, &equip_dirty).join() {
-            to_update.insert(entity, ItemUpdate{ weight: 0.0, initiative: 0.0, might: 0, fitness: 0, quickness: 0, intelligence: 0 });
+            to_update.insert(entity, ItemUpdate{ weight: 0.0, initiative: 0.0, armor_weight: 0.0, might: 0, fitness: 0, quickness: 0, intelligence: 0 });
         }
 
         // Remove all dirty statements
@@ -52,6 +59,9 @@ impl<'a> System<'a> for EncumbranceSystem {
                 let totals = to_update.get_mut(&equipped.owner).unwrap();
                 totals.weight += item.weight_lbs;
                 totals.initiative += item.initiative_penalty;
+                if wearables.get(entity).is_some() {
+                    totals.armor_weight += item.weight_lbs;
+                }
                 if let Some(attr) = attrbonus.get(entity) {
                     totals.might += attr.might.unwrap_or(0);
                     totals.fitness += attr.fitness.unwrap_or(0);
@@ -105,12 +115,19 @@ impl<'a> System<'a> for EncumbranceSystem {
                     attr.quickness.bonus = attr_bonus(attr.quickness.base + attr.quickness.modifiers);
                     attr.intelligence.bonus = attr_bonus(attr.intelligence.base + attr.intelligence.modifiers);
 
+                    if let (Some(stealth), Some(skills)) = (stealth.get_mut(*entity), skills.get(*entity)) {
+                        stealth.value = stealth_value(skills, attr.quickness.bonus, item.armor_weight);
+                    }
+                    if let (Some(perception), Some(skills)) = (perception.get_mut(*entity), skills.get(*entity)) {
+                        perception.value = perception_value(skills, attr.intelligence.bonus);
+                    }
+
                     let carry_capacity_lbs = (attr.might.base + attr.might.modifiers) * 15;
                     if pool.total_weight as i32 > carry_capacity_lbs {
                         // Overburdened
                         pool.total_initiative_penalty += 4.0;
                         if *entity == *player {
-                            gamelog.entries.push("You are overburdened, and suffering an initiative penalty.".to_string());
+                            gamelog.log(LogCategory::Status, "You are overburdened, and suffering an initiative penalty.");
                         }
                     }
                 }
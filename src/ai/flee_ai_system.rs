@@ -1,6 +1,8 @@
-use bracket_lib::prelude::DijkstraMap;
+use bracket_lib::prelude::{DijkstraMap, Point};
 use specs::prelude::*;
-use crate::{MyTurn, WantsToFlee, Position, Map, ApplyMove};
+use crate::{MyTurn, WantsToFlee, Position, Map, ApplyMove, Routed, StatusEffect, Swimmer, Flying};
+use super::{DijkstraMaps, DijkstraTarget, safe_footing};
+use std::collections::HashSet;
 
 pub struct FleeAI {}
 
@@ -12,28 +14,49 @@ impl<'a> System<'a> for FleeAI {
         WriteStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
-        WriteStorage<'a, ApplyMove>
+        WriteStorage<'a, ApplyMove>,
+        ReadExpect<'a, DijkstraMaps>,
+        ReadExpect<'a, Point>,
+        ReadStorage<'a, Routed>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, Swimmer>,
+        ReadStorage<'a, Flying>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut turns, mut want_flee, positions, mut map,
-            entities, mut apply_move) = data;
+            entities, mut apply_move, dijkstra, player_pos, routed, statuses, swimmers, flying) = data;
 
+        let mut routed_entities : HashSet<Entity> = HashSet::new();
+        for (status, _routed) in (&statuses, &routed).join() {
+            routed_entities.insert(status.target);
+        }
+
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, pos, flee, _myturn) in
             (&entities, &positions, &want_flee, &turns).join()
         {
             turn_done.push(entity);
             let my_idx = map.xy_idx(pos.x, pos.y);
+            let can_swim = swimmers.get(entity).is_some() || flying.get(entity).is_some();
+            let flee_target = if routed_entities.contains(&entity) && dijkstra.distance(DijkstraTarget::Exits, my_idx).is_some() {
+                // A broken pack makes for the nearest way out
+                dijkstra.next_step(DijkstraTarget::Exits, my_idx, &map, can_swim)
+                    .or_else(|| dijkstra.next_step(DijkstraTarget::AwayFromPlayer, my_idx, &map, can_swim))
+            } else if flee.indices.contains(&player_idx) {
+                dijkstra.next_step(DijkstraTarget::AwayFromPlayer, my_idx, &map, can_swim)
+            } else {
+                // Running from something other than the player needs its own map
                 map.populate_blocked();
                 let flee_map = DijkstraMap::new(map.width as usize, map.height as usize, &flee.indices, &*map, 100.0);
-                let flee_target = DijkstraMap::find_highest_exit(&flee_map, my_idx, &*map);
-                if let Some(flee_target) = flee_target {
-                    if !crate::spatial::is_blocked(flee_target as usize) {
-                        apply_move.insert(entity, ApplyMove{ dest_idx : flee_target }).expect("Unable to insert");
-                        turn_done.push(entity);
-                    }
+                DijkstraMap::find_highest_exit(&flee_map, my_idx, &*map)
+            };
+            if let Some(flee_target) = flee_target {
+                if !crate::spatial::is_blocked(flee_target) && safe_footing(&map, flee_target, can_swim) {
+                    apply_move.insert(entity, ApplyMove{ dest_idx : flee_target }).expect("Unable to insert");
                 }
+            }
         }
 
         want_flee.clear();
@@ -1,6 +1,7 @@
 use bracket_lib::prelude::{RandomNumberGenerator, Point, DistanceAlg};
 use specs::prelude::*;
-use crate::{Initiative, Position, MyTurn, Attributes, RunState, Pools, Duration, EquipmentChanged, StatusEffect, DamageOverTime};
+use crate::{Initiative, Position, MyTurn, Attributes, RunState, Pools, Duration, EquipmentChanged, StatusEffect, DamageOverTime,
+    gamelog::GameLog};
 
 pub struct InitiativeSystem {}
 
@@ -19,13 +20,14 @@ impl<'a> System<'a> for InitiativeSystem {
                         WriteStorage<'a, Duration>,
                         WriteStorage<'a, EquipmentChanged>,
                         ReadStorage<'a, StatusEffect>,
-                        ReadStorage<'a, DamageOverTime>
+                        ReadStorage<'a, DamageOverTime>,
+                        WriteExpect<'a, GameLog>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut initiatives, positions, mut turns, entities, mut rng, attributes,
             mut runstate, player, player_pos, pools, mut durations, mut dirty,
-            statuses, dots) = data;
+            statuses, dots, mut gamelog) = data;
 
         if *runstate != RunState::Ticking { return; }
 
@@ -74,6 +76,7 @@ impl<'a> System<'a> for InitiativeSystem {
 
         // Handle durations
         if *runstate == RunState::AwaitingInput {
+            gamelog.next_turn();
             use crate::effects::*;
             for (effect_entity, duration, status) in (&entities, &mut durations, &statuses).join() {
                 if entities.is_alive(status.target) {
@@ -0,0 +1,170 @@
+use bracket_lib::prelude::{DistanceAlg, Point};
+use specs::prelude::*;
+use crate::{MyTurn, Position, Map, Pools, ItemUser, Item, InBackpack, Equipped, Equippable, MeleeWeapon,
+    Wearable, NaturalAttackDefense, CursedItem, ProvidesHealing, Ranged, InflictsDamage, Confusion,
+    Chasing, Viewshed, WantsToUseItem, WantsToPickupItem, ApplyMove, Name, MagicItem, ObfuscatedName,
+    MasterDungeonMap, EquipmentSlot, Swimmer, Flying, gamelog::{GameLog, LogCategory}};
+use super::{DijkstraMaps, DijkstraTarget};
+
+pub struct ItemUseAI {}
+
+impl<'a> System<'a> for ItemUseAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, ItemUser>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Equippable>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        ReadStorage<'a, CursedItem>,
+        (ReadStorage<'a, ProvidesHealing>, ReadStorage<'a, Ranged>, ReadStorage<'a, InflictsDamage>, ReadStorage<'a, Confusion>),
+        ReadStorage<'a, Chasing>,
+        ReadStorage<'a, Viewshed>,
+        WriteStorage<'a, WantsToUseItem>,
+        WriteStorage<'a, WantsToPickupItem>,
+        WriteStorage<'a, ApplyMove>,
+        ReadExpect<'a, DijkstraMaps>,
+        (ReadStorage<'a, Name>, ReadStorage<'a, MagicItem>, ReadStorage<'a, ObfuscatedName>, ReadExpect<'a, MasterDungeonMap>),
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        (ReadStorage<'a, Swimmer>, ReadStorage<'a, Flying>)
+    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut turns, item_users, positions, map, pools, items, backpacks, equipped, equippable,
+            weapons, wearables, natural, cursed, (healing, ranged, damage, confusion), chasing, viewsheds,
+            mut wants_use, mut wants_pickup, mut apply_move, dijkstra, (names, magic_items, obfuscated_names, dm),
+            mut gamelog, entities, (swimmers, flying)) = data;
+
+        let mut turn_done : Vec<Entity> = Vec::new();
+        for (entity, user, pos, pool, _turn) in (&entities, &item_users, &positions, &pools, &turns).join() {
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let carried : Vec<Entity> = (&entities, &backpacks).join()
+                .filter(|(_, pack)| pack.owner == entity)
+                .map(|(item, _)| item)
+                .collect();
+            let visible = map.visible_tiles[my_idx];
+            let item_name = |item : Entity| crate::inventory_system::obfuscate_name(item, &names, &magic_items, &obfuscated_names, &dm);
+            let my_name = names.get(entity).map(|n| n.name.clone()).unwrap_or_else(|| "Something".to_string());
+
+            // Drink a healing potion when badly hurt
+            if user.potions && pool.hit_points.current < pool.hit_points.max / 3 {
+                if let Some(potion) = carried.iter().find(|item| healing.get(**item).is_some()) {
+                    wants_use.insert(entity, WantsToUseItem{ item: *potion, target: None }).expect("Unable to insert");
+                    if visible { gamelog.log(LogCategory::Combat, format!("{} drinks the {}.", my_name, item_name(*potion))); }
+                    turn_done.push(entity);
+                    continue;
+                }
+            }
+
+            // Read an attack scroll at whatever we're after, if it isn't too close for comfort
+            if user.scrolls {
+                if let (Some(chase), Some(viewshed)) = (chasing.get(entity), viewsheds.get(entity)) {
+                    if let Some(target_pos) = positions.get(chase.target) {
+                        let target = Point::new(target_pos.x, target_pos.y);
+                        let distance = DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), target);
+                        let can_see = viewshed.visible_tiles.contains(&target);
+                        let scroll = carried.iter().find(|item| {
+                            if let Some(range) = ranged.get(**item) {
+                                (damage.get(**item).is_some() || confusion.get(**item).is_some()) && distance <= range.range as f32
+                            } else {
+                                false
+                            }
+                        });
+                        if let (true, true, Some(scroll)) = (can_see, distance > 1.5, scroll) {
+                            wants_use.insert(entity, WantsToUseItem{ item: *scroll, target: Some(target) }).expect("Unable to insert");
+                            if visible { gamelog.log(LogCategory::Combat, format!("{} reads the {}.", my_name, item_name(*scroll))); }
+                            turn_done.push(entity);
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            // Everything else can wait until there's nothing to fight
+            if chasing.get(entity).is_some() { continue; }
+
+            if user.equip {
+                let upgrade = carried.iter().find(|item| {
+                    if let Some(slot) = equippable.get(**item) {
+                        let current = (&entities, &equipped).join()
+                            .find(|(_, eq)| eq.owner == entity && eq.slot == slot.slot)
+                            .map(|(e, _)| e);
+                        if let Some(current) = current {
+                            if cursed.get(current).is_some() { return false; }
+                        }
+                        let score = |e : Entity| -> f32 {
+                            if let Some(weapon) = weapons.get(e) { weapon_score(weapon) }
+                            else if let Some(armor) = wearables.get(e) { armor.armor_class }
+                            else { 0.0 }
+                        };
+                        let current_score = match current {
+                            Some(current) => score(current),
+                            None if slot.slot == EquipmentSlot::Melee => unarmed_score(natural.get(entity)),
+                            None => 0.0
+                        };
+                        score(**item) > current_score
+                    } else {
+                        false
+                    }
+                });
+                if let Some(upgrade) = upgrade {
+                    wants_use.insert(entity, WantsToUseItem{ item: *upgrade, target: None }).expect("Unable to insert");
+                    if visible { gamelog.log(LogCategory::Loot, format!("{} equips the {}.", my_name, item_name(*upgrade))); }
+                    turn_done.push(entity);
+                    continue;
+                }
+            }
+
+            if user.pickup {
+                let mut here : Option<Entity> = None;
+                crate::spatial::for_each_tile_content(my_idx, |other| {
+                    if items.get(other).is_some() { here = Some(other); }
+                });
+                if let Some(item) = here {
+                    wants_pickup.insert(entity, WantsToPickupItem{ collected_by: entity, item }).expect("Unable to insert");
+                    if visible { gamelog.log(LogCategory::Loot, format!("{} picks up the {}.", my_name, item_name(item))); }
+                    turn_done.push(entity);
+                    continue;
+                }
+                // Go after anything shiny nearby
+                if let Some(distance) = dijkstra.distance(DijkstraTarget::Items, my_idx) {
+                    if distance < 8.0 {
+                        if let Some(dest_idx) = dijkstra.next_step(DijkstraTarget::Items, my_idx, &map, swimmers.get(entity).is_some() || flying.get(entity).is_some()) {
+                            apply_move.insert(entity, ApplyMove{ dest_idx }).expect("Unable to insert");
+                            turn_done.push(entity);
+                        }
+                    }
+                }
+            }
+        }
+
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
+
+fn weapon_score(weapon : &MeleeWeapon) -> f32 {
+    weapon.damage_n_dice as f32 * (weapon.damage_die_type as f32 + 1.0) / 2.0
+        + weapon.damage_bonus as f32 + weapon.hit_bonus as f32
+}
+
+fn unarmed_score(natural : Option<&NaturalAttackDefense>) -> f32 {
+    let mut best = 2.5; // A 1d4 punch
+    if let Some(natural) = natural {
+        for attack in natural.attacks.iter() {
+            let score = attack.damage_n_dice as f32 * (attack.damage_die_type as f32 + 1.0) / 2.0
+                + attack.damage_bonus as f32 + attack.hit_bonus as f32;
+            if score > best { best = score; }
+        }
+    }
+    best
+}
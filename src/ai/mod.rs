@@ -8,6 +8,9 @@ mod flee_ai_system;
 mod default_move_system;
 mod chase_ai_system;
 mod encumbrance_system;
+mod pack_ai_system;
+mod dijkstra_maps;
+mod item_use_ai_system;
 pub use initiative_system::InitiativeSystem;
 pub use turn_status::TurnStatusSystem;
 pub use quipping::QuipSystem;
@@ -18,3 +21,6 @@ pub use flee_ai_system::FleeAI;
 pub use default_move_system::DefaultMoveAI;
 pub use chase_ai_system::ChaseAI;
 pub use encumbrance_system::EncumbranceSystem;
+pub use pack_ai_system::PackAI;
+pub use dijkstra_maps::{DijkstraMaps, DijkstraTarget, DijkstraMapSystem, safe_footing};
+pub use item_use_ai_system::ItemUseAI;
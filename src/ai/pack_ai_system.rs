@@ -0,0 +1,175 @@
+use bracket_lib::prelude::{DistanceAlg, Point};
+use specs::prelude::*;
+use crate::{MyTurn, Faction, Position, Map, Chasing, WantsToApproach, Behavior, AIState, PackLeader,
+    PackMember, Routed, StatusEffect, Name, Pools, gamelog::{GameLog, LogCategory}, WantsToCastSpell, WantsToFlee};
+use crate::raws::{faction_hunts_in_packs, RAWS};
+use std::collections::HashSet;
+
+pub struct PackAI {}
+
+impl<'a> System<'a> for PackAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        WriteStorage<'a, Chasing>,
+        WriteStorage<'a, WantsToApproach>,
+        WriteStorage<'a, Behavior>,
+        ReadStorage<'a, PackLeader>,
+        WriteStorage<'a, PackMember>,
+        ReadStorage<'a, Routed>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Pools>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, WantsToCastSpell>,
+        ReadStorage<'a, WantsToFlee>
+    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (turns, factions, positions, map, mut chasing, mut want_approach, mut behaviors, leaders,
+            mut members, routed, statuses, names, pools, mut gamelog, entities, player, casting, fleeing) = data;
+
+        let mut routed_entities : HashSet<Entity> = HashSet::new();
+        for (status, _routed) in (&statuses, &routed).join() {
+            routed_entities.insert(status.target);
+        }
+
+        // Everyone in a pack faction, so we don't keep asking the raws
+        let mut pack : Vec<(Entity, String, Point)> = Vec::new();
+        {
+            let raws = RAWS.lock().unwrap();
+            for (entity, faction, pos, pool) in (&entities, &factions, &positions, &pools).join() {
+                if entity != *player && pool.hit_points.current > 0 && faction_hunts_in_packs(&faction.name, &raws) {
+                    pack.push((entity, faction.name.clone(), Point::new(pos.x, pos.y)));
+                }
+            }
+        }
+
+        // Followers join up with the nearest leader of their faction
+        for (entity, faction, pos) in pack.iter() {
+            if leaders.get(*entity).is_some() || members.get(*entity).is_some() || routed_entities.contains(entity) { continue; }
+            let leader = pack.iter()
+                .filter(|(other, other_faction, _)| leaders.get(*other).is_some() && other_faction == faction)
+                .map(|(other, _, other_pos)| (*other, DistanceAlg::Pythagoras.distance2d(*pos, *other_pos)))
+                .filter(|(_, distance)| *distance < 12.0)
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            if let Some((leader, _)) = leader {
+                members.insert(*entity, PackMember{ leader }).expect("Unable to insert");
+            }
+        }
+
+        // Anyone who is hunting raises the alarm with nearby allies, and leaders call in their whole pack
+        let mut alerts : Vec<(Entity, Entity)> = Vec::new();
+        for (entity, faction, pos) in pack.iter() {
+            if let Some(chase) = chasing.get(*entity) {
+                for (ally, ally_faction, ally_pos) in pack.iter() {
+                    if ally == entity || ally_faction != faction || routed_entities.contains(ally) || chasing.get(*ally).is_some() {
+                        continue;
+                    }
+                    let is_follower = if let Some(member) = members.get(*ally) { member.leader == *entity } else { false };
+                    if is_follower || DistanceAlg::Pythagoras.distance2d(*pos, *ally_pos) < 8.0 {
+                        alerts.push((*ally, chase.target));
+                    }
+                }
+                if !alerts.is_empty() && map.visible_tiles[map.xy_idx(pos.x, pos.y)] {
+                    if let Some(name) = names.get(*entity) {
+                        gamelog.log(LogCategory::Combat, format!("{} shouts a warning!", name.name));
+                    }
+                }
+            }
+            for (ally, target) in alerts.drain(..) {
+                chasing.insert(ally, Chasing{ target }).expect("Unable to insert");
+                if let Some(behavior) = behaviors.get_mut(ally) {
+                    behavior.state = AIState::Hunting;
+                }
+            }
+        }
+
+        // Pack members who are acting this turn try to surround their prey, or stick close to the leader
+        for (entity, faction, pos) in pack.iter() {
+            if turns.get(*entity).is_none() || routed_entities.contains(entity) { continue; }
+            if casting.get(*entity).is_some() || fleeing.get(*entity).is_some() { continue; }
+
+            if let Some(chase) = chasing.get(*entity) {
+                if let Some(target_pos) = positions.get(chase.target) {
+                    let target = Point::new(target_pos.x, target_pos.y);
+                    let distance = DistanceAlg::Pythagoras.distance2d(*pos, target);
+                    if distance > 1.5 && distance < 8.0 {
+                        let allies : Vec<Point> = pack.iter()
+                            .filter(|(other, other_faction, _)| other != entity && other_faction == faction)
+                            .map(|(_, _, other_pos)| *other_pos)
+                            .collect();
+                        if let Some(idx) = flanking_tile(&map, *pos, target, &allies) {
+                            want_approach.insert(*entity, WantsToApproach{ idx: idx as i32 }).expect("Unable to insert");
+                        }
+                    }
+                }
+            } else if let Some(member) = members.get(*entity) {
+                if let Some(leader_pos) = positions.get(member.leader) {
+                    let leader = Point::new(leader_pos.x, leader_pos.y);
+                    if DistanceAlg::Pythagoras.distance2d(*pos, leader) > 4.0 {
+                        if let Some(idx) = flanking_tile(&map, *pos, leader, &[]) {
+                            want_approach.insert(*entity, WantsToApproach{ idx: idx as i32 }).expect("Unable to insert");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Picks an open tile next to the target, preferring ones that aren't already covered by an ally
+/// and then the one closest to us.
+fn flanking_tile(map : &Map, me : Point, target : Point, allies : &[Point]) -> Option<usize> {
+    let mut best : Option<(usize, f32)> = None;
+    for y in target.y-1 ..= target.y+1 {
+        for x in target.x-1 ..= target.x+1 {
+            if x < 1 || x > map.width-2 || y < 1 || y > map.height-2 || (x == target.x && y == target.y) { continue; }
+            let idx = map.xy_idx(x, y);
+            if crate::spatial::is_blocked(idx) { continue; }
+            let tile = Point::new(x, y);
+            let crowding = allies.iter()
+                .filter(|ally| DistanceAlg::Pythagoras.distance2d(**ally, tile) < 1.5)
+                .count() as f32;
+            let score = crowding * 10.0 + DistanceAlg::Pythagoras.distance2d(me, tile);
+            if best.is_none() || score < best.unwrap().1 {
+                best = Some((idx, score));
+            }
+        }
+    }
+    best.map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flanking_tile_picks_the_nearest_side() {
+        let map = Map::new(1, 10, 10, "Test");
+        let tile = flanking_tile(&map, Point::new(2, 5), Point::new(5, 5), &[]);
+        assert_eq!(tile, Some(map.xy_idx(4, 5)));
+    }
+
+    #[test]
+    fn flanking_tile_avoids_sides_allies_hold() {
+        let map = Map::new(1, 10, 10, "Test");
+        let tile = flanking_tile(&map, Point::new(2, 5), Point::new(5, 5), &[Point::new(4, 5)]).unwrap();
+        let (x, y) = (tile as i32 % map.width, tile as i32 / map.width);
+        assert!(x == 6 || (x == 5 && (y == 4 || y == 6)));
+        assert!(DistanceAlg::Pythagoras.distance2d(Point::new(x, y), Point::new(5, 5)) < 1.5);
+    }
+
+    #[test]
+    fn flanking_tile_stays_off_the_map_edge() {
+        let map = Map::new(1, 10, 10, "Test");
+        let tile = flanking_tile(&map, Point::new(0, 0), Point::new(1, 1), &[]).unwrap();
+        let (x, y) = (tile as i32 % map.width, tile as i32 / map.width);
+        assert!(x >= 1 && y >= 1);
+    }
+}
@@ -1,6 +1,6 @@
-use bracket_lib::prelude::{Point, RandomNumberGenerator};
+use bracket_lib::prelude::{Point, RandomNumberGenerator, RGB, YELLOW};
 use specs::prelude::*;
-use crate::{gamelog::GameLog, Quips, Name, MyTurn, Viewshed};
+use crate::{gamelog::{GameLog, LogCategory}, Quips, Name, MyTurn, Viewshed};
 
 pub struct QuipSystem {}
 
@@ -23,9 +23,10 @@ impl<'a> System<'a> for QuipSystem {
                     if quip.available.len() == 1 { 0 }
                     else { (rng.roll_dice(1, quip.available.len() as i32)-1) as usize };
 
-                gamelog.entries.push(
-                    format!("{} says \"{}\"", name.name, quip.available[quip_index])
-                );
+                gamelog.line(LogCategory::World)
+                    .colored(&name.name, RGB::named(YELLOW))
+                    .append(format!(" says \"{}\"", quip.available[quip_index]))
+                    .log();
                 quip.available.remove(quip_index);
             }
         }
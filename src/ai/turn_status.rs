@@ -1,28 +1,44 @@
 use specs::prelude::*;
-use crate::{MyTurn, Confusion, RunState, StatusEffect};
+use crate::{MyTurn, Confusion, Paralysis, RunState, StatusEffect, Behavior, AIState, Viewshed, Perception, Stealth};
 use std::collections::HashSet;
 use crate::effects::{add_effect, EffectType, Targets};
 use bracket_lib::prelude::*;
 
 pub struct TurnStatusSystem {}
 
+/// Sleepers that can see the player roll their perception against this, plus the player's
+/// stealth, to wake up.
+const WAKE_DIFFICULTY : i32 = 15;
+
 impl<'a> System<'a> for TurnStatusSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = ( WriteStorage<'a, MyTurn>,
                         ReadStorage<'a, Confusion>,
                         Entities<'a>,
-                        ReadExpect<'a, RunState>,
-                        ReadStorage<'a, StatusEffect>
+                        WriteExpect<'a, RunState>,
+                        ReadStorage<'a, StatusEffect>,
+                        WriteStorage<'a, Behavior>,
+                        ReadStorage<'a, Paralysis>,
+                        ReadExpect<'a, Entity>,
+                        ReadExpect<'a, Point>,
+                        ReadStorage<'a, Viewshed>,
+                        ReadStorage<'a, Perception>,
+                        ReadStorage<'a, Stealth>,
+                        WriteExpect<'a, RandomNumberGenerator>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, confusion, entities, runstate, statuses) = data;
+        let (mut turns, confusion, entities, mut runstate, statuses, mut behaviors, paralysis, player,
+            player_pos, viewsheds, perception, stealth, mut rng) = data;
 
-        if *runstate != RunState::Ticking { return; }
+        if *runstate != RunState::Ticking && *runstate != RunState::AwaitingInput { return; }
+        // The player's own turn is handled below, once everyone else has been dealt with
+        let awaiting_input = *runstate == RunState::AwaitingInput;
 
         // Collect a set of all entities whose turn it is
         let mut entity_turns = HashSet::new();
         for (entity, _turn) in (&entities, &turns).join() {
+            if awaiting_input && entity == *player { continue; }
             entity_turns.insert(entity);
         }
 
@@ -44,11 +60,33 @@ impl<'a> System<'a> for TurnStatusSystem {
                     );
                     not_my_turn.push(status_effect.target);
                 }
+                // Paralysis is the same, but with nothing to show for it
+                if paralysis.get(effect_entity).is_some() {
+                    not_my_turn.push(status_effect.target);
+                }
+            }
+        }
+
+        // Sleeping entities don't do anything until something wakes them. Catching sight of
+        // the player might, but waking up still takes the turn.
+        let player_stealth = stealth.get(*player).map(|s| s.value).unwrap_or(0);
+        for (entity, behavior) in (&entities, &mut behaviors).join() {
+            if !entity_turns.contains(&entity) || behavior.state != AIState::Asleep { continue; }
+            not_my_turn.push(entity);
+            let sees_player = viewsheds.get(entity).is_some_and(|v| v.visible_tiles.contains(&*player_pos));
+            let alertness = perception.get(entity).map(|p| p.value).unwrap_or(0);
+            if sees_player && rng.roll_dice(1, 20) + alertness > WAKE_DIFFICULTY + player_stealth {
+                behavior.state = behavior.resting_state.clone();
             }
         }
 
         for e in not_my_turn {
             turns.remove(e);
         }
+
+        // A paralyzed player doesn't get to act either
+        if awaiting_input && (&statuses, &paralysis).join().any(|(status, _)| status.target == *player) {
+            *runstate = RunState::Ticking;
+        }
     }
-}
\ No newline at end of file
+}
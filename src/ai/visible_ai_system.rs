@@ -1,6 +1,8 @@
 use bracket_lib::prelude::{RandomNumberGenerator, DistanceAlg, Point};
 use specs::prelude::*;
-use crate::{MyTurn, Faction, Position, Map, raws::Reaction, Viewshed, WantsToFlee, WantsToApproach, Chasing, SpecialAbilities, WantsToCastSpell, Name, SpellTemplate};
+use crate::{MyTurn, Faction, Position, Map, raws::Reaction, Viewshed, WantsToFlee, WantsToApproach, Chasing, SpecialAbilities, WantsToCastSpell, Name, SpellTemplate, Stealth,
+    Behavior, AIState, Routed, StatusEffect};
+use std::collections::HashSet;
 
 pub struct VisibleAI {}
 
@@ -21,12 +23,21 @@ impl<'a> System<'a> for VisibleAI {
         WriteExpect<'a, RandomNumberGenerator>,
         WriteStorage<'a, WantsToCastSpell>,
         ReadStorage<'a, Name>,
-        ReadStorage<'a, SpellTemplate>
+        ReadStorage<'a, SpellTemplate>,
+        ReadStorage<'a, Stealth>,
+        WriteStorage<'a, Behavior>,
+        ReadStorage<'a, Routed>,
+        ReadStorage<'a, StatusEffect>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (turns, factions, positions, map, mut want_approach, mut want_flee, entities, player,
-            viewsheds, mut chasing, abilities, mut rng, mut casting, names, spells) = data;
+            viewsheds, mut chasing, abilities, mut rng, mut casting, names, spells, stealth, mut behaviors, routed, statuses) = data;
+
+        let mut routed_entities : HashSet<Entity> = HashSet::new();
+        for (status, _routed) in (&statuses, &routed).join() {
+            routed_entities.insert(status.target);
+        }
 
         for (entity, _turn, my_faction, pos, viewshed) in (&entities, &turns, &factions, &positions, &viewsheds).join() {
             if entity != *player {
@@ -42,8 +53,18 @@ impl<'a> System<'a> for VisibleAI {
 
                 let mut done = false;
                 for reaction in reactions.iter() {
-                    match reaction.1 {
+                    // A routed mob runs from anything it would otherwise fight
+                    let response = if reaction.1 == Reaction::Attack && routed_entities.contains(&entity) { Reaction::Flee } else { reaction.1 };
+                    match response {
                         Reaction::Attack => {
+                            // Stealthy targets have to be noticed before we react to them
+                            if let Some(stealth) = stealth.get(reaction.2) {
+                                let already_aware = if let Some(chase) = chasing.get(entity) { chase.target == reaction.2 } else { false };
+                                if !already_aware && rng.roll_dice(1, 20) <= 10 + stealth.value {
+                                    continue;
+                                }
+                            }
+
                             if let Some(abilities) = abilities.get(entity) {
                                 let range = DistanceAlg::Pythagoras.distance2d(
                                     Point::new(pos.x, pos.y),
@@ -60,6 +81,7 @@ impl<'a> System<'a> for VisibleAI {
                                                 spell : find_spell_entity_by_name(&ability.spell, &names, &spells, &entities).unwrap(),
                                                 target : Some(Point::new(reaction.0 as i32 % map.width, reaction.0 as i32 / map.width))}
                                         ).expect("Unable to insert");
+                                        chasing.insert(entity, Chasing{ target: reaction.2}).expect("Unable to insert");
                                         done = true;
                                     }
                                 }
@@ -80,6 +102,13 @@ impl<'a> System<'a> for VisibleAI {
 
                 if !done && !flee.is_empty() {
                     want_flee.insert(entity, WantsToFlee{ indices : flee }).expect("Unable to insert");
+                    if let Some(behavior) = behaviors.get_mut(entity) {
+                        behavior.state = AIState::Fleeing;
+                    }
+                } else if done {
+                    if let Some(behavior) = behaviors.get_mut(entity) {
+                        behavior.state = AIState::Hunting;
+                    }
                 }
             }
         }
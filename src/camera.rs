@@ -2,6 +2,7 @@ use specs::prelude::*;
 use super::{Map,Position,Renderable,Hidden};
 use bracket_lib::prelude::*;
 use crate::map::tile_glyph;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub fn get_screen_bounds(ecs: &World, _ctx:  &mut BTerm) -> (i32, i32, i32, i32) {
     let player_pos = ecs.fetch::<Point>();
@@ -19,6 +20,42 @@ pub fn get_screen_bounds(ecs: &World, _ctx:  &mut BTerm) -> (i32, i32, i32, i32)
     (min_x, max_x, min_y, max_y)
 }
 
+/// The map tile under the mouse, if the mouse is over the map at all.
+pub fn mouse_map_pos(ecs: &World, ctx: &mut BTerm) -> Option<Point> {
+    let (min_x, _max_x, min_y, _max_y) = get_screen_bounds(ecs, ctx);
+    let (mouse_x, mouse_y) = ctx.mouse_pos();
+    if !(1..=48).contains(&mouse_x) || !(1..=44).contains(&mouse_y) { return None; }
+
+    let map = ecs.fetch::<Map>();
+    let x = mouse_x + min_x - 1;
+    let y = mouse_y + min_y - 1;
+    if !(1..map.width-1).contains(&x) || !(1..map.height-1).contains(&y) { return None; }
+    Some(Point::new(x, y))
+}
+
+/// True only on the frame the left button goes down; `left_click` alone also fires when
+/// it is let go.
+pub fn left_clicked(ctx: &BTerm) -> bool {
+    ctx.left_click && INPUT.lock().is_mouse_button_pressed(0)
+}
+
+/// The terminal only says whether the right button is held, so its state is compared with
+/// the last frame's to pick out the moment it goes down.
+static RIGHT_BUTTON_HELD : AtomicBool = AtomicBool::new(false);
+static RIGHT_CLICKED : AtomicBool = AtomicBool::new(false);
+
+/// Called once at the start of every frame, whatever state the game is in.
+pub fn update_mouse_buttons() {
+    let held = INPUT.lock().is_mouse_button_pressed(1);
+    let was_held = RIGHT_BUTTON_HELD.swap(held, Ordering::Relaxed);
+    RIGHT_CLICKED.store(held && !was_held, Ordering::Relaxed);
+}
+
+/// True only on the frame the right button goes down.
+pub fn right_clicked() -> bool {
+    RIGHT_CLICKED.load(Ordering::Relaxed)
+}
+
 const SHOW_BOUNDARIES : bool = false;
 
 pub fn render_camera(ecs: &World, ctx : &mut BTerm) {
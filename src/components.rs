@@ -5,8 +5,9 @@ use bracket_lib::prelude::{RGB};
 use serde::{Serialize, Deserialize};
 use specs::saveload::{Marker, ConvertSaveload};
 use specs::error::NoError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use bracket_lib::geometry::Point;
+use crate::map::{GasType, LevelId};
 
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Position {
@@ -18,7 +19,7 @@ pub struct Position {
 pub struct OtherLevelPosition {
     pub x: i32,
     pub y: i32,
-    pub depth: i32
+    pub level: LevelId
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -50,6 +51,14 @@ pub struct Initiative {
     pub current : i32
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ItemUser {
+    pub pickup : bool,
+    pub equip : bool,
+    pub potions : bool,
+    pub scrolls : bool
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Vendor {
     pub categories : Vec<String>
@@ -72,7 +81,7 @@ pub struct ApplyMove {
 pub struct ApplyTeleport {
     pub dest_x : i32,
     pub dest_y : i32,
-    pub dest_depth : i32
+    pub dest_level : LevelId
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -97,11 +106,34 @@ pub struct MoveMode {
     pub mode : Movement
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum AIState {
+    Asleep,
+    Wandering,
+    Guarding { post : Point },
+    Patrolling { route : Vec<Point>, next : usize },
+    Hunting,
+    Fleeing,
+    Searching { target : Point, turns : i32 }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Behavior {
+    pub state : AIState,
+    pub resting_state : AIState
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct Name {
     pub name : String
 }
 
+/// A line or two of flavor text, shown when the player takes a closer look.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Description {
+    pub text : String
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct ObfuscatedName {
     pub name : String
@@ -149,13 +181,61 @@ pub struct Attributes {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
-pub enum Skill { Melee, Defense, Magic }
+pub enum Skill { Melee, Defense, Magic, Stealth, Perception }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Skills {
     pub skills : HashMap<Skill, i32>
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Stealth {
+    pub value : i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Perception {
+    pub value : i32
+}
+
+/// Spends the next few turns looking for hidden things.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Searching {
+    pub turns : i32
+}
+
+/// How things stood on the player's last turn, so that a long walk can stop as soon as
+/// anything changes. Entities don't survive a save, so the sets come back empty and are
+/// taken afresh on the next turn.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Baseline {
+    pub hit_points : i32,
+    pub hunger : HungerState,
+    #[serde(skip)]
+    pub items_in_view : Option<HashSet<Entity>>,
+    #[serde(skip)]
+    pub statuses : Option<HashSet<Entity>>
+}
+
+/// The player is walking a path over several turns, one step each time they'd get to act.
+/// When exploring, the path is planned afresh every step.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Travelling {
+    pub path : Vec<usize>,
+    pub pick_up : bool,
+    pub blocked : bool,
+    pub exploring : bool,
+    pub baseline : Baseline
+}
+
+/// Letting the turns go by: until healed if there's no count, otherwise for that many turns.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Resting {
+    pub turns_left : Option<i32>,
+    pub turns_rested : i32,
+    pub baseline : Baseline
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToMelee {
     pub target : Entity
@@ -166,6 +246,17 @@ pub struct Chasing {
     pub target : Entity
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct PackLeader {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct PackMember {
+    pub leader : Entity
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Routed {}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct LootTable {
     pub table : String
@@ -263,6 +354,42 @@ pub struct Door {
     pub open: bool
 }
 
+/// A door that won't open without a key of the same kind.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Lock {
+    pub key : String
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Key {
+    pub id : String
+}
+
+/// Iron gates can only be moved by whatever mechanism they're hooked up to.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Portcullis {}
+
+/// Sends a signal down its channel every time it is pulled.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Lever {
+    pub channel : i32,
+    pub pulled : bool
+}
+
+/// Sends a signal when enough weight settles on it, and again when it's lifted.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct PressurePlate {
+    pub channel : i32,
+    pub min_weight : f32,
+    pub pressed : bool
+}
+
+/// Toggles whenever a signal arrives on its channel: doors open or shut, bridges raise or lower.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Mechanism {
+    pub channel : i32
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InBackpack {
     pub owner : Entity
@@ -293,6 +420,9 @@ pub struct WantsToRemoveItem {
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum EquipmentSlot { Melee, Shield, Head, Torso, Legs, Feet, Hands }
 
+pub const EQUIPMENT_SLOTS : [EquipmentSlot; 7] = [EquipmentSlot::Melee, EquipmentSlot::Shield, EquipmentSlot::Head,
+    EquipmentSlot::Torso, EquipmentSlot::Hands, EquipmentSlot::Legs, EquipmentSlot::Feet];
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Equippable {
     pub slot : EquipmentSlot
@@ -371,7 +501,7 @@ pub struct SpawnParticleBurst {
     pub lifetime_ms : f32
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
 pub enum HungerState { WellFed, Normal, Hungry, Starving }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -393,13 +523,21 @@ pub struct TownPortal {}
 pub struct TeleportTo {
     pub x: i32,
     pub y: i32,
-    pub depth: i32,
+    pub level: LevelId,
     pub player_only : bool
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Hidden {}
 
+/// The player has laid eyes on this item or vendor, so it shows up on their map of the level.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Remembered {}
+
+/// Sits on a wall tile until found, at which point the wall opens up.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SecretDoor {}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct EntryTrigger {}
 
@@ -446,6 +584,76 @@ pub struct DamageOverTime {
     pub damage : i32
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ignites {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OnFire {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ReleasesGas {
+    pub gas_type : GasType,
+    pub volume : i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Paralysis {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Swimmer {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Flying {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Levitation {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct DiggingTool {}
+
+/// Can be lobbed at a distant tile, where it breaks and takes effect.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Throwable {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Tunneler {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct DestroysTerrain {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InDeepWater {
+    pub turns : i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RandomTeleport {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Alarm {
+    pub radius : i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Entangles {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Entangled {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Disarmable {
+    pub difficulty : i32
+}
+
+/// A trap the player set: they know where it is, but the monsters don't.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Camouflaged {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct PlacesTrap {
+    pub trap : String
+}
+
 // Serialization helper code. We need to implement ConvertSaveLoad for each type that contains an
 // Entity.
 
@@ -1,6 +1,6 @@
 use specs::prelude::*;
-use super::{Pools, Player, Name, gamelog::GameLog, RunState, Position, InBackpack, Equipped, LootTable};
-use bracket_lib::prelude::RandomNumberGenerator;
+use super::{Pools, Player, Name, gamelog::{GameLog, LogCategory}, RunState, Position, LootTable};
+use bracket_lib::prelude::{RandomNumberGenerator, RGB, YELLOW};
 
 pub fn delete_the_dead(ecs : &mut World) {
     let mut dead : Vec<Entity> = Vec::new();
@@ -18,7 +18,7 @@ pub fn delete_the_dead(ecs : &mut World) {
                     None => {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
-                            log.entries.push(format!("{} is dead", &victim_name.name));
+                            log.line(LogCategory::Combat).colored(&victim_name.name, RGB::named(YELLOW)).append(" is dead").log();
                         }
                         dead.push(entity)
                     }
@@ -31,35 +31,14 @@ pub fn delete_the_dead(ecs : &mut World) {
         }
     }
 
-    // Drop everything held by dead people
+    // Roll for loot - their own belongings were dropped when they died
     let mut to_spawn : Vec<(String, Position)> = Vec::new();
     { // To avoid keeping hold of borrowed entries, use a scope
-        let mut to_drop : Vec<(Entity, Position)> = Vec::new();
-        let entities = ecs.entities();
-        let mut equipped = ecs.write_storage::<Equipped>();
-        let mut carried = ecs.write_storage::<InBackpack>();
-        let mut positions = ecs.write_storage::<Position>();
+        let positions = ecs.read_storage::<Position>();
         let loot_tables = ecs.read_storage::<LootTable>();
         let mut rng = ecs.write_resource::<RandomNumberGenerator>();
         for victim in dead.iter() {
             let pos = positions.get(*victim);
-            for (entity, equipped) in (&entities, &equipped).join() {
-                if equipped.owner == *victim {
-                    // Drop their stuff
-                    if let Some(pos) = pos {
-                        to_drop.push((entity, pos.clone()));
-                    }
-                }
-            }
-            for (entity, backpack) in (&entities, &carried).join() {
-                if backpack.owner == *victim {
-                    // Drop their stuff
-                    if let Some(pos) = pos {
-                        to_drop.push((entity, pos.clone()));
-                    }
-                }
-            }
-
             if let Some(table) = loot_tables.get(*victim) {
                 let drop_finder = crate::raws::get_item_drop(
                     &crate::raws::RAWS.lock().unwrap(),
@@ -73,12 +52,6 @@ pub fn delete_the_dead(ecs : &mut World) {
                 }
             }
         }
-
-        for drop in to_drop.iter() {
-            equipped.remove(drop.0);
-            carried.remove(drop.0);
-            positions.insert(drop.0, drop.1.clone()).expect("Unable to insert position");
-        }
     }
 
     {
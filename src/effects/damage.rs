@@ -1,12 +1,13 @@
 use specs::prelude::*;
 use super::*;
-use crate::components::{Pools, Player, Attributes, Confusion};
-use crate::map::Map;
+use crate::components::{Pools, Player, Attributes, Confusion, Paralysis, Position, Equipped, InBackpack};
+use crate::map::{Map, Surface};
 use crate::gamesystem::{player_hp_at_level, mana_at_level};
-use crate::gamelog::GameLog;
+use crate::gamelog::{GameLog, LogCategory};
 use bracket_lib::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use crate::{DamageOverTime, Duration, EquipmentChanged, Name, SerializeMe, Slow, StatusEffect};
+use crate::{DamageOverTime, Duration, EquipmentChanged, Name, SerializeMe, Slow, StatusEffect,
+    PackLeader, PackMember, Routed, Chasing, Behavior, AIState};
 
 pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
     let mut pools = ecs.write_storage::<Pools>();
@@ -35,10 +36,21 @@ pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
 
 pub fn bloodstain(ecs: &mut World, tile_idx : i32) {
     let mut map = ecs.fetch_mut::<Map>();
-    map.bloodstains.insert(tile_idx as usize);
+    let idx = tile_idx as usize;
+    // Blood doesn't stick to water, and won't cover up anything more interesting
+    if map.liquid[idx].is_none() && map.surface[idx].is_none() {
+        map.surface[idx] = Some(Surface::Blood);
+    }
 }
 
 pub fn death(ecs: &mut World, effect: &EffectSpawner, target : Entity) {
+    if ecs.read_storage::<PackLeader>().get(target).is_some() {
+        rout_pack(ecs, target);
+    }
+    if ecs.read_storage::<Player>().get(target).is_none() {
+        drop_inventory(ecs, target);
+    }
+
     let mut xp_gain = 0;
     let mut gold_gain = 0.0f32;
 
@@ -65,7 +77,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target : Entity) {
                 if player_stats.xp >= player_stats.level * 1000 {
                     // We've gone up a level!
                     player_stats.level += 1;
-                    log.entries.push(format!("Congratulations, you are now level {}", player_stats.level));
+                    log.log(LogCategory::Status, format!("Congratulations, you are now level {}", player_stats.level));
                     player_stats.hit_points.max = player_hp_at_level(
                         player_attributes.fitness.base + player_attributes.fitness.modifiers,
                         player_stats.level
@@ -98,6 +110,53 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target : Entity) {
     }
 }
 
+fn drop_inventory(ecs: &mut World, victim : Entity) {
+    let pos = if let Some(pos) = ecs.read_storage::<Position>().get(victim) { pos.clone() } else { return; };
+    let entities = ecs.entities();
+    let mut equipped = ecs.write_storage::<Equipped>();
+    let mut carried = ecs.write_storage::<InBackpack>();
+    let mut positions = ecs.write_storage::<Position>();
+
+    let mut to_drop : Vec<Entity> = Vec::new();
+    for (entity, equipped) in (&entities, &equipped).join() {
+        if equipped.owner == victim { to_drop.push(entity); }
+    }
+    for (entity, backpack) in (&entities, &carried).join() {
+        if backpack.owner == victim { to_drop.push(entity); }
+    }
+
+    for item in to_drop.iter() {
+        equipped.remove(*item);
+        carried.remove(*item);
+        positions.insert(*item, pos.clone()).expect("Unable to insert position");
+    }
+}
+
+fn rout_pack(ecs: &mut World, leader : Entity) {
+    let followers : Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<PackMember>()).join()
+        .filter(|(_, member)| member.leader == leader)
+        .map(|(entity, _)| entity)
+        .collect();
+    if followers.is_empty() { return; }
+
+    for follower in followers.iter() {
+        ecs.write_storage::<PackMember>().remove(*follower);
+        ecs.write_storage::<Chasing>().remove(*follower);
+        if let Some(behavior) = ecs.write_storage::<Behavior>().get_mut(*follower) {
+            behavior.state = AIState::Fleeing;
+        }
+        ecs.create_entity()
+            .with(StatusEffect{ target : *follower })
+            .with(Routed{})
+            .with(Duration{ turns : 10 })
+            .with(Name{ name : "Routed".to_string() })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+    }
+
+    ecs.fetch_mut::<GameLog>().log(LogCategory::Combat, "With its leader dead, the pack breaks and runs!");
+}
+
 pub fn heal_damage(ecs: &mut World, heal: &EffectSpawner, target: Entity) {
     let mut pools = ecs.write_storage::<Pools>();
     if let Some(pool) = pools.get_mut(target) {
@@ -146,6 +205,18 @@ pub fn add_confusion(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
     }
 }
 
+pub fn add_paralysis(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Paralysis{turns} = &effect.effect_type {
+        ecs.create_entity()
+            .with(StatusEffect{ target })
+            .with(Paralysis{})
+            .with(Duration{ turns : *turns})
+            .with(Name{ name : "Paralysis".to_string() })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+    }
+}
+
 pub fn attribute_effect(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
     if let EffectType::AttributeEffect{bonus, name, duration} = &effect.effect_type {
         ecs.create_entity()
@@ -0,0 +1,58 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Door, Position, Pools, OnFire, StatusEffect, Duration, DamageOverTime, Name};
+use crate::map::Map;
+use crate::gamelog::{GameLog, LogCategory};
+use crate::SerializeMe;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+/// How long someone keeps burning after leaving the flames
+const BURN_TURNS : i32 = 3;
+
+pub fn ignite_tile(ecs: &mut World, tile_idx : i32) {
+    let idx = tile_idx as usize;
+    let door_here = {
+        let map = ecs.fetch::<Map>();
+        let positions = ecs.read_storage::<Position>();
+        let doors = ecs.read_storage::<Door>();
+        (&positions, &doors).join().any(|(pos, _)| map.xy_idx(pos.x, pos.y) == idx)
+    };
+    let mut map = ecs.fetch_mut::<Map>();
+    crate::fire_system::set_alight(&mut map, idx, door_here);
+}
+
+pub fn set_on_fire(ecs: &mut World, target : Entity) {
+    if ecs.read_storage::<Pools>().get(target).is_none() { return; }
+    if let Some(pos) = entity_position(ecs, target) {
+        if crate::fire_system::douses_fire(&ecs.fetch::<Map>(), pos as usize) { return; }
+    }
+
+    // Catching fire again just keeps you burning for longer
+    let already_burning = {
+        let entities = ecs.entities();
+        let statuses = ecs.read_storage::<StatusEffect>();
+        let on_fire = ecs.read_storage::<OnFire>();
+        (&entities, &statuses, &on_fire).join()
+            .find(|(_, status, _)| status.target == target)
+            .map(|(entity, _, _)| entity)
+    };
+    if let Some(status) = already_burning {
+        if let Some(duration) = ecs.write_storage::<Duration>().get_mut(status) {
+            duration.turns = BURN_TURNS;
+        }
+        return;
+    }
+
+    ecs.create_entity()
+        .with(StatusEffect{ target })
+        .with(OnFire{})
+        .with(DamageOverTime{ damage : 2 })
+        .with(Duration{ turns : BURN_TURNS })
+        .with(Name{ name : "On Fire".to_string() })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    if target == *ecs.fetch::<Entity>() {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::Status, "You catch fire!");
+    }
+}
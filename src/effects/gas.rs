@@ -0,0 +1,17 @@
+use specs::prelude::*;
+use super::*;
+use crate::map::{Map, Gas, tile_opaque};
+
+pub fn release_gas(ecs: &mut World, effect: &EffectSpawner, tile_idx : i32) {
+    if let EffectType::ReleaseGas{ gas_type, volume } = &effect.effect_type {
+        let mut map = ecs.fetch_mut::<Map>();
+        let idx = tile_idx as usize;
+        if tile_opaque(map.tiles[idx]) { return; }
+        map.gas[idx] = match map.gas[idx] {
+            // More of the same thickens the cloud; anything else gets pushed out of the way
+            Some(existing) if existing.gas_type == *gas_type => Some(Gas{ gas_type: *gas_type, volume: existing.volume + volume }),
+            Some(existing) if existing.volume > *volume => Some(existing),
+            _ => Some(Gas{ gas_type: *gas_type, volume: *volume })
+        };
+    }
+}
@@ -0,0 +1,88 @@
+use specs::prelude::*;
+use bracket_lib::prelude::to_cp437;
+use super::{EffectSpawner, EffectType};
+use crate::components::{Mechanism, Door, Portcullis, BlocksTile, BlocksVisibility, Renderable, Position, Pools, Viewshed};
+use crate::map::{Map, TileType};
+use crate::gamelog::{GameLog, LogCategory};
+
+/// Works everything hooked up to the signal's channel.
+pub fn signal(ecs: &mut World, effect: &EffectSpawner) {
+    let channel = if let EffectType::Signal{channel} = effect.effect_type { channel } else { return; };
+
+    let targets : Vec<(Entity, usize)> = {
+        let map = ecs.fetch::<Map>();
+        let entities = ecs.entities();
+        let mechanisms = ecs.read_storage::<Mechanism>();
+        let positions = ecs.read_storage::<Position>();
+        (&entities, &mechanisms, &positions).join()
+            .filter(|(_, mechanism, _)| mechanism.channel == channel)
+            .map(|(entity, _, pos)| (entity, map.xy_idx(pos.x, pos.y)))
+            .collect()
+    };
+    if targets.is_empty() { return; }
+
+    let mut seen = false;
+    for (entity, idx) in targets.iter() {
+        if ecs.read_storage::<Door>().get(*entity).is_some() {
+            let open = ecs.read_storage::<BlocksTile>().get(*entity).is_none();
+            set_door(ecs, *entity, *idx, !open);
+        } else {
+            toggle_bridge(ecs, *idx);
+        }
+        seen |= ecs.fetch::<Map>().visible_tiles[*idx];
+    }
+
+    if !seen {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::World, "You hear the distant grinding of gears.");
+    }
+    for viewshed in (&mut ecs.write_storage::<Viewshed>()).join() {
+        viewshed.dirty = true;
+    }
+}
+
+/// Opens or shuts a door, unless something is standing in the way of it closing.
+fn set_door(ecs: &mut World, door_entity : Entity, idx : usize, open : bool) {
+    if !open {
+        let pools = ecs.read_storage::<Pools>();
+        let mut blocked = false;
+        crate::spatial::for_each_tile_content(idx, |e| if pools.get(e).is_some() { blocked = true; });
+        if blocked { return; }
+    }
+
+    ecs.fetch_mut::<Map>().terrain_changed();
+    let is_portcullis = ecs.read_storage::<Portcullis>().get(door_entity).is_some();
+    if let Some(door) = ecs.write_storage::<Door>().get_mut(door_entity) {
+        door.open = open;
+    }
+    let mut blocks_tile = ecs.write_storage::<BlocksTile>();
+    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
+    if open {
+        blocks_tile.remove(door_entity);
+        blocks_visibility.remove(door_entity);
+    } else {
+        blocks_tile.insert(door_entity, BlocksTile{}).expect("Unable to insert");
+        // You can see through the bars of a portcullis
+        if !is_portcullis {
+            blocks_visibility.insert(door_entity, BlocksVisibility{}).expect("Unable to insert");
+        }
+    }
+    if let Some(glyph) = ecs.write_storage::<Renderable>().get_mut(door_entity) {
+        glyph.glyph = match (is_portcullis, open) {
+            (true, true) => to_cp437('∙'),
+            (true, false) => to_cp437('≡'),
+            (false, true) => to_cp437('/'),
+            (false, false) => to_cp437('+')
+        };
+    }
+}
+
+/// Lowers a raised bridge over the water, or raises a lowered one.
+fn toggle_bridge(ecs: &mut World, idx : usize) {
+    let mut map = ecs.fetch_mut::<Map>();
+    match map.tiles[idx] {
+        TileType::DeepWater => map.tiles[idx] = TileType::Bridge,
+        TileType::Bridge => map.tiles[idx] = TileType::DeepWater,
+        _ => return
+    }
+    map.terrain_changed();
+}
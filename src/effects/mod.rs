@@ -1,7 +1,7 @@
 use std::sync::Mutex;
 use specs::prelude::*;
 use std::collections::VecDeque;
-use crate::map::Map;
+use crate::map::{Map, LevelId};
 mod damage;
 mod targeting;
 pub use targeting::*;
@@ -9,8 +9,15 @@ mod particles;
 mod triggers;
 mod hunger;
 mod movement;
+mod noise;
+mod fire;
+mod gas;
+mod terrain;
+mod mechanism;
+mod traps;
 use bracket_lib::prelude::*;
 use crate::AttributeBonus;
+use crate::map::GasType;
 
 lazy_static! {
     pub static ref EFFECT_QUEUE : Mutex<VecDeque<EffectSpawner>> = Mutex::new(VecDeque::new());
@@ -29,10 +36,20 @@ pub enum EffectType {
     Mana { amount : i32 },
     Confusion { turns : i32 },
     TriggerFire { trigger: Entity },
-    TeleportTo { x:i32, y:i32, depth: i32, player_only : bool },
+    TeleportTo { x:i32, y:i32, level: LevelId, player_only : bool },
     AttributeEffect { bonus : AttributeBonus, name : String, duration : i32 },
     Slow { initiative_penalty : f32 },
-    DamageOverTime { damage : i32 }
+    DamageOverTime { damage : i32 },
+    Noise { radius : i32 },
+    Ignite,
+    ReleaseGas { gas_type : GasType, volume : i32 },
+    Paralysis { turns : i32 },
+    Levitation { turns : i32 },
+    DestroyTerrain,
+    Signal { channel : i32 },
+    RandomTeleport,
+    Entangle { turns : i32 },
+    PlaceTrap { trap : String }
 }
 
 #[derive(Clone, Debug)]
@@ -101,6 +118,11 @@ fn tile_effect_hits_entities(effect: &EffectType) -> bool {
         EffectType::AttributeEffect{..} => true,
         EffectType::Slow{..} => true,
         EffectType::DamageOverTime{..} => true,
+        EffectType::Ignite => true,
+        EffectType::Paralysis{..} => true,
+        EffectType::Levitation{..} => true,
+        EffectType::RandomTeleport => true,
+        EffectType::Entangle{..} => true,
         _ => false
     }
 }
@@ -114,6 +136,12 @@ fn affect_tile(ecs: &mut World, effect: &EffectSpawner, tile_idx : i32) {
     match &effect.effect_type {
         EffectType::Bloodstain => damage::bloodstain(ecs, tile_idx),
         EffectType::Particle{..} => particles::particle_to_tile(ecs, tile_idx, &effect),
+        EffectType::Noise{..} => noise::make_noise(ecs, effect, tile_idx),
+        EffectType::Ignite => fire::ignite_tile(ecs, tile_idx),
+        EffectType::ReleaseGas{..} => gas::release_gas(ecs, effect, tile_idx),
+        EffectType::DestroyTerrain => terrain::destroy_terrain(ecs, tile_idx),
+        EffectType::Signal{..} => mechanism::signal(ecs, effect),
+        EffectType::PlaceTrap{..} => traps::place_trap(ecs, effect, tile_idx),
         _ => {}
     }
 }
@@ -132,6 +160,15 @@ fn affect_entity(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
         EffectType::AttributeEffect{..} => damage::attribute_effect(ecs, effect, target),
         EffectType::Slow{..} => damage::slow(ecs, effect, target),
         EffectType::DamageOverTime{..} => damage::damage_over_time(ecs, effect, target),
+        EffectType::Noise{..} => if let Some(pos) = entity_position(ecs, target) { noise::make_noise(ecs, effect, pos) },
+        EffectType::Ignite => fire::set_on_fire(ecs, target),
+        EffectType::ReleaseGas{..} => if let Some(pos) = entity_position(ecs, target) { gas::release_gas(ecs, effect, pos) },
+        EffectType::Paralysis{..} => damage::add_paralysis(ecs, effect, target),
+        EffectType::Levitation{..} => movement::levitate(ecs, effect, target),
+        EffectType::Signal{..} => mechanism::signal(ecs, effect),
+        EffectType::RandomTeleport => movement::random_teleport(ecs, target),
+        EffectType::Entangle{..} => movement::entangle(ecs, effect, target),
+        EffectType::PlaceTrap{..} => if let Some(pos) = entity_position(ecs, target) { traps::place_trap(ecs, effect, pos) },
         _ => {}
     }
 }
\ No newline at end of file
@@ -1,17 +1,69 @@
 use specs::prelude::*;
 use super::*;
-use crate::components::{ApplyTeleport};
+use crate::components::{ApplyTeleport, StatusEffect, Levitation, Entangled, Duration, Name};
+use crate::map::Map;
+use crate::SerializeMe;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
 
 pub fn apply_teleport(ecs: &mut World, destination: &EffectSpawner, target: Entity) {
     let player_entity = ecs.fetch::<Entity>();
-    if let EffectType::TeleportTo{x, y, depth, player_only} = &destination.effect_type {
+    if let EffectType::TeleportTo{x, y, level, player_only} = &destination.effect_type {
         if !player_only || target == *player_entity {
             let mut apply_teleport = ecs.write_storage::<ApplyTeleport>();
             apply_teleport.insert(target, ApplyTeleport{
                 dest_x : *x,
                 dest_y : *y,
-                dest_depth : *depth
+                dest_level : *level
             }).expect("Unable to insert");
         }
     }
-}
\ No newline at end of file
+}
+pub fn levitate(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Levitation{turns} = &effect.effect_type {
+        ecs.create_entity()
+            .with(StatusEffect{ target })
+            .with(Levitation{})
+            .with(Duration{ turns : *turns })
+            .with(Name{ name : "Levitating".to_string() })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+    }
+}
+
+/// Drops the target somewhere random on the same level.
+pub fn random_teleport(ecs: &mut World, target: Entity) {
+    let destination = {
+        let map = ecs.fetch::<Map>();
+        let mut rng = ecs.fetch_mut::<RandomNumberGenerator>();
+        let mut destination = None;
+        for _try in 0..20 {
+            let x = rng.roll_dice(1, map.width-2);
+            let y = rng.roll_dice(1, map.height-2);
+            let idx = map.xy_idx(x, y);
+            if map.walkable(idx) && !crate::spatial::is_blocked(idx) {
+                destination = Some((x, y, map.level_id()));
+                break;
+            }
+        }
+        destination
+    };
+    if let Some((x, y, level)) = destination {
+        ecs.write_storage::<ApplyTeleport>().insert(target, ApplyTeleport{
+            dest_x : x,
+            dest_y : y,
+            dest_level : level
+        }).expect("Unable to insert");
+    }
+}
+
+pub fn entangle(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Entangle{turns} = &effect.effect_type {
+        ecs.create_entity()
+            .with(StatusEffect{ target })
+            .with(Entangled{})
+            .with(Duration{ turns : *turns })
+            .with(Name{ name : "Entangled".to_string() })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+    }
+}
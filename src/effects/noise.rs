@@ -0,0 +1,48 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Position, Faction, Pools, Chasing, Stealth, Behavior, AIState};
+use crate::raws::{Reaction, faction_reaction, RAWS};
+
+pub fn make_noise(ecs: &mut World, effect: &EffectSpawner, tile_idx : i32) {
+    if let EffectType::Noise{radius} = effect.effect_type {
+        // Noise with no source has nothing to lead listeners towards
+        let source = if let Some(source) = effect.creator { source } else { return; };
+        let factions = ecs.read_storage::<Faction>();
+        let source_faction = if let Some(f) = factions.get(source) { f.name.clone() } else { return; };
+
+        // Stealthy entities muffle the noise they make
+        let radius = if let Some(stealth) = ecs.read_storage::<Stealth>().get(source) {
+            i32::max(1, radius - stealth.value)
+        } else {
+            radius
+        };
+
+        let map = ecs.fetch::<Map>();
+        let origin = Point::new(tile_idx % map.width, tile_idx / map.width);
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let pools = ecs.read_storage::<Pools>();
+        let player_entity = ecs.fetch::<Entity>();
+        let mut chasing = ecs.write_storage::<Chasing>();
+        let mut behaviors = ecs.write_storage::<Behavior>();
+
+        // Sound travels through walls, so anyone in range hears it
+        for (entity, pos, faction, pool) in (&entities, &positions, &factions, &pools).join() {
+            if entity == source || entity == *player_entity || pool.hit_points.current < 1 { continue; }
+            if chasing.get(entity).is_some() { continue; }
+            let distance = DistanceAlg::Pythagoras.distance2d(origin, Point::new(pos.x, pos.y));
+            let mut hearing = radius as f32;
+            if let Some(behavior) = behaviors.get(entity) {
+                // Sleepers only wake up for noises close by
+                if behavior.state == AIState::Asleep { hearing /= 2.0; }
+            }
+            if distance > hearing { continue; }
+            if faction_reaction(&faction.name, &source_faction, &RAWS.lock().unwrap()) == Reaction::Attack {
+                chasing.insert(entity, Chasing{ target : source }).expect("Unable to insert");
+                if let Some(behavior) = behaviors.get_mut(entity) {
+                    behavior.state = AIState::Hunting;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use specs::prelude::*;
+use crate::components::Viewshed;
+use crate::map::Map;
+
+pub fn destroy_terrain(ecs: &mut World, tile_idx : i32) {
+    let idx = tile_idx as usize;
+    {
+        let mut map = ecs.fetch_mut::<Map>();
+        if !map.diggable(idx) { return; }
+        map.collapse(idx);
+    }
+
+    // Everyone's view of the world may have just opened up
+    for viewshed in (&mut ecs.write_storage::<Viewshed>()).join() {
+        viewshed.dirty = true;
+    }
+}
@@ -0,0 +1,34 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{EntryTrigger, Hidden, Camouflaged};
+use crate::gamelog::{GameLog, LogCategory};
+use crate::raws::{spawn_named_entity, SpawnType, RAWS};
+
+/// Sets a trap on the tile, hidden from everyone but whoever set it.
+pub fn place_trap(ecs: &mut World, effect: &EffectSpawner, tile_idx : i32) {
+    if let EffectType::PlaceTrap{ trap } = &effect.effect_type {
+        let idx = tile_idx as usize;
+        let (x, y, walkable) = {
+            let map = ecs.fetch::<Map>();
+            (tile_idx % map.width, tile_idx / map.width, map.walkable(idx))
+        };
+
+        let mut occupied = false;
+        {
+            let triggers = ecs.read_storage::<EntryTrigger>();
+            crate::spatial::for_each_tile_content(idx, |e| if triggers.get(e).is_some() { occupied = true; });
+        }
+        if !walkable || occupied || crate::spatial::is_blocked(idx) {
+            ecs.fetch_mut::<GameLog>().log(LogCategory::World, "There's no room to set a trap there.");
+            return;
+        }
+
+        let trap_entity = spawn_named_entity(&RAWS.lock().unwrap(), ecs, trap, SpawnType::AtPosition{ x, y });
+        if let Some(trap_entity) = trap_entity {
+            ecs.write_storage::<Hidden>().remove(trap_entity);
+            ecs.write_storage::<Camouflaged>().insert(trap_entity, Camouflaged{}).expect("Unable to insert");
+            crate::spatial::index_entity(trap_entity, idx, false);
+            ecs.fetch_mut::<GameLog>().log(LogCategory::World, format!("You set the {}.", trap));
+        }
+    }
+}
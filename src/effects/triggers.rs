@@ -1,6 +1,6 @@
 use super::*;
 use crate::components::*;
-use crate::gamelog::GameLog;
+use crate::gamelog::{GameLog, LogCategory};
 use crate::RunState;
 
 pub fn item_trigger(creator : Option<Entity>, item: Entity, targets : &Targets, ecs: &mut World) {
@@ -9,7 +9,7 @@ pub fn item_trigger(creator : Option<Entity>, item: Entity, targets : &Targets,
         if c.charges < 1 {
             // Cancel
             let mut gamelog = ecs.fetch_mut::<GameLog>();
-            gamelog.entries.push(format!("{} is out of charges!", ecs.read_storage::<Name>().get(item).unwrap().name));
+            gamelog.log(LogCategory::Loot, format!("{} is out of charges!", ecs.read_storage::<Name>().get(item).unwrap().name));
             return;
         } else {
             c.charges -= 1;
@@ -42,6 +42,11 @@ pub fn spell_trigger(creator : Option<Entity>, spell: Entity, targets : &Targets
         }
     }
     event_trigger(creator, spell, targets, ecs);
+
+    // Spellcasting is loud
+    if let Some(caster) = creator {
+        add_effect(creator, EffectType::Noise{ radius : 6 }, Targets::Single{ target : caster });
+    }
 }
 
 pub fn trigger(creator : Option<Entity>, trigger: Entity, targets : &Targets, ecs: &mut World) {
@@ -51,6 +56,11 @@ pub fn trigger(creator : Option<Entity>, trigger: Entity, targets : &Targets, ec
     // Use the item via the generic system
     let did_something = event_trigger(creator, trigger, targets, ecs);
 
+    // Traps going off can be heard from a distance
+    if let Some(victim) = creator {
+        add_effect(creator, EffectType::Noise{ radius : 8 }, Targets::Single{ target : victim });
+    }
+
     // If it was a single activation, then it gets deleted
     if did_something && ecs.read_storage::<SingleActivation>().get(trigger).is_some() {
         ecs.entities().delete(trigger).expect("Delete Failed");
@@ -102,14 +112,14 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
     if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
         add_effect(creator, EffectType::WellFed, targets.clone());
         let names = ecs.read_storage::<Name>();
-        gamelog.entries.push(format!("You eat the {}.", names.get(entity).unwrap().name));
+        gamelog.log(LogCategory::Status, format!("You eat the {}.", names.get(entity).unwrap().name));
         did_something = true;
     }
 
     // Magic mapper
     if ecs.read_storage::<MagicMapper>().get(entity).is_some() {
         let mut runstate = ecs.fetch_mut::<RunState>();
-        gamelog.entries.push("The map is revealed to you!".to_string());
+        gamelog.log(LogCategory::World, "The map is revealed to you!");
         *runstate = RunState::MagicMapReveal{ row : 0};
         did_something = true;
     }
@@ -132,9 +142,9 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
     if ecs.read_storage::<TownPortal>().get(entity).is_some() {
         let map = ecs.fetch::<Map>();
         if map.depth == 1 {
-            gamelog.entries.push("You are already in town, so the scroll does nothing.".to_string());
+            gamelog.log(LogCategory::World, "You are already in town, so the scroll does nothing.");
         } else {
-            gamelog.entries.push("You are telported back to town!".to_string());
+            gamelog.log(LogCategory::World, "You are telported back to town!");
             let mut runstate = ecs.fetch_mut::<RunState>();
             *runstate = RunState::TownPortal;
             did_something = true;
@@ -174,7 +184,7 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
             EffectType::TeleportTo{
                 x : teleport.x,
                 y : teleport.y,
-                depth: teleport.depth,
+                level: teleport.level,
                 player_only: teleport.player_only
             },
             targets.clone()
@@ -224,6 +234,59 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
         did_something = true;
     }
 
+    // Setting things on fire
+    if ecs.read_storage::<Ignites>().get(entity).is_some() {
+        add_effect(creator, EffectType::Ignite, targets.clone());
+        did_something = true;
+    }
+
+    // Levitation
+    if ecs.read_storage::<Levitation>().get(entity).is_some() {
+        if let Some(duration) = ecs.read_storage::<Duration>().get(entity) {
+            add_effect(creator, EffectType::Levitation{ turns : duration.turns }, targets.clone());
+            did_something = true;
+        }
+    }
+
+    // Blowing holes in the walls
+    if ecs.read_storage::<DestroysTerrain>().get(entity).is_some() {
+        add_effect(creator, EffectType::DestroyTerrain, targets.clone());
+        did_something = true;
+    }
+
+    // Gas clouds
+    if let Some(gas) = ecs.read_storage::<ReleasesGas>().get(entity) {
+        add_effect(creator, EffectType::ReleaseGas{ gas_type : gas.gas_type, volume : gas.volume }, targets.clone());
+        did_something = true;
+    }
+
+    // Being sent elsewhere on the level
+    if ecs.read_storage::<RandomTeleport>().get(entity).is_some() {
+        add_effect(creator, EffectType::RandomTeleport, targets.clone());
+        did_something = true;
+    }
+
+    // Alarms wake up everything for a good distance around
+    if let Some(alarm) = ecs.read_storage::<Alarm>().get(entity) {
+        gamelog.log(LogCategory::World, "An alarm bell clangs loudly!");
+        add_effect(creator, EffectType::Noise{ radius : alarm.radius }, targets.clone());
+        did_something = true;
+    }
+
+    // Nets
+    if ecs.read_storage::<Entangles>().get(entity).is_some() {
+        if let Some(duration) = ecs.read_storage::<Duration>().get(entity) {
+            add_effect(creator, EffectType::Entangle{ turns : duration.turns }, targets.clone());
+            did_something = true;
+        }
+    }
+
+    // Setting traps
+    if let Some(kit) = ecs.read_storage::<PlacesTrap>().get(entity) {
+        add_effect(creator, EffectType::PlaceTrap{ trap : kit.trap.clone() }, targets.clone());
+        did_something = true;
+    }
+
     // Learn spells
     if let Some(spell) = ecs.read_storage::<TeachesSpell>().get(entity) {
         if let Some(known) = ecs.write_storage::<KnownSpells>().get_mut(creator.unwrap()) {
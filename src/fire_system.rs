@@ -0,0 +1,124 @@
+use specs::prelude::*;
+use bracket_lib::prelude::RandomNumberGenerator;
+use crate::{Map, TileType, Door, Portcullis, Position, Pools, OnFire, StatusEffect, MyTurn, Item, ReleasesGas, gamelog::{GameLog, LogCategory}};
+use crate::effects::{add_effect, EffectType, Targets};
+use std::collections::HashMap;
+
+/// How many turns it takes a wooden door to burn through
+const DOOR_FUEL : i32 = 6;
+
+pub struct FireSystem {}
+
+impl<'a> System<'a> for FireSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+                        WriteExpect<'a, Map>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        ReadExpect<'a, Entity>, // The player
+                        ReadStorage<'a, MyTurn>,
+                        ReadStorage<'a, Position>,
+                        ReadStorage<'a, Door>,
+                        ReadStorage<'a, Pools>,
+                        ReadStorage<'a, OnFire>,
+                        ReadStorage<'a, StatusEffect>,
+                        WriteExpect<'a, GameLog>,
+                        Entities<'a>,
+                        ReadStorage<'a, Item>,
+                        ReadStorage<'a, ReleasesGas>,
+                        ReadStorage<'a, Portcullis>
+                      );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut map, mut rng, player_entity, turns, positions, doors, pools, on_fire,
+            statuses, mut log, entities, items, releases_gas, portcullises) = data;
+
+        // Fire burns at the same pace as the player acts
+        if turns.get(*player_entity).is_none() { return; }
+
+        // Iron gates don't burn
+        let doors_at : HashMap<usize, Entity> = (&entities, &positions, &doors, !&portcullises).join()
+            .map(|(entity, pos, _, _)| (map.xy_idx(pos.x, pos.y), entity))
+            .collect();
+
+        // Anyone who is on fire sets light to whatever they are standing on, until they find water
+        for (status_entity, status, _on_fire) in (&entities, &statuses, &on_fire).join() {
+            if let Some(pos) = positions.get(status.target) {
+                let idx = map.xy_idx(pos.x, pos.y);
+                if douses_fire(&map, idx) {
+                    entities.delete(status_entity).expect("Unable to delete");
+                    if status.target == *player_entity {
+                        log.log(LogCategory::World, "The water puts out the flames.");
+                    }
+                } else {
+                    set_alight(&mut map, idx, doors_at.contains_key(&idx));
+                }
+            }
+        }
+
+        let burning : Vec<usize> = map.fire.iter().enumerate()
+            .filter(|(_, fire)| **fire > 0)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        for idx in burning.iter() {
+            // Spread to anything flammable next door
+            let x = *idx as i32 % map.width;
+            let y = *idx as i32 / map.width;
+            for (nx, ny) in [(x-1, y), (x+1, y), (x, y-1), (x, y+1)].iter() {
+                if *nx < 1 || *nx > map.width-2 || *ny < 1 || *ny > map.height-2 { continue; }
+                let neighbor = map.xy_idx(*nx, *ny);
+                if map.fire[neighbor] == 0 && rng.roll_dice(1, 3) == 1 {
+                    set_alight(&mut map, neighbor, doors_at.contains_key(&neighbor));
+                }
+            }
+
+            // Burn whoever is standing in it, and crack any flasks lying around
+            let mut victims : Vec<Entity> = Vec::new();
+            let mut flasks : Vec<Entity> = Vec::new();
+            crate::spatial::for_each_tile_content(*idx, |entity| {
+                if pools.get(entity).is_some() { victims.push(entity); }
+                if items.get(entity).is_some() && releases_gas.get(entity).is_some() { flasks.push(entity); }
+            });
+            for victim in victims.iter() {
+                add_effect(None, EffectType::Damage{ amount: rng.roll_dice(1, 4) }, Targets::Single{ target: *victim });
+                add_effect(None, EffectType::Ignite, Targets::Single{ target: *victim });
+            }
+            for flask in flasks.iter() {
+                let gas = releases_gas.get(*flask).unwrap();
+                add_effect(None, EffectType::ReleaseGas{ gas_type: gas.gas_type, volume: gas.volume }, Targets::Tile{ tile_idx: *idx as i32 });
+                entities.delete(*flask).expect("Unable to delete");
+                if map.visible_tiles[*idx] {
+                    log.log(LogCategory::World, "A flask shatters in the heat!");
+                }
+            }
+
+            map.fire[*idx] -= 1;
+            if map.fire[*idx] < 1 {
+                map.burn_out(*idx);
+                if let Some(door) = doors_at.get(idx) {
+                    entities.delete(*door).expect("Unable to delete");
+                    if map.visible_tiles[*idx] {
+                        log.log(LogCategory::World, "A door burns away.");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Water puts fires out, and stops them from starting.
+pub fn douses_fire(map : &Map, idx : usize) -> bool {
+    matches!(map.tiles[idx], TileType::ShallowWater | TileType::DeepWater)
+}
+
+/// Sets a tile burning if there is anything there to burn, returning true if it caught.
+pub fn set_alight(map : &mut Map, idx : usize, door_here : bool) -> bool {
+    if map.fire[idx] > 0 || douses_fire(map, idx) { return false; }
+    let fuel = map.fuel(idx) + if door_here { DOOR_FUEL } else { 0 };
+    if fuel > 0 {
+        map.fire[idx] = fuel;
+        true
+    } else {
+        false
+    }
+}
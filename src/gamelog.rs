@@ -1,3 +1,155 @@
+use bracket_lib::prelude::*;
+
+/// What a message is about, so the history can be filtered down to what the player cares about.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LogCategory { Combat, Loot, Status, World, System }
+
+pub const LOG_CATEGORIES : [LogCategory; 5] = [LogCategory::Combat, LogCategory::Loot, LogCategory::Status,
+    LogCategory::World, LogCategory::System];
+
+impl LogCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogCategory::Combat => "Combat",
+            LogCategory::Loot => "Loot",
+            LogCategory::Status => "Status",
+            LogCategory::World => "World",
+            LogCategory::System => "System"
+        }
+    }
+
+    /// Text that doesn't ask for a color of its own is drawn in its category's.
+    pub fn color(&self) -> RGB {
+        match self {
+            LogCategory::Combat => RGB::from_f32(1.0, 0.6, 0.6),
+            LogCategory::Loot => RGB::from_f32(1.0, 0.85, 0.4),
+            LogCategory::Status => RGB::from_f32(0.8, 0.6, 1.0),
+            LogCategory::World => RGB::from_f32(0.6, 0.8, 1.0),
+            LogCategory::System => RGB::named(WHITE)
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct LogFragment {
+    pub text : String,
+    pub color : RGB
+}
+
+pub struct LogEntry {
+    pub category : LogCategory,
+    pub turn : i32,
+    pub count : i32,
+    pub fragments : Vec<LogFragment>
+}
+
+impl LogEntry {
+    /// The whole message, with repeats noted on the end.
+    pub fn text(&self) -> String {
+        let mut text : String = self.fragments.iter().map(|f| f.text.as_str()).collect();
+        if self.count > 1 {
+            text += &format!(" x{}", self.count);
+        }
+        text
+    }
+}
+
 pub struct GameLog {
-    pub entries : Vec<String>
+    entries : Vec<LogEntry>,
+    pub turn : i32
+}
+
+impl GameLog {
+    pub fn new() -> GameLog {
+        GameLog{ entries: Vec::new(), turn: 1 }
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    pub fn next_turn(&mut self) {
+        self.turn += 1;
+    }
+
+    /// Logs a plain message, in the category's own color.
+    pub fn log<S : ToString>(&mut self, category : LogCategory, text : S) {
+        self.line(category).append(text).log();
+    }
+
+    /// Starts a message that is built up out of colored fragments.
+    pub fn line(&mut self, category : LogCategory) -> LogLine<'_> {
+        LogLine{ log: self, category, fragments: Vec::new() }
+    }
+
+    fn push(&mut self, category : LogCategory, fragments : Vec<LogFragment>) {
+        // Saying the same thing again just bumps the count on the last message
+        if let Some(last) = self.entries.last_mut() {
+            if last.category == category && last.fragments == fragments {
+                last.count += 1;
+                last.turn = self.turn;
+                return;
+            }
+        }
+        self.entries.push(LogEntry{ category, turn: self.turn, count: 1, fragments });
+    }
+}
+
+pub struct LogLine<'a> {
+    log : &'a mut GameLog,
+    category : LogCategory,
+    fragments : Vec<LogFragment>
+}
+
+impl<'a> LogLine<'a> {
+    pub fn append<S : ToString>(mut self, text : S) -> Self {
+        let color = self.category.color();
+        self.fragments.push(LogFragment{ text: text.to_string(), color });
+        self
+    }
+
+    pub fn colored<S : ToString>(mut self, text : S, color : RGB) -> Self {
+        self.fragments.push(LogFragment{ text: text.to_string(), color });
+        self
+    }
+
+    pub fn log(self) {
+        self.log.push(self.category, self.fragments);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_collapse_into_a_count() {
+        let mut log = GameLog::new();
+        log.log(LogCategory::Combat, "The rat bites you.");
+        log.next_turn();
+        log.log(LogCategory::Combat, "The rat bites you.");
+        assert_eq!(log.entries().len(), 1);
+        assert_eq!(log.entries()[0].count, 2);
+        assert_eq!(log.entries()[0].turn, 2);
+        assert_eq!(log.entries()[0].text(), "The rat bites you. x2");
+    }
+
+    #[test]
+    fn different_messages_stay_apart() {
+        let mut log = GameLog::new();
+        log.log(LogCategory::Combat, "The rat bites you.");
+        log.log(LogCategory::Combat, "You hit the rat.");
+        log.log(LogCategory::Combat, "The rat bites you.");
+        assert_eq!(log.entries().len(), 3);
+        assert!(log.entries().iter().all(|e| e.count == 1));
+    }
+
+    #[test]
+    fn category_and_color_count_towards_a_repeat() {
+        let mut log = GameLog::new();
+        log.log(LogCategory::Combat, "Ouch.");
+        log.log(LogCategory::Status, "Ouch.");
+        log.line(LogCategory::Status).colored("Ouch.", RGB::named(RED)).log();
+        assert_eq!(log.entries().len(), 3);
+    }
 }
@@ -35,3 +35,11 @@ pub fn skill_bonus(skill : Skill, skills: &Skills) -> i32 {
         -4
     }
 }
+
+pub fn stealth_value(skills: &Skills, quickness_bonus: i32, armor_weight: f32) -> i32 {
+    skill_bonus(Skill::Stealth, skills) + quickness_bonus - (armor_weight / 10.0) as i32
+}
+
+pub fn perception_value(skills: &Skills, intelligence_bonus: i32) -> i32 {
+    skill_bonus(Skill::Perception, skills) + intelligence_bonus
+}
@@ -0,0 +1,175 @@
+use specs::prelude::*;
+use bracket_lib::prelude::{RGB, to_cp437, RED, BLACK};
+use crate::{Map, Door, Position, Pools, StatusEffect, Confusion, Paralysis, MyTurn, Viewshed, gamelog::{GameLog, LogCategory},
+    map::{Gas, GasType, tile_opaque}};
+use crate::effects::{add_effect, EffectType, Targets};
+use std::collections::HashSet;
+
+pub struct GasSystem {}
+
+impl<'a> System<'a> for GasSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+                        WriteExpect<'a, Map>,
+                        ReadExpect<'a, Entity>, // The player
+                        ReadStorage<'a, MyTurn>,
+                        ReadStorage<'a, Position>,
+                        ReadStorage<'a, Door>,
+                        ReadStorage<'a, Pools>,
+                        ReadStorage<'a, StatusEffect>,
+                        ReadStorage<'a, Confusion>,
+                        ReadStorage<'a, Paralysis>,
+                        WriteStorage<'a, Viewshed>,
+                        WriteExpect<'a, GameLog>,
+                        Entities<'a>
+                      );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut map, player_entity, turns, positions, doors, pools, statuses, confusion, paralysis,
+            mut viewsheds, mut log, entities) = data;
+
+        // Gas drifts at the same pace as the player acts
+        if turns.get(*player_entity).is_none() { return; }
+        if map.gas.iter().all(|gas| gas.is_none()) { return; }
+
+        let closed_doors : HashSet<usize> = (&positions, &doors).join()
+            .filter(|(_, door)| !door.open)
+            .map(|(pos, _)| map.xy_idx(pos.x, pos.y))
+            .collect();
+        let was_opaque : Vec<bool> = map.gas.iter().map(|gas| gas.is_some_and(|g| g.flags().opaque)).collect();
+
+        // Methane goes up the moment it meets a flame
+        let mut explosions : Vec<usize> = Vec::new();
+        for (idx, gas) in map.gas.iter().enumerate() {
+            if let Some(gas) = gas {
+                if gas.gas_type == GasType::Methane && neighbors(&map, idx).iter().chain([idx].iter()).any(|n| map.fire[*n] > 0) {
+                    explosions.push(idx);
+                }
+            }
+        }
+        for idx in explosions.iter() {
+            let volume = map.gas[*idx].map_or(0, |g| g.volume);
+            map.gas[*idx] = None;
+            map.fire[*idx] = i32::max(map.fire[*idx], 1);
+            add_effect(None, EffectType::Damage{ amount: 3 + volume / 10 }, Targets::Tile{ tile_idx: *idx as i32 });
+            add_effect(None, EffectType::Ignite, Targets::Tile{ tile_idx: *idx as i32 });
+            add_effect(None,
+                EffectType::Particle{ glyph: to_cp437('▓'), fg: RGB::named(RED), bg: RGB::named(BLACK), lifespan: 200.0 },
+                Targets::Tile{ tile_idx: *idx as i32 });
+        }
+        if explosions.iter().any(|idx| map.visible_tiles[*idx]) {
+            log.log(LogCategory::World, "The methane explodes!");
+        }
+
+        // Each cloud spreads itself evenly over every open tile around it
+        let mut next : Vec<Option<Gas>> = vec![None; map.gas.len()];
+        for idx in 0..map.gas.len() {
+            if let Some(gas) = map.gas[idx] {
+                let open : Vec<usize> = neighbors(&map, idx).into_iter()
+                    .filter(|n| !tile_opaque(map.tiles[*n]) && !closed_doors.contains(n))
+                    .collect();
+                let share = gas.volume / (open.len() as i32 + 1);
+                mix(&mut next, idx, gas.gas_type, gas.volume - share * open.len() as i32);
+                if share > 0 {
+                    for n in open.iter() {
+                        mix(&mut next, *n, gas.gas_type, share);
+                    }
+                }
+            }
+        }
+
+        // ...and slowly thins out
+        for gas in next.iter_mut() {
+            if let Some(g) = gas {
+                g.volume -= g.gas_type.dissipation();
+                if g.volume < 1 { *gas = None; }
+            }
+        }
+        map.gas = next;
+
+        // Thick clouds coming and going change what everyone can see
+        let opacity_changed = map.gas.iter().zip(was_opaque.iter())
+            .any(|(gas, was)| gas.is_some_and(|g| g.flags().opaque) != *was);
+        if opacity_changed {
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+
+        // Breathe it in
+        for (entity, pos, _pools) in (&entities, &positions, &pools).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            if let Some(gas) = map.gas[idx] {
+                let is_player = entity == *player_entity;
+                let has_status = |marker : &dyn Fn(Entity) -> bool| {
+                    (&entities, &statuses).join().any(|(e, status)| status.target == entity && marker(e))
+                };
+                match gas.gas_type {
+                    GasType::Poison => {
+                        add_effect(None, EffectType::Damage{ amount: 1 + gas.volume / 30 }, Targets::Single{ target: entity });
+                        if is_player { log.log(LogCategory::Status, "The caustic gas burns your lungs!"); }
+                    }
+                    GasType::Confusion => {
+                        if !has_status(&|e| confusion.get(e).is_some()) {
+                            add_effect(None, EffectType::Confusion{ turns: 3 }, Targets::Single{ target: entity });
+                            if is_player { log.log(LogCategory::Status, "The gas makes your head spin!"); }
+                        }
+                    }
+                    GasType::Paralysis => {
+                        if !has_status(&|e| paralysis.get(e).is_some()) {
+                            add_effect(None, EffectType::Paralysis{ turns: 3 }, Targets::Single{ target: entity });
+                            if is_player { log.log(LogCategory::Status, "Your muscles lock up!"); }
+                        }
+                    }
+                    GasType::Smoke | GasType::Methane => {}
+                }
+            }
+        }
+    }
+}
+
+fn neighbors(map : &Map, idx : usize) -> Vec<usize> {
+    let x = idx as i32 % map.width;
+    let y = idx as i32 / map.width;
+    let mut result = Vec::new();
+    for ny in y-1 ..= y+1 {
+        for nx in x-1 ..= x+1 {
+            if (nx == x && ny == y) || nx < 1 || nx > map.width-2 || ny < 1 || ny > map.height-2 { continue; }
+            result.push(map.xy_idx(nx, ny));
+        }
+    }
+    result
+}
+
+/// Adds gas to a tile; where two kinds of gas meet, the thicker one wins out.
+fn mix(gas : &mut [Option<Gas>], idx : usize, gas_type : GasType, volume : i32) {
+    gas[idx] = match gas[idx] {
+        Some(existing) if existing.gas_type == gas_type => Some(Gas{ gas_type, volume: existing.volume + volume }),
+        Some(existing) if existing.volume >= volume => Some(existing),
+        _ => Some(Gas{ gas_type, volume })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_adds_to_the_same_gas() {
+        let mut gas = vec![None; 4];
+        mix(&mut gas, 1, GasType::Smoke, 3);
+        mix(&mut gas, 1, GasType::Smoke, 4);
+        assert_eq!(gas[1], Some(Gas{ gas_type: GasType::Smoke, volume: 7 }));
+        assert_eq!(gas[0], None);
+    }
+
+    #[test]
+    fn mix_lets_the_thicker_gas_win() {
+        let mut gas = vec![None; 4];
+        mix(&mut gas, 2, GasType::Poison, 5);
+        mix(&mut gas, 2, GasType::Smoke, 3);
+        assert_eq!(gas[2], Some(Gas{ gas_type: GasType::Poison, volume: 5 }));
+        mix(&mut gas, 2, GasType::Smoke, 8);
+        assert_eq!(gas[2], Some(Gas{ gas_type: GasType::Smoke, volume: 8 }));
+    }
+}
@@ -1,10 +1,15 @@
 use bracket_lib::prelude::*;
 use specs::prelude::*;
-use crate::{Duration, KnownSpells, StatusEffect};
-use super::{Pools, gamelog::GameLog, Map, Name, Position, State, InBackpack,
+use crate::{Duration, KnownSpells, StatusEffect, Behavior, AIState};
+use super::{Pools, gamelog::{GameLog, LogEntry, LogCategory, LOG_CATEGORIES}, Map, Name, Position, State, InBackpack,
     Viewshed, RunState, Equipped, HungerClock, HungerState, rex_assets::RexAssets,
     Hidden, camera, Attributes, Attribute, Consumable, VendorMode, Item, Vendor,
-    MagicItem, MagicItemClass, ObfuscatedName, CursedItem, MasterDungeonMap };
+    MagicItem, MagicItemClass, ObfuscatedName, CursedItem, MasterDungeonMap, BRANCHES, Description,
+    Faction, Door, Lock, Lever, EntryTrigger, Disarmable, MeleeWeapon, WeaponAttribute, Wearable,
+    EquipmentSlot, ProvidesHealing, ProvidesMana, InflictsDamage, Ranged, AreaOfEffect,
+    raws::{Reaction, faction_reaction, RAWS}, keybindings::{KEYBINDINGS, ACTIONS, Action, is_bindable}, AttributeBonus, Skills, Skill, skill_bonus, Stealth,
+    Perception, NaturalAttackDefense, Levitation, Flying, Swimmer, Equippable, EQUIPMENT_SLOTS, Throwable,
+    Remembered, Renderable, TileType };
 
 pub fn get_item_color(ecs : &World, item : Entity) -> RGB {
     let dm = ecs.fetch::<crate::map::MasterDungeonMap>();
@@ -148,12 +153,16 @@ pub fn draw_ui(ecs: &World, ctx : &mut BTerm) {
     ctx.print_color(50,10, white, black, &format!("Initiative Penalty: {:.0}", player_pools.total_initiative_penalty));
     ctx.print_color(50,11, RGB::named(GOLD), black, &format!("Gold: {:.1}", player_pools.gold));
 
+    // Status lines stack up from just above the minimap, so the lists stop short of them
+    let status = status_lines(ecs, *player_entity);
+    let list_end = MINIMAP_TOP - 1 - status.len() as i32;
+
     // Equipped
     let mut y = 13;
     let entities = ecs.entities();
     let equipped = ecs.read_storage::<Equipped>();
     for (entity, equipped_by) in (&entities, &equipped).join() {
-        if equipped_by.owner == *player_entity {
+        if equipped_by.owner == *player_entity && y < list_end {
             ctx.print_color(50, y, get_item_color(ecs, entity), black, &get_item_display_name(ecs, entity));
             y += 1;
         }
@@ -166,7 +175,7 @@ pub fn draw_ui(ecs: &World, ctx : &mut BTerm) {
     let backpack = ecs.read_storage::<InBackpack>();
     let mut index = 1;
     for (entity, carried_by, _consumable) in (&entities, &backpack, &consumables).join() {
-        if carried_by.owner == *player_entity && index < 10 {
+        if carried_by.owner == *player_entity && index < 10 && y < list_end {
             ctx.print_color(50, y, yellow, black, &format!("↑{}", index));
             ctx.print_color(53, y, get_item_color(ecs, entity), black, &get_item_display_name(ecs, entity));
             y += 1;
@@ -182,56 +191,128 @@ pub fn draw_ui(ecs: &World, ctx : &mut BTerm) {
     let known_spells = &known_spells_storage.get(*player_entity).unwrap().spells;
     let mut index = 1;
     for spell in known_spells.iter() {
+        if y >= list_end { break; }
         ctx.print_color(50, y, blue, black, &format!("^{}", index));
         ctx.print_color(53, y, blue, black, &format!("{} ({})", spell.display_name, spell.mana_cost));
         index += 1;
         y += 1;
     }
 
+    draw_minimap(ecs, ctx);
+    ctx.set(49, MINIMAP_TOP - 1, box_gray, black, to_cp437('├'));
+    for x in 50..79 {
+        ctx.set(x, MINIMAP_TOP - 1, box_gray, black, to_cp437('─'));
+    }
+    ctx.set(79, MINIMAP_TOP - 1, box_gray, black, to_cp437('┤'));
+
     // Status
-    let mut y = 44;
+    for (i, (color, text)) in status.iter().enumerate() {
+        ctx.print_color(50, MINIMAP_TOP - 2 - i as i32, *color, RGB::named(BLACK), text);
+    }
+
+    // Draw the log
+    let log = ecs.fetch::<GameLog>();
+    let mut y = 46;
+    for entry in log.entries().iter().rev() {
+        if y < 59 { draw_log_entry(ctx, 2, y, entry, 76); }
+        y += 1;
+    }
+
+    draw_tooltips(ecs, ctx);
+}
+
+/// Hunger and the effects on the player, bottom line first.
+fn status_lines(ecs : &World, player_entity : Entity) -> Vec<(RGB, String)> {
+    let mut lines = Vec::new();
     let hunger = ecs.read_storage::<HungerClock>();
-    let hc = hunger.get(*player_entity).unwrap();
+    let hc = hunger.get(player_entity).unwrap();
     match hc.state {
-        HungerState::WellFed => {
-            ctx.print_color(50, y, RGB::named(GREEN), RGB::named(BLACK), "Well Fed");
-            y -= 1;
-        }
+        HungerState::WellFed => lines.push((RGB::named(GREEN), "Well Fed".to_string())),
         HungerState::Normal => {}
-        HungerState::Hungry => {
-            ctx.print_color(50, y, RGB::named(ORANGE), RGB::named(BLACK), "Hungry");
-            y -= 1;
-        }
-        HungerState::Starving => {
-            ctx.print_color(50, y, RGB::named(RED), RGB::named(BLACK), "Starving");
-            y -= 1;
-        }
+        HungerState::Hungry => lines.push((RGB::named(ORANGE), "Hungry".to_string())),
+        HungerState::Starving => lines.push((RGB::named(RED), "Starving".to_string()))
     }
     let statuses = ecs.read_storage::<StatusEffect>();
     let durations = ecs.read_storage::<Duration>();
     let names = ecs.read_storage::<Name>();
     for (status, duration, name) in (&statuses, &durations, &names).join() {
-        if status.target == *player_entity {
-            ctx.print_color(
-                50,
-                y,
-                RGB::named(RED),
-                RGB::named(BLACK),
-                &format!("{} ({})", name.name, duration.turns)
-            );
-            y -= 1;
+        if status.target == player_entity {
+            lines.push((RGB::named(RED), format!("{} ({})", name.name, duration.turns)));
         }
     }
+    lines
+}
 
-    // Draw the log
-    let log = ecs.fetch::<GameLog>();
-    let mut y = 46;
-    for s in log.entries.iter().rev() {
-        if y < 59 { ctx.print(2, y, s); }
-        y += 1;
+const MINIMAP_TOP : i32 = 35;
+const MINIMAP_WIDTH : i32 = 29;
+const MINIMAP_HEIGHT : i32 = 9;
+
+/// The tile that best stands for a block of the map: open ground if the player knows of any,
+/// otherwise whatever they have seen.
+fn map_block(map : &Map, x : i32, y : i32, scale : i32) -> Option<usize> {
+    let mut seen = None;
+    for ty in y .. i32::min(y + scale, map.height) {
+        for tx in x .. i32::min(x + scale, map.width) {
+            let idx = map.xy_idx(tx, ty);
+            if !map.revealed_tiles[idx] { continue; }
+            if map.walkable(idx) { return Some(idx); }
+            seen = Some(idx);
+        }
     }
+    seen
+}
 
-    draw_tooltips(ecs, ctx);
+fn minimap_color(map : &Map, x : i32, y : i32, scale : i32, player_pos : Point) -> RGB {
+    if player_pos.x >= x && player_pos.x < x + scale && player_pos.y >= y && player_pos.y < y + scale {
+        return RGB::named(YELLOW);
+    }
+    for ty in y .. i32::min(y + scale, map.height) {
+        for tx in x .. i32::min(x + scale, map.width) {
+            let idx = map.xy_idx(tx, ty);
+            if map.revealed_tiles[idx] && (map.tiles[idx] == TileType::DownStairs || map.tiles[idx] == TileType::UpStairs) {
+                return RGB::named(MAGENTA);
+            }
+        }
+    }
+    match map_block(map, x, y, scale) {
+        None => RGB::named(BLACK),
+        Some(idx) if !map.walkable(idx) => RGB::from_f32(0.25, 0.25, 0.3),
+        Some(idx) if map.visible_tiles[idx] => RGB::named(LIGHT_GRAY),
+        Some(_) => RGB::from_f32(0.45, 0.45, 0.45)
+    }
+}
+
+/// The whole level in miniature, as much of it as the player has seen. It sits at the foot
+/// of the sidebar, with each character showing two blocks of tiles, one above the other.
+fn draw_minimap(ecs : &World, ctx : &mut BTerm) {
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let scale = i32::max((map.width + MINIMAP_WIDTH - 1) / MINIMAP_WIDTH, (map.height + MINIMAP_HEIGHT * 2 - 1) / (MINIMAP_HEIGHT * 2));
+    let columns = (map.width + scale - 1) / scale;
+    let left = 50 + (MINIMAP_WIDTH - columns) / 2;
+
+    for row in 0 .. MINIMAP_HEIGHT {
+        for column in 0 .. columns {
+            let x = column * scale;
+            let top = minimap_color(&map, x, row * 2 * scale, scale, player_pos);
+            let bottom = minimap_color(&map, x, (row * 2 + 1) * scale, scale, player_pos);
+            ctx.set(left + column, MINIMAP_TOP + row, top, bottom, to_cp437('▀'));
+        }
+    }
+}
+
+/// Prints a log message fragment by fragment, cutting it off if it runs out of room.
+fn draw_log_entry(ctx : &mut BTerm, x : i32, y : i32, entry : &LogEntry, max_width : i32) {
+    let mut x_pos = x;
+    for fragment in entry.fragments.iter() {
+        let room = (x + max_width - x_pos).max(0) as usize;
+        let text : String = fragment.text.chars().take(room).collect();
+        ctx.print_color(x_pos, y, fragment.color, RGB::named(BLACK), &text);
+        x_pos += text.chars().count() as i32;
+    }
+    if entry.count > 1 && x_pos < x + max_width {
+        ctx.print_color(x_pos, y, RGB::named(GREY), RGB::named(BLACK), format!(" x{}", entry.count));
+    }
 }
 
 struct Tooltip {
@@ -272,6 +353,18 @@ impl Tooltip {
     }
 }
 
+fn ai_state_name(state : &AIState) -> &'static str {
+    match state {
+        AIState::Asleep => "asleep",
+        AIState::Wandering => "wandering",
+        AIState::Guarding{..} => "guarding",
+        AIState::Patrolling{..} => "patrolling",
+        AIState::Hunting => "hunting",
+        AIState::Fleeing => "fleeing",
+        AIState::Searching{..} => "searching"
+    }
+}
+
 fn draw_tooltips(ecs: &World, ctx : &mut BTerm) {
     let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
     let map = ecs.fetch::<Map>();
@@ -298,7 +391,11 @@ fn draw_tooltips(ecs: &World, ctx : &mut BTerm) {
     for (entity, position, _hidden) in (&entities, &positions, !&hidden).join() {
         if position.x == mouse_map_pos.0 && position.y == mouse_map_pos.1 {
             let mut tip = Tooltip::new();
-            tip.add(get_item_display_name(ecs, entity));
+            if let Some(behavior) = ecs.read_storage::<Behavior>().get(entity) {
+                tip.add(format!("{} ({})", get_item_display_name(ecs, entity), ai_state_name(&behavior.state)));
+            } else {
+                tip.add(get_item_display_name(ecs, entity));
+            }
 
             // Comment on attributes
             let attr = attributes.get(entity);
@@ -658,6 +755,10 @@ pub fn ranged_target(gs : &mut State, ctx : &mut BTerm, range : i32) -> (ItemMen
     let viewsheds = gs.ecs.read_storage::<Viewshed>();
 
     ctx.print_color(5, 0, RGB::named(YELLOW), RGB::named(BLACK), "Select Target:");
+    draw_hint(ctx, "Click a highlighted tile to target; click elsewhere or ESCAPE to cancel.");
+    if ctx.key == Some(VirtualKeyCode::Escape) {
+        return (ItemMenuResult::Cancel, None);
+    }
 
     // Highlight available target cells
     let mut available_cells = Vec::new();
@@ -702,7 +803,7 @@ pub fn ranged_target(gs : &mut State, ctx : &mut BTerm, range : i32) -> (ItemMen
 }
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum MainMenuSelection { NewGame, LoadGame, Quit }
+pub enum MainMenuSelection { NewGame, LoadGame, KeyBindings, Quit }
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuResult { NoSelection{ selected : MainMenuSelection }, Selected{ selected: MainMenuSelection } }
@@ -737,6 +838,13 @@ pub fn main_menu(gs : &mut State, ctx : &mut BTerm) -> MainMenuResult {
             y += 1;
         }
 
+        if selection == MainMenuSelection::KeyBindings {
+            ctx.print_color_centered(y, RGB::named(MAGENTA), RGB::named(BLACK), "Key Bindings");
+        } else {
+            ctx.print_color_centered(y, RGB::named(WHITE), RGB::named(BLACK), "Key Bindings");
+        }
+        y += 1;
+
         if selection == MainMenuSelection::Quit {
             ctx.print_color_centered(y, RGB::named(MAGENTA), RGB::named(BLACK), "Quit");
         } else {
@@ -753,7 +861,8 @@ pub fn main_menu(gs : &mut State, ctx : &mut BTerm) -> MainMenuResult {
                         match selection {
                             MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
                             MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                            MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame
+                            MainMenuSelection::KeyBindings => newselection = MainMenuSelection::LoadGame,
+                            MainMenuSelection::Quit => newselection = MainMenuSelection::KeyBindings
                         }
                         if newselection == MainMenuSelection::LoadGame && !save_exists {
                             newselection = MainMenuSelection::NewGame;
@@ -764,11 +873,12 @@ pub fn main_menu(gs : &mut State, ctx : &mut BTerm) -> MainMenuResult {
                         let mut newselection;
                         match selection {
                             MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
+                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::KeyBindings,
+                            MainMenuSelection::KeyBindings => newselection = MainMenuSelection::Quit,
                             MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
                         }
                         if newselection == MainMenuSelection::LoadGame && !save_exists {
-                            newselection = MainMenuSelection::Quit;
+                            newselection = MainMenuSelection::KeyBindings;
                         }
                         return MainMenuResult::NoSelection{ selected: newselection }
                     }
@@ -782,6 +892,57 @@ pub fn main_menu(gs : &mut State, ctx : &mut BTerm) -> MainMenuResult {
     MainMenuResult::NoSelection { selected: MainMenuSelection::NewGame }
 }
 
+#[derive(PartialEq, Copy, Clone)]
+pub enum KeyBindingResult { NoResponse, Close, Select(usize), Listen, Bind(VirtualKeyCode), StopListening, Clear, Reset }
+
+/// Lists every action with the keys bound to it. Pressing Enter waits for a key to add to
+/// the highlighted action.
+pub fn show_keybindings(ctx : &mut BTerm, selected : usize, listening : bool) -> KeyBindingResult {
+    let bindings = KEYBINDINGS.lock().unwrap();
+    let selected = usize::min(selected, ACTIONS.len() - 1);
+
+    ctx.cls();
+    ctx.draw_box(0, 0, 79, 59, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(3, 0, RGB::named(YELLOW), RGB::named(BLACK), "Key Bindings");
+    if listening {
+        ctx.print_color(3, 59, RGB::named(CYAN), RGB::named(BLACK),
+            format!("Press a key for \"{}\", or ESCAPE to cancel", ACTIONS[selected].name()));
+    } else {
+        ctx.print_color(3, 59, RGB::named(YELLOW), RGB::named(BLACK),
+            "ENTER add key, BACKSPACE clear, DELETE default, ESCAPE back");
+    }
+
+    for (i, action) in ACTIONS.iter().enumerate() {
+        let y = 3 + i as i32;
+        let color = if i == selected { RGB::named(MAGENTA) } else { RGB::named(WHITE) };
+        if i == selected { ctx.set(3, y, RGB::named(YELLOW), RGB::named(BLACK), to_cp437('►')); }
+        ctx.print_color(5, y, color, RGB::named(BLACK), action.name());
+        ctx.print_color(30, y, RGB::named(GREY), RGB::named(BLACK), bindings.describe(*action));
+    }
+
+    match ctx.key {
+        None => KeyBindingResult::NoResponse,
+        Some(key) if listening => {
+            match key {
+                VirtualKeyCode::Escape => KeyBindingResult::StopListening,
+                key if is_bindable(key) => KeyBindingResult::Bind(key),
+                _ => KeyBindingResult::NoResponse
+            }
+        }
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => KeyBindingResult::Close,
+                VirtualKeyCode::Up => KeyBindingResult::Select((selected + ACTIONS.len() - 1) % ACTIONS.len()),
+                VirtualKeyCode::Down => KeyBindingResult::Select((selected + 1) % ACTIONS.len()),
+                VirtualKeyCode::Return => KeyBindingResult::Listen,
+                VirtualKeyCode::Back => KeyBindingResult::Clear,
+                VirtualKeyCode::Delete => KeyBindingResult::Reset,
+                _ => KeyBindingResult::NoResponse
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult { NoSelection, QuitToMenu }
 
@@ -846,6 +1007,1023 @@ pub fn show_cheat_mode(_gs : &mut State, ctx : &mut BTerm) -> CheatMenuResult {
     }
 }
 
+#[derive(PartialEq, Copy, Clone)]
+pub enum LookResult { NoResponse, Close, Select(usize) }
+
+/// Everything the player can currently see that is worth a closer look: creatures first,
+/// then items, then features, each nearest first.
+pub fn look_targets(ecs : &World) -> Vec<Entity> {
+    let map = ecs.fetch::<Map>();
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let names = ecs.read_storage::<Name>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let pools = ecs.read_storage::<Pools>();
+    let items = ecs.read_storage::<Item>();
+
+    let mut targets : Vec<(Entity, i32, f32)> = Vec::new();
+    for (entity, pos, _name, _hidden) in (&entities, &positions, &names, !&hidden).join() {
+        if entity == *player_entity { continue; }
+        let idx = map.xy_idx(pos.x, pos.y);
+        if idx >= map.visible_tiles.len() || !map.visible_tiles[idx] { continue; }
+        let group = if pools.get(entity).is_some() { 0 } else if items.get(entity).is_some() { 1 } else { 2 };
+        let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, Point::new(pos.x, pos.y));
+        targets.push((entity, group, distance));
+    }
+    targets.sort_by(|a, b| a.1.cmp(&b.1).then(a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal)));
+    targets.iter().map(|t| t.0).collect()
+}
+
+/// Breaks text up into lines no wider than `width`, at word boundaries.
+fn wrap_text(text : &str, width : usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + word.len() + 1 > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() { line += " "; }
+        line += word;
+    }
+    if !line.is_empty() { lines.push(line); }
+    lines
+}
+
+fn health_estimate(pools : &Pools) -> (&'static str, RGB) {
+    let ratio = pools.hit_points.current as f32 / i32::max(1, pools.hit_points.max) as f32;
+    if ratio >= 1.0 { ("unhurt", RGB::named(GREEN)) }
+    else if ratio > 0.66 { ("lightly wounded", RGB::named(YELLOW)) }
+    else if ratio > 0.33 { ("wounded", RGB::named(ORANGE)) }
+    else { ("badly wounded", RGB::named(RED)) }
+}
+
+fn slot_name(slot : EquipmentSlot) -> &'static str {
+    match slot {
+        EquipmentSlot::Melee => "Weapon",
+        EquipmentSlot::Shield => "Shield",
+        EquipmentSlot::Head => "Head",
+        EquipmentSlot::Torso => "Torso",
+        EquipmentSlot::Legs => "Legs",
+        EquipmentSlot::Feet => "Feet",
+        EquipmentSlot::Hands => "Hands"
+    }
+}
+
+/// How hurt a creature is, how it feels about the player, and what it is carrying and
+/// suffering from.
+fn describe_creature(ecs : &World, entity : Entity, lines : &mut Vec<(String, RGB)>) {
+    let white = RGB::named(WHITE);
+    if let Some(pools) = ecs.read_storage::<Pools>().get(entity) {
+        let (health, color) = health_estimate(pools);
+        lines.push((format!("Level {}, {}", pools.level, health), color));
+    }
+    if let Some(behavior) = ecs.read_storage::<Behavior>().get(entity) {
+        lines.push((format!("It is {}.", ai_state_name(&behavior.state)), white));
+    }
+    if let Some(faction) = ecs.read_storage::<Faction>().get(entity) {
+        let (attitude, color) = match faction_reaction(&faction.name, "Player", &RAWS.lock().unwrap()) {
+            Reaction::Attack => ("Hostile", RGB::named(RED)),
+            Reaction::Flee => ("Afraid of you", RGB::named(YELLOW)),
+            Reaction::Ignore => ("Indifferent", RGB::named(GREEN))
+        };
+        lines.push((attitude.to_string(), color));
+    }
+
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let durations = ecs.read_storage::<Duration>();
+    let names = ecs.read_storage::<Name>();
+    for (status, duration, name) in (&statuses, &durations, &names).join() {
+        if status.target == entity {
+            lines.push((format!("{} ({} turns)", name.name, duration.turns), RGB::named(MAGENTA)));
+        }
+    }
+
+    let entities = ecs.entities();
+    let equipped = ecs.read_storage::<Equipped>();
+    let gear : Vec<Entity> = (&entities, &equipped).join()
+        .filter(|(_, equipped_by)| equipped_by.owner == entity)
+        .map(|(item, _)| item)
+        .collect();
+    if !gear.is_empty() {
+        lines.push(("".to_string(), white));
+        lines.push(("Equipped:".to_string(), white));
+        for item in gear {
+            lines.push((format!(" {}", get_item_display_name(ecs, item)), get_item_color(ecs, item)));
+        }
+    }
+}
+
+/// Mundane things are always known; magic items only once they have been identified.
+fn is_identified(ecs : &World, entity : Entity) -> bool {
+    match (ecs.read_storage::<MagicItem>().get(entity), ecs.read_storage::<Name>().get(entity)) {
+        (Some(_), Some(name)) => ecs.fetch::<MasterDungeonMap>().identified_items.contains(&name.name),
+        _ => true
+    }
+}
+
+/// What an item does. Magic items keep their secrets until they are identified.
+fn describe_item(ecs : &World, entity : Entity, lines : &mut Vec<(String, RGB)>) {
+    let white = RGB::named(WHITE);
+    let known = is_identified(ecs, entity);
+    if let Some(item) = ecs.read_storage::<Item>().get(entity) {
+        lines.push((format!("{:.0} lbs, worth {:.0} gold", item.weight_lbs, item.base_value), white));
+    }
+    if !known {
+        lines.push(("Its properties are unknown.".to_string(), RGB::named(GREY)));
+        return;
+    }
+
+    if let Some(weapon) = ecs.read_storage::<MeleeWeapon>().get(entity) {
+        let attribute = match weapon.attribute {
+            WeaponAttribute::Might => "Might",
+            WeaponAttribute::Quickness => "Quickness"
+        };
+        lines.push((format!("Damage: {}d{}{:+}", weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus), white));
+        lines.push((format!("To hit: {:+} ({})", weapon.hit_bonus, attribute), white));
+    }
+    if let Some(wearable) = ecs.read_storage::<Wearable>().get(entity) {
+        lines.push((format!("Armor: {:+.0} ({})", wearable.armor_class, slot_name(wearable.slot)), white));
+    }
+    if let Some(healing) = ecs.read_storage::<ProvidesHealing>().get(entity) {
+        lines.push((format!("Heals {} hit points", healing.heal_amount), white));
+    }
+    if let Some(mana) = ecs.read_storage::<ProvidesMana>().get(entity) {
+        lines.push((format!("Restores {} mana", mana.mana_amount), white));
+    }
+    if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
+        lines.push((format!("Inflicts {} damage", damage.damage), white));
+    }
+    if let Some(ranged) = ecs.read_storage::<Ranged>().get(entity) {
+        lines.push((format!("Range: {}", ranged.range), white));
+    }
+    if let Some(aoe) = ecs.read_storage::<AreaOfEffect>().get(entity) {
+        lines.push((format!("Area of effect: {}", aoe.radius), white));
+    }
+    if let Some(consumable) = ecs.read_storage::<Consumable>().get(entity) {
+        if consumable.max_charges > 1 {
+            lines.push((format!("Charges: {}/{}", consumable.charges, consumable.max_charges), white));
+        }
+    }
+    if ecs.read_storage::<CursedItem>().get(entity).is_some() {
+        lines.push(("Cursed!".to_string(), RGB::named(RED)));
+    }
+}
+
+/// Doors, levers and any traps the player knows about.
+fn describe_feature(ecs : &World, entity : Entity, lines : &mut Vec<(String, RGB)>) {
+    let white = RGB::named(WHITE);
+    if let Some(door) = ecs.read_storage::<Door>().get(entity) {
+        lines.push((if door.open { "It is open." } else { "It is closed." }.to_string(), white));
+    }
+    if ecs.read_storage::<Lock>().get(entity).is_some() {
+        lines.push(("It is locked.".to_string(), RGB::named(YELLOW)));
+    }
+    if let Some(lever) = ecs.read_storage::<Lever>().get(entity) {
+        lines.push((if lever.pulled { "It has been pulled." } else { "It is up." }.to_string(), white));
+    }
+    if ecs.read_storage::<EntryTrigger>().get(entity).is_some() {
+        lines.push(("A trap!".to_string(), RGB::named(RED)));
+        if let Some(disarm) = ecs.read_storage::<Disarmable>().get(entity) {
+            lines.push((format!("Disarm difficulty: {}", disarm.difficulty), white));
+        }
+    }
+}
+
+/// Steps a cursor through whatever the player can see, describing each in the side panel.
+pub fn show_look_mode(gs : &mut State, ctx : &mut BTerm, index : usize) -> LookResult {
+    let targets = look_targets(&gs.ecs);
+    if targets.is_empty() {
+        gs.ecs.write_resource::<GameLog>().log(LogCategory::System, "There is nothing of interest in sight.");
+        return LookResult::Close;
+    }
+    let index = usize::min(index, targets.len() - 1);
+    let target = targets[index];
+    let ecs = &gs.ecs;
+
+    // Highlight the target on the map
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
+    if let Some(pos) = ecs.read_storage::<Position>().get(target) {
+        let screen_x = pos.x - min_x + 1;
+        let screen_y = pos.y - min_y + 1;
+        if screen_x > 0 && screen_x < 49 && screen_y > 0 && screen_y < 45 {
+            ctx.set_bg(screen_x, screen_y, RGB::named(CYAN));
+        }
+    }
+
+    // An unidentified item's description would give away what it is
+    let mut lines : Vec<(String, RGB)> = Vec::new();
+    if let (Some(description), true) = (ecs.read_storage::<Description>().get(target), is_identified(ecs, target)) {
+        for line in wrap_text(&description.text, 27) {
+            lines.push((line, RGB::named(LIGHT_GRAY)));
+        }
+        lines.push(("".to_string(), RGB::named(WHITE)));
+    }
+    if ecs.read_storage::<Pools>().get(target).is_some() {
+        describe_creature(ecs, target, &mut lines);
+    } else if ecs.read_storage::<Item>().get(target).is_some() {
+        describe_item(ecs, target, &mut lines);
+    } else {
+        describe_feature(ecs, target, &mut lines);
+    }
+
+    ctx.draw_box(49, 0, 30, 45, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(51, 0, RGB::named(YELLOW), RGB::named(BLACK), "Look");
+    ctx.print_color(51, 1, get_item_color(ecs, target), RGB::named(BLACK), get_item_display_name(ecs, target));
+    for (i, (line, color)) in lines.iter().take(38).enumerate() {
+        ctx.print_color(51, 3 + i as i32, *color, RGB::named(BLACK), line);
+    }
+    ctx.print_color(51, 42, RGB::named(GREY), RGB::named(BLACK), format!("({}/{})", index + 1, targets.len()));
+    ctx.print_color(51, 43, RGB::named(YELLOW), RGB::named(BLACK), "TAB/arrows cycle, ESC exits");
+
+    let count = targets.len();
+    match ctx.key {
+        None => LookResult::NoResponse,
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => LookResult::Close,
+                VirtualKeyCode::Tab | VirtualKeyCode::Right | VirtualKeyCode::Down => LookResult::Select((index + 1) % count),
+                VirtualKeyCode::Left | VirtualKeyCode::Up => LookResult::Select((index + count - 1) % count),
+                _ => LookResult::NoResponse
+            }
+        }
+    }
+}
+
+type BonusPicker = fn(&AttributeBonus) -> Option<i32>;
+
+fn signed(value : i32) -> String {
+    format!("{:+}", value)
+}
+
+/// Where an attribute's modifiers come from: worn gear, and whatever is afflicting the player.
+fn attribute_sources(ecs : &World, owner : Entity, pick : BonusPicker) -> Vec<(String, i32)> {
+    let entities = ecs.entities();
+    let bonuses = ecs.read_storage::<AttributeBonus>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let names = ecs.read_storage::<Name>();
+
+    let mut sources = Vec::new();
+    for (entity, bonus) in (&entities, &bonuses).join() {
+        let applies = equipped.get(entity).map(|e| e.owner == owner).unwrap_or(false)
+            || statuses.get(entity).map(|s| s.target == owner).unwrap_or(false);
+        if let (true, Some(amount)) = (applies, pick(bonus)) {
+            let name = if equipped.get(entity).is_some() { get_item_display_name(ecs, entity) }
+                else { names.get(entity).map(|n| n.name.clone()).unwrap_or_else(|| "Unknown".to_string()) };
+            sources.push((name, amount));
+        }
+    }
+    sources
+}
+
+/// Everything there is to know about the player, laid out over two columns.
+pub fn show_character_sheet(gs : &mut State, ctx : &mut BTerm) -> ItemMenuResult {
+    let ecs = &gs.ecs;
+    let player_entity = *ecs.fetch::<Entity>();
+    let pools_storage = ecs.read_storage::<Pools>();
+    let pools = pools_storage.get(player_entity).unwrap();
+    let attributes_storage = ecs.read_storage::<Attributes>();
+    let attr = attributes_storage.get(player_entity).unwrap();
+    let skills_storage = ecs.read_storage::<Skills>();
+    let skills = skills_storage.get(player_entity).unwrap();
+    let white = RGB::named(WHITE);
+    let yellow = RGB::named(YELLOW);
+    let grey = RGB::named(GREY);
+
+    // Left column: the character, their attributes and skills
+    let mut left : Vec<(String, RGB)> = Vec::new();
+    left.push((format!("Level {}", pools.level), yellow));
+    left.push((format!("XP: {} ({} to next level)", pools.xp, pools.level * 1000 - pools.xp), white));
+    left.push((format!("Health: {}/{}", pools.hit_points.current, pools.hit_points.max), white));
+    left.push((format!("Mana: {}/{}", pools.mana.current, pools.mana.max), white));
+    left.push((format!("Gold: {:.1}", pools.gold), RGB::named(GOLD)));
+    left.push(("".to_string(), white));
+
+    left.push(("Attributes".to_string(), yellow));
+    let attribute_rows : [(&str, &Attribute, BonusPicker); 4] = [
+        ("Might", &attr.might, |b| b.might),
+        ("Fitness", &attr.fitness, |b| b.fitness),
+        ("Quickness", &attr.quickness, |b| b.quickness),
+        ("Intelligence", &attr.intelligence, |b| b.intelligence)
+    ];
+    for (name, attribute, pick) in attribute_rows.iter() {
+        left.push((format!("{:<13}{:>3} = {} {}  bonus {}", name, attribute.base + attribute.modifiers,
+            attribute.base, signed(attribute.modifiers), signed(attribute.bonus)), white));
+        for (source, amount) in attribute_sources(ecs, player_entity, *pick) {
+            left.push((format!("  {} {}", signed(amount), source), grey));
+        }
+    }
+    left.push(("".to_string(), white));
+
+    left.push(("Skills".to_string(), yellow));
+    for (name, skill) in [("Melee", Skill::Melee), ("Defense", Skill::Defense), ("Magic", Skill::Magic),
+        ("Stealth", Skill::Stealth), ("Perception", Skill::Perception)].iter()
+    {
+        left.push((format!("{:<13}{:>3}", name, signed(skill_bonus(skill.clone(), skills))), white));
+    }
+    if let Some(stealth) = ecs.read_storage::<Stealth>().get(player_entity) {
+        left.push((format!("Stealth rating {}, after armor", signed(stealth.value)), grey));
+    }
+    if let Some(perception) = ecs.read_storage::<Perception>().get(player_entity) {
+        left.push((format!("Perception rating {}", signed(perception.value)), grey));
+    }
+    left.push(("".to_string(), white));
+
+    left.push(("Burden".to_string(), yellow));
+    left.push((format!("Carrying {:.0} of {} lbs", pools.total_weight, (attr.might.base + attr.might.modifiers) * 15), white));
+    left.push((format!("Initiative penalty: {:.0}", pools.total_initiative_penalty), white));
+
+    // Right column: fighting, protection and condition
+    let mut right : Vec<(String, RGB)> = Vec::new();
+    let entities = ecs.entities();
+    let equipped = ecs.read_storage::<Equipped>();
+    let melee_weapons = ecs.read_storage::<MeleeWeapon>();
+    let wearables = ecs.read_storage::<Wearable>();
+    let hunger = ecs.read_storage::<HungerClock>();
+    let well_fed = hunger.get(player_entity).map(|h| h.state == HungerState::WellFed).unwrap_or(false);
+
+    // Mirrors the sums that the melee combat system makes
+    right.push(("Attacks".to_string(), yellow));
+    let mut weapons : Vec<(String, MeleeWeapon)> = (&entities, &equipped, &melee_weapons).join()
+        .filter(|(_, e, _)| e.owner == player_entity && e.slot == EquipmentSlot::Melee)
+        .map(|(entity, _, weapon)| (get_item_display_name(ecs, entity), weapon.clone()))
+        .collect();
+    if weapons.is_empty() {
+        weapons.push(("Unarmed".to_string(), MeleeWeapon{ attribute: WeaponAttribute::Might, hit_bonus: 0,
+            damage_n_dice: 1, damage_die_type: 4, damage_bonus: 0, proc_chance: None, proc_target: None }));
+    }
+    let melee_skill = skill_bonus(Skill::Melee, skills);
+    for (name, weapon) in weapons.iter() {
+        let attribute_bonus = match weapon.attribute {
+            WeaponAttribute::Might => attr.might.bonus,
+            WeaponAttribute::Quickness => attr.quickness.bonus
+        };
+        let to_hit = attribute_bonus + melee_skill + weapon.hit_bonus + if well_fed { 1 } else { 0 };
+        let damage_bonus = attr.might.bonus + melee_skill * 2 + weapon.damage_bonus;
+        right.push((name.clone(), white));
+        right.push((format!("  To hit: 1d20{}", signed(to_hit)), white));
+        right.push((format!("  Damage: {}d{}{}", weapon.damage_n_dice, weapon.damage_die_type, signed(damage_bonus)), white));
+        if let Some(chance) = weapon.proc_chance {
+            right.push((format!("  {:.0}% chance of a special effect", chance * 100.0), grey));
+        }
+    }
+    right.push(("".to_string(), white));
+
+    right.push(("Armor Class".to_string(), yellow));
+    let base_armor = ecs.read_storage::<NaturalAttackDefense>().get(player_entity)
+        .and_then(|n| n.armor_class).unwrap_or(10);
+    let defense_skill = skill_bonus(Skill::Defense, skills);
+    let mut armor_total = 0.0;
+    right.push((format!("  {:>3} base", base_armor), grey));
+    right.push((format!("  {:>3} quickness", signed(attr.quickness.bonus)), grey));
+    right.push((format!("  {:>3} defense skill", signed(defense_skill)), grey));
+    for (entity, _, wearable) in (&entities, &equipped, &wearables).join().filter(|(_, e, _)| e.owner == player_entity) {
+        armor_total += wearable.armor_class;
+        right.push((format!("  {:>3} {}", signed(wearable.armor_class as i32), get_item_display_name(ecs, entity)), grey));
+    }
+    right.push((format!("Total: {}", base_armor + attr.quickness.bonus + defense_skill + armor_total as i32), white));
+    right.push(("".to_string(), white));
+
+    right.push(("Resistances".to_string(), yellow));
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let levitating = (&statuses, &ecs.read_storage::<Levitation>()).join().any(|(s, _)| s.target == player_entity);
+    let mut resistances = Vec::new();
+    if pools.god_mode { resistances.push("Immune to all damage"); }
+    if ecs.read_storage::<Flying>().get(player_entity).is_some() || levitating {
+        resistances.push("Floats over water and pressure plates");
+    }
+    if ecs.read_storage::<Swimmer>().get(player_entity).is_some() { resistances.push("Can't drown"); }
+    if resistances.is_empty() { resistances.push("None"); }
+    for r in resistances {
+        right.push((format!("  {}", r), white));
+    }
+    right.push(("".to_string(), white));
+
+    right.push(("Condition".to_string(), yellow));
+    if let Some(hc) = hunger.get(player_entity) {
+        let (state, color) = match hc.state {
+            HungerState::WellFed => ("Well Fed", RGB::named(GREEN)),
+            HungerState::Normal => ("Normal", white),
+            HungerState::Hungry => ("Hungry", RGB::named(ORANGE)),
+            HungerState::Starving => ("Starving", RGB::named(RED))
+        };
+        right.push((format!("  {} ({} turns)", state, hc.duration), color));
+    }
+    let durations = ecs.read_storage::<Duration>();
+    let names = ecs.read_storage::<Name>();
+    for (status, duration, name) in (&statuses, &durations, &names).join() {
+        if status.target == player_entity {
+            right.push((format!("  {} ({} turns)", name.name, duration.turns), RGB::named(MAGENTA)));
+        }
+    }
+
+    ctx.cls();
+    ctx.draw_box(0, 0, 79, 59, white, RGB::named(BLACK));
+    ctx.print_color(3, 0, yellow, RGB::named(BLACK), "Character Sheet");
+    ctx.print_color(3, 59, yellow, RGB::named(BLACK), "ESCAPE to close");
+    for (i, (line, color)) in left.iter().take(56).enumerate() {
+        ctx.print_color(2, 2 + i as i32, *color, RGB::named(BLACK), line);
+    }
+    for (i, (line, color)) in right.iter().take(56).enumerate() {
+        ctx.print_color(46, 2 + i as i32, *color, RGB::named(BLACK), line);
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum EquipmentResult { NoResponse, Cancel, Move(usize), Open, Equip(Entity), Unequip(Entity) }
+
+/// Where each slot sits on the little figure drawn beside the slot list.
+const PAPER_DOLL : [(i32, i32, char, EquipmentSlot); 10] = [
+    (2, 0, 'O', EquipmentSlot::Head),
+    (0, 1, '/', EquipmentSlot::Melee),
+    (1, 1, '-', EquipmentSlot::Hands),
+    (2, 1, '#', EquipmentSlot::Torso),
+    (3, 1, '-', EquipmentSlot::Hands),
+    (4, 1, ']', EquipmentSlot::Shield),
+    (1, 2, '/', EquipmentSlot::Legs),
+    (3, 2, '\\', EquipmentSlot::Legs),
+    (1, 3, '_', EquipmentSlot::Feet),
+    (3, 3, '_', EquipmentSlot::Feet)
+];
+
+fn equipped_in_slot(ecs : &World, owner : Entity, slot : EquipmentSlot) -> Option<Entity> {
+    let entities = ecs.entities();
+    let equipped = ecs.read_storage::<Equipped>();
+    (&entities, &equipped).join()
+        .find(|(_, e)| e.owner == owner && e.slot == slot)
+        .map(|(entity, _)| entity)
+}
+
+/// Backpack items that could go into a slot.
+fn equipment_candidates(ecs : &World, owner : Entity, slot : EquipmentSlot) -> Vec<Entity> {
+    let entities = ecs.entities();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let equippable = ecs.read_storage::<Equippable>();
+    (&entities, &backpack, &equippable).join()
+        .filter(|(_, pack, e)| pack.owner == owner && e.slot == slot)
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+/// Every slot on one screen: Up/Down picks a slot, Enter lists what could go there and R
+/// takes off whatever is there now.
+pub fn show_equipment(gs : &mut State, ctx : &mut BTerm, slot : usize) -> EquipmentResult {
+    let ecs = &gs.ecs;
+    let player_entity = *ecs.fetch::<Entity>();
+    let slot = usize::min(slot, EQUIPMENT_SLOTS.len() - 1);
+    let white = RGB::named(WHITE);
+    let black = RGB::named(BLACK);
+
+    ctx.draw_box(10, 10, 56, 16, white, black);
+    ctx.print_color(13, 10, RGB::named(YELLOW), black, "Equipment");
+    ctx.print_color(13, 26, RGB::named(YELLOW), black, "UP/DOWN select, ENTER swap, R remove, ESC close");
+
+    let mut worn = Vec::new();
+    for (i, s) in EQUIPMENT_SLOTS.iter().enumerate() {
+        let item = equipped_in_slot(ecs, player_entity, *s);
+        let y = 13 + i as i32 * 2;
+        let label_color = if i == slot { RGB::named(YELLOW) } else { RGB::named(GREY) };
+        if i == slot { ctx.set(22, y, RGB::named(YELLOW), black, to_cp437('►')); }
+        ctx.print_color(24, y, label_color, black, format!("{:<8}", slot_name(*s)));
+        match item {
+            Some(item) => ctx.print_color(33, y, get_item_color(ecs, item), black, get_item_display_name(ecs, item)),
+            None => ctx.print_color(33, y, RGB::named(DARK_GRAY), black, "(nothing)")
+        }
+        let spare = equipment_candidates(ecs, player_entity, *s).len();
+        if spare > 0 {
+            ctx.print_color(60, y, RGB::named(GREY), black, format!("+{}", spare));
+        }
+        worn.push(item);
+    }
+
+    for (x, y, glyph, s) in PAPER_DOLL.iter() {
+        let color = if *s == EQUIPMENT_SLOTS[slot] { RGB::named(YELLOW) }
+            else if let Some(item) = equipped_in_slot(ecs, player_entity, *s) { get_item_color(ecs, item) }
+            else { RGB::named(DARK_GRAY) };
+        ctx.set(14 + x, 15 + y, color, black, to_cp437(*glyph));
+    }
+
+    match ctx.key {
+        None => EquipmentResult::NoResponse,
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => EquipmentResult::Cancel,
+                VirtualKeyCode::Up => EquipmentResult::Move((slot + EQUIPMENT_SLOTS.len() - 1) % EQUIPMENT_SLOTS.len()),
+                VirtualKeyCode::Down => EquipmentResult::Move((slot + 1) % EQUIPMENT_SLOTS.len()),
+                VirtualKeyCode::Return => EquipmentResult::Open,
+                VirtualKeyCode::R => match worn[slot] {
+                    Some(item) => EquipmentResult::Unequip(item),
+                    None => EquipmentResult::NoResponse
+                },
+                _ => EquipmentResult::NoResponse
+            }
+        }
+    }
+}
+
+/// The numbers worth comparing between two pieces of gear. Magic items that haven't been
+/// identified only give away what they weigh.
+fn equipment_stats(ecs : &World, item : Option<Entity>) -> Vec<(&'static str, f32)> {
+    let mut stats = Vec::new();
+    let item = match item { Some(item) => item, None => return stats };
+    if let Some(i) = ecs.read_storage::<Item>().get(item) {
+        stats.push(("Weight", i.weight_lbs));
+        stats.push(("Initiative", i.initiative_penalty));
+    }
+    if let (Some(_), Some(name)) = (ecs.read_storage::<MagicItem>().get(item), ecs.read_storage::<Name>().get(item)) {
+        if !ecs.fetch::<MasterDungeonMap>().identified_items.contains(&name.name) { return stats; }
+    }
+    if let Some(weapon) = ecs.read_storage::<MeleeWeapon>().get(item) {
+        let average = weapon.damage_n_dice as f32 * (weapon.damage_die_type as f32 + 1.0) / 2.0;
+        stats.push(("Damage", average + weapon.damage_bonus as f32));
+        stats.push(("To hit", weapon.hit_bonus as f32));
+    }
+    if let Some(wearable) = ecs.read_storage::<Wearable>().get(item) {
+        stats.push(("Armor", wearable.armor_class));
+    }
+    if let Some(bonus) = ecs.read_storage::<AttributeBonus>().get(item) {
+        stats.push(("Might", bonus.might.unwrap_or(0) as f32));
+        stats.push(("Fitness", bonus.fitness.unwrap_or(0) as f32));
+        stats.push(("Quickness", bonus.quickness.unwrap_or(0) as f32));
+        stats.push(("Intelligence", bonus.intelligence.unwrap_or(0) as f32));
+    }
+    stats
+}
+
+/// Lists what in the backpack fits the chosen slot, comparing the highlighted item against
+/// whatever is being worn there now.
+pub fn show_equipment_swap(gs : &mut State, ctx : &mut BTerm, slot : usize, selected : usize) -> EquipmentResult {
+    let ecs = &gs.ecs;
+    let player_entity = *ecs.fetch::<Entity>();
+    let equipment_slot = EQUIPMENT_SLOTS[usize::min(slot, EQUIPMENT_SLOTS.len() - 1)];
+    let candidates = equipment_candidates(ecs, player_entity, equipment_slot);
+    let current = equipped_in_slot(ecs, player_entity, equipment_slot);
+    let white = RGB::named(WHITE);
+    let black = RGB::named(BLACK);
+
+    ctx.draw_box(5, 8, 70, 30, white, black);
+    ctx.print_color(8, 8, RGB::named(YELLOW), black, format!("Equip: {}", slot_name(equipment_slot)));
+    ctx.print_color(8, 38, RGB::named(YELLOW), black, "UP/DOWN select, ENTER equip, ESC back");
+
+    if candidates.is_empty() {
+        ctx.print_color(8, 10, RGB::named(GREY), black, "You have nothing that fits this slot.");
+        return match ctx.key {
+            Some(VirtualKeyCode::Escape) => EquipmentResult::Cancel,
+            _ => EquipmentResult::NoResponse
+        };
+    }
+    let selected = usize::min(selected, candidates.len() - 1);
+    let candidate = candidates[selected];
+
+    for (i, item) in candidates.iter().take(26).enumerate() {
+        let y = 10 + i as i32;
+        if i == selected { ctx.set(7, y, RGB::named(YELLOW), black, to_cp437('►')); }
+        ctx.print_color(9, y, get_item_color(ecs, *item), black, get_item_display_name(ecs, *item).chars().take(22).collect::<String>());
+    }
+
+    // The comparison: what's worn now against the highlighted candidate
+    ctx.print_color(46, 10, RGB::named(GREY), black, "Now");
+    ctx.print_color(56, 10, RGB::named(GREY), black, "Candidate");
+    let now_name = match current { Some(item) => get_item_display_name(ecs, item), None => "(nothing)".to_string() };
+    ctx.print_color(46, 11, white, black, now_name.chars().take(9).collect::<String>());
+    ctx.print_color(56, 11, get_item_color(ecs, candidate), black, get_item_display_name(ecs, candidate).chars().take(18).collect::<String>());
+
+    let now_stats = equipment_stats(ecs, current);
+    let candidate_stats = equipment_stats(ecs, Some(candidate));
+    let mut y = 13;
+    for stat in ["Damage", "To hit", "Armor", "Might", "Fitness", "Quickness", "Intelligence", "Weight", "Initiative"].iter() {
+        let now = now_stats.iter().find(|(n, _)| n == stat).map(|(_, v)| *v);
+        let new = candidate_stats.iter().find(|(n, _)| n == stat).map(|(_, v)| *v);
+        if now.unwrap_or(0.0) == 0.0 && new.unwrap_or(0.0) == 0.0 { continue; }
+        let now = now.unwrap_or(0.0);
+        let new = new.unwrap_or(0.0);
+        // Lugging less about is the better deal
+        let lower_is_better = *stat == "Weight" || *stat == "Initiative";
+        let better = if lower_is_better { new < now } else { new > now };
+        let color = if (new - now).abs() < f32::EPSILON { white } else if better { RGB::named(GREEN) } else { RGB::named(RED) };
+        ctx.print_color(33, y, RGB::named(GREY), black, *stat);
+        ctx.print_color(46, y, white, black, format!("{:.1}", now));
+        ctx.print_color(56, y, color, black, format!("{:.1} ({:+.1})", new, new - now));
+        y += 1;
+    }
+    if candidate_stats.len() <= 2 && ecs.read_storage::<MagicItem>().get(candidate).is_some() {
+        ctx.print_color(33, y + 1, RGB::named(GREY), black, "Its properties are unknown.");
+    }
+
+    match ctx.key {
+        None => EquipmentResult::NoResponse,
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => EquipmentResult::Cancel,
+                VirtualKeyCode::Up => EquipmentResult::Move((selected + candidates.len() - 1) % candidates.len()),
+                VirtualKeyCode::Down => EquipmentResult::Move((selected + 1) % candidates.len()),
+                VirtualKeyCode::Return => EquipmentResult::Equip(candidate),
+                _ => EquipmentResult::NoResponse
+            }
+        }
+    }
+}
+
+/// Every command and the keys it is bound to, built from the live key bindings so that it
+/// is never out of date.
+pub fn show_help(ctx : &mut BTerm) -> ItemMenuResult {
+    let bindings = KEYBINDINGS.lock().unwrap();
+    ctx.cls();
+    ctx.draw_box(0, 0, 79, 59, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(3, 0, RGB::named(YELLOW), RGB::named(BLACK), "Help");
+    ctx.print_color(3, 59, RGB::named(YELLOW), RGB::named(BLACK), "ESCAPE to close. Keys can be changed from the main menu.");
+
+    ctx.print_color(2, 2, RGB::named(GREY), RGB::named(BLACK), "Command");
+    ctx.print_color(21, 2, RGB::named(GREY), RGB::named(BLACK), "Keys");
+    ctx.print_color(42, 2, RGB::named(GREY), RGB::named(BLACK), "What it does");
+    let mut y = 4;
+    for action in ACTIONS.iter() {
+        ctx.print_color(2, y, RGB::named(WHITE), RGB::named(BLACK), action.name());
+        ctx.print_color(21, y, RGB::named(CYAN), RGB::named(BLACK), bindings.describe(*action).chars().take(20).collect::<String>());
+        ctx.print_color(42, y, RGB::named(LIGHT_GRAY), RGB::named(BLACK), action.description());
+        y += 1;
+    }
+
+    // The hotkeys aren't rebindable, so they are listed by hand
+    y += 1;
+    ctx.print_color(2, y, RGB::named(WHITE), RGB::named(BLACK), "Quick use");
+    ctx.print_color(21, y, RGB::named(CYAN), RGB::named(BLACK), "Shift+1..9");
+    ctx.print_color(42, y, RGB::named(LIGHT_GRAY), RGB::named(BLACK), "Use a consumable from the sidebar");
+    y += 1;
+    ctx.print_color(2, y, RGB::named(WHITE), RGB::named(BLACK), "Cast spell");
+    ctx.print_color(21, y, RGB::named(CYAN), RGB::named(BLACK), "Ctrl+1..9");
+    ctx.print_color(42, y, RGB::named(LIGHT_GRAY), RGB::named(BLACK), "Cast a spell from the sidebar");
+    y += 1;
+    ctx.print_color(2, y, RGB::named(WHITE), RGB::named(BLACK), "Tooltips");
+    ctx.print_color(21, y, RGB::named(CYAN), RGB::named(BLACK), "Mouse");
+    ctx.print_color(42, y, RGB::named(LIGHT_GRAY), RGB::named(BLACK), "Hover over the map to see what's there");
+    y += 1;
+    ctx.print_color(2, y, RGB::named(WHITE), RGB::named(BLACK), "Travel");
+    ctx.print_color(21, y, RGB::named(CYAN), RGB::named(BLACK), "Left click");
+    ctx.print_color(42, y, RGB::named(LIGHT_GRAY), RGB::named(BLACK), "Walk there, fight or pick up");
+    y += 1;
+    ctx.print_color(2, y, RGB::named(WHITE), RGB::named(BLACK), "Context menu");
+    ctx.print_color(21, y, RGB::named(CYAN), RGB::named(BLACK), "Right click");
+    ctx.print_color(42, y, RGB::named(LIGHT_GRAY), RGB::named(BLACK), "Examine, throw or use things there");
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse
+    }
+}
+
+/// A line of advice along the top of the message log, for screens that need explaining.
+fn draw_hint(ctx : &mut BTerm, hint : &str) {
+    ctx.print_color(2, 45, RGB::named(CYAN), RGB::named(BLACK), format!(" {} ", hint));
+}
+
+/// Potions and the like can only be lobbed so far.
+const THROW_RANGE : f32 = 6.0;
+
+/// Something to do with a spot on the map, offered by a right click.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ContextAction {
+    Examine(Entity),
+    Travel(Point),
+    PickUp(Point),
+    Attack(Entity),
+    Throw(Entity, Point),
+    UseOn(Entity, Point)
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ContextMenuResult { NoResponse, Cancel, Selected(ContextAction) }
+
+/// Everything that makes sense to do with a spot, and how to describe it in the menu.
+pub fn context_actions(ecs : &World, target : Point) -> Vec<(ContextAction, String)> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+    let (visible, revealed) = {
+        let map = ecs.fetch::<Map>();
+        let idx = map.xy_idx(target.x, target.y);
+        (map.visible_tiles[idx], map.revealed_tiles[idx])
+    };
+    let distance = DistanceAlg::Pythagoras.distance2d(player_pos, target);
+    let mut actions = Vec::new();
+
+    if let Some(hostile) = crate::player::hostile_at(ecs, target) {
+        if distance < 1.5 {
+            let name = ecs.read_storage::<Name>().get(hostile).map(|n| n.name.clone()).unwrap_or_default();
+            actions.push((ContextAction::Attack(hostile), format!("Attack the {}", name)));
+        }
+    }
+    if visible {
+        let positions = ecs.read_storage::<Position>();
+        for entity in look_targets(ecs) {
+            if let Some(pos) = positions.get(entity) {
+                if pos.x == target.x && pos.y == target.y {
+                    let name = if ecs.read_storage::<Item>().get(entity).is_some() {
+                        get_item_display_name(ecs, entity)
+                    } else {
+                        ecs.read_storage::<Name>().get(entity).map(|n| n.name.clone()).unwrap_or_default()
+                    };
+                    actions.push((ContextAction::Examine(entity), format!("Examine the {}", name)));
+                }
+            }
+        }
+    }
+    if let Some(item) = crate::player::item_at(ecs, target) {
+        actions.push((ContextAction::PickUp(target), format!("Pick up the {}", get_item_display_name(ecs, item))));
+    }
+    if revealed && target != player_pos {
+        actions.push((ContextAction::Travel(target), "Travel here".to_string()));
+    }
+
+    // Things in the pack that can be sent there from here
+    if visible && target != player_pos {
+        let entities = ecs.entities();
+        let backpack = ecs.read_storage::<InBackpack>();
+        let throwable = ecs.read_storage::<Throwable>();
+        let ranged = ecs.read_storage::<Ranged>();
+        let consumables = ecs.read_storage::<Consumable>();
+        let mut seen : Vec<String> = Vec::new();
+        for (item, packed) in (&entities, &backpack).join() {
+            if packed.owner != player_entity { continue; }
+            let name = get_item_display_name(ecs, item);
+            if seen.contains(&name) { continue; }
+            if throwable.get(item).is_some() && distance <= THROW_RANGE {
+                actions.push((ContextAction::Throw(item, target), format!("Throw the {}", name)));
+                seen.push(name);
+            } else if let (Some(range), Some(_)) = (ranged.get(item), consumables.get(item)) {
+                if distance <= range.range as f32 {
+                    actions.push((ContextAction::UseOn(item, target), format!("Use the {} here", name)));
+                    seen.push(name);
+                }
+            }
+        }
+    }
+    actions
+}
+
+/// A small menu beside a right-clicked spot. Choose with a letter or a click; clicking
+/// anywhere else closes it.
+pub fn show_context_menu(gs : &mut State, ctx : &mut BTerm, target : Point) -> ContextMenuResult {
+    let actions = context_actions(&gs.ecs, target);
+    if actions.is_empty() {
+        return ContextMenuResult::Cancel;
+    }
+
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
+    let screen_x = target.x - min_x + 1;
+    let screen_y = target.y - min_y + 1;
+    ctx.set_bg(screen_x, screen_y, RGB::named(CYAN));
+
+    let width = actions.iter().map(|a| a.1.len()).max().unwrap_or(0) as i32 + 5;
+    let height = actions.len() as i32;
+    let x = if screen_x + width + 2 < 49 { screen_x + 1 } else { screen_x - width - 2 };
+    let y = i32::max(1, i32::min(screen_y - 1, 43 - height));
+    ctx.draw_box(x, y, width + 1, height + 1, RGB::named(WHITE), RGB::named(BLACK));
+    for (i, (_action, label)) in actions.iter().enumerate() {
+        let row = y + 1 + i as i32;
+        ctx.set(x + 1, row, RGB::named(WHITE), RGB::named(BLACK), to_cp437('('));
+        ctx.set(x + 2, row, RGB::named(YELLOW), RGB::named(BLACK), 97 + i as FontCharType);
+        ctx.set(x + 3, row, RGB::named(WHITE), RGB::named(BLACK), to_cp437(')'));
+        ctx.print(x + 5, row, label);
+    }
+
+    if camera::left_clicked(ctx) {
+        let (mouse_x, mouse_y) = ctx.mouse_pos();
+        let row = mouse_y - y - 1;
+        if mouse_x > x && mouse_x <= x + width && row >= 0 && row < height {
+            return ContextMenuResult::Selected(actions[row as usize].0);
+        }
+        return ContextMenuResult::Cancel;
+    }
+    match ctx.key {
+        None => ContextMenuResult::NoResponse,
+        Some(VirtualKeyCode::Escape) => ContextMenuResult::Cancel,
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < height {
+                return ContextMenuResult::Selected(actions[selection as usize].0);
+            }
+            ContextMenuResult::NoResponse
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum WaitPromptResult { NoResponse, Cancel, Edit(i32), Confirm(i32) }
+
+/// Asks how many turns to wait, typed in as digits.
+pub fn show_wait_prompt(ctx : &mut BTerm, turns : i32) -> WaitPromptResult {
+    ctx.draw_box(10, 20, 34, 4, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(12, 20, RGB::named(YELLOW), RGB::named(BLACK), "Wait a while");
+    ctx.print(12, 22, "Turns to wait:");
+    let typed = if turns > 0 { turns.to_string() } else { String::new() };
+    ctx.print_color(27, 22, RGB::named(CYAN), RGB::named(BLACK), format!("{}_", typed));
+    ctx.print_color(12, 24, RGB::named(YELLOW), RGB::named(BLACK), "ENTER to wait, ESCAPE to cancel");
+
+    let digit = match ctx.key {
+        Some(VirtualKeyCode::Key0) | Some(VirtualKeyCode::Numpad0) => Some(0),
+        Some(VirtualKeyCode::Key1) | Some(VirtualKeyCode::Numpad1) => Some(1),
+        Some(VirtualKeyCode::Key2) | Some(VirtualKeyCode::Numpad2) => Some(2),
+        Some(VirtualKeyCode::Key3) | Some(VirtualKeyCode::Numpad3) => Some(3),
+        Some(VirtualKeyCode::Key4) | Some(VirtualKeyCode::Numpad4) => Some(4),
+        Some(VirtualKeyCode::Key5) | Some(VirtualKeyCode::Numpad5) => Some(5),
+        Some(VirtualKeyCode::Key6) | Some(VirtualKeyCode::Numpad6) => Some(6),
+        Some(VirtualKeyCode::Key7) | Some(VirtualKeyCode::Numpad7) => Some(7),
+        Some(VirtualKeyCode::Key8) | Some(VirtualKeyCode::Numpad8) => Some(8),
+        Some(VirtualKeyCode::Key9) | Some(VirtualKeyCode::Numpad9) => Some(9),
+        _ => None
+    };
+    if let Some(digit) = digit {
+        return if turns < 100 { WaitPromptResult::Edit(turns * 10 + digit) } else { WaitPromptResult::NoResponse };
+    }
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => WaitPromptResult::Cancel,
+        Some(VirtualKeyCode::Back) => WaitPromptResult::Edit(turns / 10),
+        Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) if turns > 0 => WaitPromptResult::Confirm(turns),
+        _ => WaitPromptResult::NoResponse
+    }
+}
+
+/// The whole of the current level, shrunk to fit the screen if need be, with the stairs,
+/// vendors and items the player knows about picked out.
+pub fn show_level_map(gs : &mut State, ctx : &mut BTerm) -> ItemMenuResult {
+    let ecs = &gs.ecs;
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let (area_width, area_height) = (80, 54);
+    let scale = i32::max((map.width + area_width - 1) / area_width, (map.height + area_height - 1) / area_height);
+    let columns = (map.width + scale - 1) / scale;
+    let rows = (map.height + scale - 1) / scale;
+    let left = (area_width - columns) / 2;
+    let top = 2 + (area_height - rows) / 2;
+    let to_screen = |x : i32, y : i32| (left + x / scale, top + y / scale);
+
+    ctx.cls();
+    ctx.print_color(2, 0, RGB::named(YELLOW), RGB::named(BLACK), format!("Map of {}", map.name));
+    for row in 0 .. rows {
+        for column in 0 .. columns {
+            if let Some(idx) = map_block(&map, column * scale, row * scale, scale) {
+                let (glyph, fg, bg) = crate::map::tile_glyph(idx, &map);
+                ctx.set(left + column, top + row, fg, bg, glyph);
+            }
+        }
+    }
+
+    // Stairs might be lost inside a block, so they go on top
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if !map.revealed_tiles[idx] { continue; }
+        let glyph = match tile {
+            TileType::DownStairs => '>',
+            TileType::UpStairs => '<',
+            _ => continue
+        };
+        let color = match map.branch_exits.get(&idx) {
+            Some(branch) => { let (r, g, b) = branch.color(); RGB::from_f32(r, g, b) }
+            None => RGB::named(CYAN)
+        };
+        let (x, y) = to_screen(idx as i32 % map.width, idx as i32 / map.width);
+        ctx.set(x, y, color, RGB::named(BLACK), to_cp437(glyph));
+    }
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    for (_item, _remembered, pos, render) in (&ecs.read_storage::<Item>(), &ecs.read_storage::<Remembered>(), &positions, &renderables).join() {
+        let (x, y) = to_screen(pos.x, pos.y);
+        ctx.set(x, y, render.fg, RGB::named(BLACK), render.glyph);
+    }
+    for (_vendor, _remembered, pos) in (&ecs.read_storage::<Vendor>(), &ecs.read_storage::<Remembered>(), &positions).join() {
+        let (x, y) = to_screen(pos.x, pos.y);
+        ctx.set(x, y, RGB::named(GOLD), RGB::named(BLACK), to_cp437('$'));
+    }
+    let (x, y) = to_screen(player_pos.x, player_pos.y);
+    ctx.set(x, y, RGB::named(YELLOW), RGB::named(BLACK), to_cp437('@'));
+
+    ctx.print_color(2, 57, RGB::named(YELLOW), RGB::named(BLACK), "@");
+    ctx.print(4, 57, "you");
+    ctx.print_color(9, 57, RGB::named(CYAN), RGB::named(BLACK), "> <");
+    ctx.print(13, 57, "stairs");
+    ctx.print_color(21, 57, RGB::named(GOLD), RGB::named(BLACK), "$");
+    ctx.print(23, 57, "vendor");
+    ctx.print_color(2, 59, RGB::named(YELLOW), RGB::named(BLACK), "ESCAPE to close");
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        Some(key) if KEYBINDINGS.lock().unwrap().action_for(key) == Some(Action::LevelMap) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse
+    }
+}
+
+/// Lists every branch the player has set foot in, and the levels visited in each.
+pub fn show_dungeon_overview(gs : &mut State, ctx : &mut BTerm) -> ItemMenuResult {
+    let dm = gs.ecs.fetch::<MasterDungeonMap>();
+    let here = gs.ecs.fetch::<Map>().level_id();
+    let mut visited = dm.visited_levels();
+    visited.sort_by_key(|(level, _)| level.depth);
+
+    let mut lines : Vec<(String, RGB)> = Vec::new();
+    for branch in BRANCHES.iter() {
+        let levels : Vec<&(crate::LevelId, String)> = visited.iter().filter(|(level, _)| level.branch == *branch).collect();
+        if levels.is_empty() { continue; }
+        let (r, g, b) = branch.color();
+        let heading = match branch.entrance() {
+            Some(entrance) => format!("{} (from {} {})", branch.name(), entrance.branch.name(), entrance.depth),
+            None => branch.name().to_string()
+        };
+        lines.push((heading, RGB::from_f32(r, g, b)));
+        for (level, name) in levels {
+            if *level == here {
+                lines.push((format!("  {:>2}: {} <- you are here", level.depth, name), RGB::named(YELLOW)));
+            } else {
+                lines.push((format!("  {:>2}: {}", level.depth, name), RGB::named(WHITE)));
+            }
+        }
+    }
+
+    let count = lines.len() as i32;
+    let y = 25 - (count / 2);
+    ctx.draw_box(10, y-2, 51, count+3, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(13, y-2, RGB::named(YELLOW), RGB::named(BLACK), "Dungeon Overview");
+    ctx.print_color(13, y+count+1, RGB::named(YELLOW), RGB::named(BLACK), "ESCAPE to close");
+    for (i, (line, color)) in lines.iter().enumerate() {
+        ctx.print_color(12, y + i as i32, *color, RGB::named(BLACK), line);
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MessageLogResult { NoResponse, Close, Scroll(i32), Filter(Option<LogCategory>) }
+
+/// The whole message history, newest at the bottom, optionally narrowed down to one category.
+pub fn show_message_log(gs : &mut State, ctx : &mut BTerm, scroll : i32, filter : Option<LogCategory>) -> MessageLogResult {
+    const PAGE : i32 = 52;
+    let log = gs.ecs.fetch::<GameLog>();
+    let shown : Vec<&LogEntry> = log.entries().iter()
+        .filter(|e| filter.is_none() || Some(e.category) == filter)
+        .collect();
+    let max_scroll = i32::max(0, shown.len() as i32 - PAGE);
+    let scroll = i32::min(scroll, max_scroll);
+
+    ctx.cls();
+    ctx.draw_box(0, 0, 79, 59, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(3, 0, RGB::named(YELLOW), RGB::named(BLACK), "Message History");
+    ctx.print_color(3, 59, RGB::named(YELLOW), RGB::named(BLACK), "UP/DOWN, PGUP/PGDN to scroll, ESCAPE to close");
+
+    // The category filters, with whichever is active picked out
+    let mut x = 2;
+    let all_color = if filter.is_none() { RGB::named(YELLOW) } else { RGB::named(GREY) };
+    ctx.print_color(x, 2, all_color, RGB::named(BLACK), "(0) All");
+    x += 9;
+    for (i, category) in LOG_CATEGORIES.iter().enumerate() {
+        let color = if filter == Some(*category) { RGB::named(YELLOW) } else { category.color() };
+        let label = format!("({}) {}", i + 1, category.name());
+        ctx.print_color(x, 2, color, RGB::named(BLACK), &label);
+        x += label.len() as i32 + 2;
+    }
+
+    let end = shown.len() as i32 - scroll;
+    let start = i32::max(0, end - PAGE);
+    for (i, entry) in shown[start as usize .. end as usize].iter().enumerate() {
+        let y = 4 + i as i32;
+        let stamp = format!("[{:>5}] ", entry.turn);
+        ctx.print_color(2, y, RGB::named(GREY), RGB::named(BLACK), &stamp);
+        draw_log_entry(ctx, 2 + stamp.len() as i32, y, entry, 75 - stamp.len() as i32);
+    }
+    if scroll > 0 {
+        ctx.print_color(70, 59, RGB::named(CYAN), RGB::named(BLACK), "(more)");
+    }
+
+    match ctx.key {
+        None => MessageLogResult::NoResponse,
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => MessageLogResult::Close,
+                VirtualKeyCode::Up => MessageLogResult::Scroll(i32::min(scroll + 1, max_scroll)),
+                VirtualKeyCode::Down => MessageLogResult::Scroll(i32::max(scroll - 1, 0)),
+                VirtualKeyCode::PageUp => MessageLogResult::Scroll(i32::min(scroll + PAGE, max_scroll)),
+                VirtualKeyCode::PageDown => MessageLogResult::Scroll(i32::max(scroll - PAGE, 0)),
+                VirtualKeyCode::Key0 => MessageLogResult::Filter(None),
+                VirtualKeyCode::Key1 => MessageLogResult::Filter(Some(LOG_CATEGORIES[0])),
+                VirtualKeyCode::Key2 => MessageLogResult::Filter(Some(LOG_CATEGORIES[1])),
+                VirtualKeyCode::Key3 => MessageLogResult::Filter(Some(LOG_CATEGORIES[2])),
+                VirtualKeyCode::Key4 => MessageLogResult::Filter(Some(LOG_CATEGORIES[3])),
+                VirtualKeyCode::Key5 => MessageLogResult::Filter(Some(LOG_CATEGORIES[4])),
+                _ => MessageLogResult::NoResponse
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum VendorResult { NoResponse, Cancel, Sell, BuyMode, SellMode, Buy }
 
@@ -938,8 +2116,35 @@ fn vendor_buy_menu(gs : &mut State, ctx : &mut BTerm, vendor : Entity, _mode : V
 }
 
 pub fn show_vendor_menu(gs : &mut State, ctx : &mut BTerm, vendor : Entity, mode : VendorMode) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+    let gold = gs.ecs.read_storage::<Pools>().get(*gs.ecs.fetch::<Entity>()).map(|p| p.gold).unwrap_or(0.0);
+    let hint = match mode {
+        VendorMode::Buy => format!("Press a letter to buy, at 120% of value. You have {:.1} gold.", gold),
+        VendorMode::Sell => format!("Press a letter to sell, for 80% of value. You have {:.1} gold.", gold)
+    };
+    draw_hint(ctx, &hint);
     match mode {
         VendorMode::Buy => vendor_buy_menu(gs, ctx, vendor, mode),
         VendorMode::Sell => vendor_sell_menu(gs, ctx, vendor, mode)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_text_breaks_at_words() {
+        assert_eq!(wrap_text("the quick brown fox jumps", 10), vec!["the quick", "brown fox", "jumps"]);
+    }
+
+    #[test]
+    fn wrap_text_keeps_long_words_whole() {
+        assert_eq!(wrap_text("a mithril breastplate", 5), vec!["a", "mithril", "breastplate"]);
+    }
+
+    #[test]
+    fn wrap_text_ignores_extra_whitespace() {
+        assert_eq!(wrap_text("  one   two  ", 20), vec!["one two"]);
+        assert!(wrap_text("", 20).is_empty());
+    }
+}
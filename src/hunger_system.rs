@@ -1,6 +1,6 @@
 use specs::prelude::*;
 use crate::effects::{add_effect, EffectType, Targets};
-use super::{HungerClock, HungerState, gamelog::GameLog, MyTurn};
+use super::{HungerClock, HungerState, gamelog::{GameLog, LogCategory}, MyTurn};
 
 pub struct HungerSystem {}
 
@@ -26,27 +26,27 @@ impl<'a> System<'a> for HungerSystem {
                         clock.state = HungerState::Normal;
                         clock.duration = 200;
                         if entity == *player_entity {
-                            log.entries.push("You are no longer well fed.".to_string());
+                            log.log(LogCategory::Status, "You are no longer well fed.");
                         }
                     }
                     HungerState::Normal => {
                         clock.state = HungerState::Hungry;
                         clock.duration = 200;
                         if entity == *player_entity {
-                            log.entries.push("You are hungry.".to_string());
+                            log.log(LogCategory::Status, "You are hungry.");
                         }
                     }
                     HungerState::Hungry => {
                         clock.state = HungerState::Starving;
                         clock.duration = 200;
                         if entity == *player_entity {
-                            log.entries.push("You are starving!".to_string());
+                            log.log(LogCategory::Status, "You are starving!");
                         }
                     }
                     HungerState::Starving => {
                         // Inflict damage from hunger
                         if entity == *player_entity {
-                            log.entries.push("Your hunger pangs are getting painful! You suffer 1 hp damage.".to_string());
+                            log.log(LogCategory::Status, "Your hunger pangs are getting painful! You suffer 1 hp damage.");
                             add_effect(
                                 None,
                                 EffectType::Damage{ amount: 1},
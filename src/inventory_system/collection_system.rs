@@ -1,5 +1,6 @@
 use specs::prelude::*;
-use super::{WantsToPickupItem, Name, InBackpack, Position, gamelog::GameLog, EquipmentChanged,
+use bracket_lib::prelude::{RGB, CYAN};
+use super::{WantsToPickupItem, Name, InBackpack, Position, gamelog::{GameLog, LogCategory}, EquipmentChanged,
             MagicItem, ObfuscatedName, MasterDungeonMap };
 
 pub struct ItemCollectionSystem {}
@@ -28,12 +29,11 @@ impl<'a> System<'a> for ItemCollectionSystem {
             dirty.insert(pickup.collected_by, EquipmentChanged{}).expect("Unable to insert");
 
             if pickup.collected_by == *player_entity {
-                gamelog.entries.push(
-                    format!(
-                        "You pick up the {}.",
-                        super::obfuscate_name(pickup.item, &names, &magic_items, &obfuscated_names, &dm)
-                    )
-                );
+                gamelog.line(LogCategory::Loot)
+                    .append("You pick up the ")
+                    .colored(super::obfuscate_name(pickup.item, &names, &magic_items, &obfuscated_names, &dm), RGB::named(CYAN))
+                    .append(".")
+                    .log();
             }
         }
 
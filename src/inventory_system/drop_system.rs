@@ -1,5 +1,6 @@
 use specs::prelude::*;
-use super::{Name, InBackpack, Position, gamelog::GameLog, WantsToDropItem, EquipmentChanged,
+use bracket_lib::prelude::{RGB, CYAN};
+use super::{Name, InBackpack, Position, gamelog::{GameLog, LogCategory}, WantsToDropItem, EquipmentChanged,
             MagicItem, ObfuscatedName, MasterDungeonMap};
 
 pub struct ItemDropSystem {}
@@ -35,12 +36,11 @@ impl<'a> System<'a> for ItemDropSystem {
             dirty.insert(entity, EquipmentChanged{}).expect("Unable to insert");
 
             if entity == *player_entity {
-                gamelog.entries.push(
-                    format!(
-                        "You pick up the {}.",
-                        super::obfuscate_name(to_drop.item, &names, &magic_items, &obfuscated_names, &dm)
-                    )
-                );
+                gamelog.line(LogCategory::Loot)
+                    .append("You drop the ")
+                    .colored(super::obfuscate_name(to_drop.item, &names, &magic_items, &obfuscated_names, &dm), RGB::named(CYAN))
+                    .append(".")
+                    .log();
             }
         }
 
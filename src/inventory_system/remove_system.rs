@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use super::{InBackpack, Equipped, WantsToRemoveItem, CursedItem, Name, EquipmentChanged};
+use super::{InBackpack, Equipped, WantsToRemoveItem, CursedItem, Name, EquipmentChanged, gamelog::{GameLog, LogCategory}};
 
 pub struct ItemRemoveSystem {}
 
@@ -11,7 +11,7 @@ impl<'a> System<'a> for ItemRemoveSystem {
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
         ReadStorage<'a, CursedItem>,
-        WriteExpect<'a, crate::gamelog::GameLog>,
+        WriteExpect<'a, GameLog>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, EquipmentChanged>
     );
@@ -21,7 +21,7 @@ impl<'a> System<'a> for ItemRemoveSystem {
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
             if cursed.get(to_remove.item).is_some() {
-                gamelog.entries.push(format!("You cannot remove {}, it is cursed", names.get(to_remove.item).unwrap().name));
+                gamelog.log(LogCategory::Loot, format!("You cannot remove {}, it is cursed", names.get(to_remove.item).unwrap().name));
             } else {
                 equipped.remove(to_remove.item);
                 backpack.insert(to_remove.item, InBackpack{ owner: entity }).expect("Unable to insert backpack");
@@ -37,7 +37,7 @@ impl<'a> System<'a> for ItemUseSystem {
                 Some(entity),
                 EffectType::ItemUse{ item : useitem.item },
                 match useitem.target {
-                    None => Targets::Single{ target: *player_entity },
+                    None => Targets::Single{ target: entity },
                     Some(target) => {
                         if let Some(aoe) = aoe.get(useitem.item) {
                             Targets::Tiles{ tiles: aoe_tiles(&*map, target, aoe.radius) }
@@ -0,0 +1,335 @@
+use bracket_lib::prelude::{VirtualKeyCode, console};
+use std::collections::{HashMap, BTreeMap};
+use std::sync::Mutex;
+
+const BINDINGS_FILE : &str = "./keybindings.json";
+
+/// Everything the player can ask for with a single key press.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Action {
+    MoveWest, MoveEast, MoveNorth, MoveSouth,
+    MoveNorthWest, MoveNorthEast, MoveSouthWest, MoveSouthEast,
+    Wait, Rest, WaitTurns, Search, Disarm, Descend, Ascend,
+    Explore, TravelDown, TravelUp,
+    PickUp, Inventory, Drop, Remove, Equipment,
+    Look, CharacterSheet, MessageLog, LevelMap, DungeonOverview,
+    Help, SaveAndQuit, Cheat
+}
+
+pub const ACTIONS : [Action; 31] = [
+    Action::MoveWest, Action::MoveEast, Action::MoveNorth, Action::MoveSouth,
+    Action::MoveNorthWest, Action::MoveNorthEast, Action::MoveSouthWest, Action::MoveSouthEast,
+    Action::Wait, Action::Rest, Action::WaitTurns, Action::Search, Action::Disarm, Action::Descend, Action::Ascend,
+    Action::Explore, Action::TravelDown, Action::TravelUp,
+    Action::PickUp, Action::Inventory, Action::Drop, Action::Remove, Action::Equipment,
+    Action::Look, Action::CharacterSheet, Action::MessageLog, Action::LevelMap, Action::DungeonOverview,
+    Action::Help, Action::SaveAndQuit, Action::Cheat
+];
+
+impl Action {
+    /// The name the action goes by in the bindings file.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::MoveWest => "move_west",
+            Action::MoveEast => "move_east",
+            Action::MoveNorth => "move_north",
+            Action::MoveSouth => "move_south",
+            Action::MoveNorthWest => "move_north_west",
+            Action::MoveNorthEast => "move_north_east",
+            Action::MoveSouthWest => "move_south_west",
+            Action::MoveSouthEast => "move_south_east",
+            Action::Wait => "wait",
+            Action::Rest => "rest",
+            Action::WaitTurns => "wait_turns",
+            Action::Search => "search",
+            Action::Disarm => "disarm",
+            Action::Descend => "descend",
+            Action::Ascend => "ascend",
+            Action::Explore => "explore",
+            Action::TravelDown => "travel_down",
+            Action::TravelUp => "travel_up",
+            Action::PickUp => "pick_up",
+            Action::Inventory => "inventory",
+            Action::Drop => "drop",
+            Action::Remove => "remove",
+            Action::Equipment => "equipment",
+            Action::Look => "look",
+            Action::CharacterSheet => "character_sheet",
+            Action::MessageLog => "message_log",
+            Action::LevelMap => "level_map",
+            Action::DungeonOverview => "dungeon_overview",
+            Action::Help => "help",
+            Action::SaveAndQuit => "save_and_quit",
+            Action::Cheat => "cheat"
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveWest => "Move west",
+            Action::MoveEast => "Move east",
+            Action::MoveNorth => "Move north",
+            Action::MoveSouth => "Move south",
+            Action::MoveNorthWest => "Move north-west",
+            Action::MoveNorthEast => "Move north-east",
+            Action::MoveSouthWest => "Move south-west",
+            Action::MoveSouthEast => "Move south-east",
+            Action::Wait => "Wait a turn",
+            Action::Rest => "Rest",
+            Action::WaitTurns => "Wait a while",
+            Action::Search => "Search",
+            Action::Disarm => "Disarm trap",
+            Action::Descend => "Go down stairs",
+            Action::Ascend => "Go up stairs",
+            Action::Explore => "Explore",
+            Action::TravelDown => "Travel downstairs",
+            Action::TravelUp => "Travel upstairs",
+            Action::PickUp => "Pick up",
+            Action::Inventory => "Use item",
+            Action::Drop => "Drop item",
+            Action::Remove => "Remove item",
+            Action::Equipment => "Equipment",
+            Action::Look => "Look around",
+            Action::CharacterSheet => "Character sheet",
+            Action::MessageLog => "Message history",
+            Action::LevelMap => "Level map",
+            Action::DungeonOverview => "Dungeon overview",
+            Action::Help => "Help",
+            Action::SaveAndQuit => "Save and quit",
+            Action::Cheat => "Cheat menu"
+        }
+    }
+
+    /// A short explanation, for the help screen.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::MoveWest | Action::MoveEast | Action::MoveNorth | Action::MoveSouth |
+            Action::MoveNorthWest | Action::MoveNorthEast | Action::MoveSouthWest | Action::MoveSouthEast
+                => "Walk; bump to attack, open or talk",
+            Action::Wait => "Let a turn pass, healing if safe",
+            Action::Rest => "Wait until healed or disturbed",
+            Action::WaitTurns => "Wait a number of turns, if undisturbed",
+            Action::Search => "Look for hidden doors and traps",
+            Action::Disarm => "Try to disarm an adjacent trap",
+            Action::Descend => "Take the stairs down",
+            Action::Ascend => "Take the stairs up",
+            Action::Explore => "Walk to whatever you haven't seen yet",
+            Action::TravelDown => "Walk to the nearest known way down",
+            Action::TravelUp => "Walk to the nearest known way up",
+            Action::PickUp => "Pick up the item underfoot",
+            Action::Inventory => "Use, eat, read or equip an item",
+            Action::Drop => "Drop an item from your pack",
+            Action::Remove => "Take off an equipped item",
+            Action::Equipment => "Manage what you are wearing",
+            Action::Look => "Examine what you can see",
+            Action::CharacterSheet => "Your attributes, skills and status",
+            Action::MessageLog => "Read back through old messages",
+            Action::LevelMap => "This whole level, as far as you know it",
+            Action::DungeonOverview => "The levels you have visited",
+            Action::Help => "This screen",
+            Action::SaveAndQuit => "Save the game and quit to menu",
+            Action::Cheat => "Debugging cheats"
+        }
+    }
+
+    fn default_keys(&self) -> Vec<VirtualKeyCode> {
+        use VirtualKeyCode::*;
+        match self {
+            Action::MoveWest => vec![Left, Numpad4, H],
+            Action::MoveEast => vec![Right, Numpad6, L],
+            Action::MoveNorth => vec![Up, Numpad8, K],
+            Action::MoveSouth => vec![Down, Numpad2, J],
+            Action::MoveNorthWest => vec![Numpad7, Y],
+            Action::MoveNorthEast => vec![Numpad9, U],
+            Action::MoveSouthWest => vec![Numpad1, B],
+            Action::MoveSouthEast => vec![Numpad3, N],
+            Action::Wait => vec![Numpad5, Space],
+            Action::Rest => vec![W],
+            Action::WaitTurns => vec![A],
+            Action::Search => vec![S],
+            Action::Disarm => vec![X],
+            Action::Descend => vec![Period],
+            Action::Ascend => vec![Comma],
+            Action::Explore => vec![Z],
+            Action::TravelDown => vec![T],
+            Action::TravelUp => vec![Q],
+            Action::PickUp => vec![G],
+            Action::Inventory => vec![I],
+            Action::Drop => vec![D],
+            Action::Remove => vec![R],
+            Action::Equipment => vec![E],
+            Action::Look => vec![V],
+            Action::CharacterSheet => vec![C],
+            Action::MessageLog => vec![M],
+            Action::LevelMap => vec![Tab],
+            Action::DungeonOverview => vec![O],
+            Action::Help => vec![Slash],
+            Action::SaveAndQuit => vec![Escape],
+            Action::Cheat => vec![Backslash]
+        }
+    }
+}
+
+/// Keys that can be bound to an action. Modified digits are kept back for the hotkeys.
+const BINDABLE_KEYS : [VirtualKeyCode; 70] = {
+    use VirtualKeyCode::*;
+    [A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    Left, Right, Up, Down, Home, End, PageUp, PageDown, Insert, Delete,
+    Space, Tab, Escape, Period, Comma, Slash, Backslash, Semicolon, Apostrophe, Minus, Equals,
+    LBracket, RBracket, Grave]
+};
+
+/// The name a key is written under in the bindings file.
+pub fn key_name(key : VirtualKeyCode) -> String {
+    format!("{:?}", key)
+}
+
+fn parse_key(name : &str) -> Option<VirtualKeyCode> {
+    BINDABLE_KEYS.iter().find(|k| key_name(**k) == name).cloned()
+}
+
+pub fn is_bindable(key : VirtualKeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+pub struct KeyBindings {
+    bindings : HashMap<Action, Vec<VirtualKeyCode>>
+}
+
+impl KeyBindings {
+    pub fn defaults() -> KeyBindings {
+        let mut bindings = HashMap::new();
+        for action in ACTIONS.iter() {
+            bindings.insert(*action, action.default_keys());
+        }
+        KeyBindings{ bindings }
+    }
+
+    pub fn action_for(&self, key : VirtualKeyCode) -> Option<Action> {
+        ACTIONS.iter().find(|a| self.keys_for(**a).contains(&key)).cloned()
+    }
+
+    pub fn keys_for(&self, action : Action) -> &[VirtualKeyCode] {
+        match self.bindings.get(&action) {
+            Some(keys) => keys,
+            None => &[]
+        }
+    }
+
+    /// Adds a key to an action, taking it away from whatever had it before.
+    pub fn bind(&mut self, action : Action, key : VirtualKeyCode) {
+        for keys in self.bindings.values_mut() {
+            keys.retain(|k| *k != key);
+        }
+        self.bindings.entry(action).or_default().push(key);
+    }
+
+    pub fn clear(&mut self, action : Action) {
+        self.bindings.insert(action, Vec::new());
+    }
+
+    pub fn reset(&mut self, action : Action) {
+        self.clear(action);
+        for key in action.default_keys() {
+            self.bind(action, key);
+        }
+    }
+
+    /// A readable list of the keys bound to an action, for menus and help.
+    pub fn describe(&self, action : Action) -> String {
+        let keys = self.keys_for(action);
+        if keys.is_empty() { return "(unbound)".to_string(); }
+        keys.iter().map(|k| key_name(*k)).collect::<Vec<String>>().join(", ")
+    }
+}
+
+lazy_static! {
+    pub static ref KEYBINDINGS : Mutex<KeyBindings> = Mutex::new(KeyBindings::defaults());
+}
+
+/// Reads the player's bindings, if they have saved any. Actions the file doesn't mention keep
+/// their default keys.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_keybindings() {
+    let contents = match std::fs::read_to_string(BINDINGS_FILE) {
+        Ok(contents) => contents,
+        Err(_) => return
+    };
+    let saved : HashMap<String, Vec<String>> = match serde_json::from_str(&contents) {
+        Ok(saved) => saved,
+        Err(e) => {
+            console::log(format!("Unable to read {}: {}", BINDINGS_FILE, e));
+            return;
+        }
+    };
+
+    let mut bindings = KeyBindings::defaults();
+    for (id, key_names) in saved.iter() {
+        if let Some(action) = ACTIONS.iter().find(|a| a.id() == id) {
+            bindings.clear(*action);
+            for name in key_names.iter() {
+                match parse_key(name) {
+                    Some(key) => bindings.bind(*action, key),
+                    None => console::log(format!("Unknown key {} bound to {}", name, id))
+                }
+            }
+        } else {
+            console::log(format!("Unknown action {} in {}", id, BINDINGS_FILE));
+        }
+    }
+    *KEYBINDINGS.lock().unwrap() = bindings;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_keybindings() {
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_keybindings() {
+    let bindings = KEYBINDINGS.lock().unwrap();
+    let saved : BTreeMap<&str, Vec<String>> = ACTIONS.iter()
+        .map(|a| (a.id(), bindings.keys_for(*a).iter().map(|k| key_name(*k)).collect()))
+        .collect();
+    let json = serde_json::to_string_pretty(&saved).unwrap();
+    if let Err(e) = std::fs::write(BINDINGS_FILE, json) {
+        console::log(format!("Unable to save {}: {}", BINDINGS_FILE, e));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_keybindings() {
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_takes_the_key_from_its_old_action() {
+        let mut bindings = KeyBindings::defaults();
+        assert_eq!(bindings.action_for(VirtualKeyCode::W), Some(Action::Rest));
+        bindings.bind(Action::Search, VirtualKeyCode::W);
+        assert_eq!(bindings.action_for(VirtualKeyCode::W), Some(Action::Search));
+        assert!(bindings.keys_for(Action::Rest).is_empty());
+        assert_eq!(bindings.keys_for(Action::Search), &[VirtualKeyCode::S, VirtualKeyCode::W]);
+    }
+
+    #[test]
+    fn bind_does_not_repeat_a_key() {
+        let mut bindings = KeyBindings::defaults();
+        bindings.bind(Action::Search, VirtualKeyCode::S);
+        assert_eq!(bindings.keys_for(Action::Search), &[VirtualKeyCode::S]);
+    }
+
+    #[test]
+    fn reset_takes_back_the_default_keys() {
+        let mut bindings = KeyBindings::defaults();
+        bindings.bind(Action::Search, VirtualKeyCode::W);
+        bindings.reset(Action::Rest);
+        assert_eq!(bindings.action_for(VirtualKeyCode::W), Some(Action::Rest));
+        assert_eq!(bindings.keys_for(Action::Search), &[VirtualKeyCode::S]);
+        assert_eq!(bindings.describe(Action::Rest), key_name(VirtualKeyCode::W));
+    }
+}
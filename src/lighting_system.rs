@@ -2,6 +2,8 @@ use specs::prelude::*;
 use super::{Viewshed, Position, Map, LightSource};
 use bracket_lib::prelude::*;
 
+const FIRE_LIGHT_RANGE : i32 = 4;
+
 pub struct LightingSystem {}
 
 impl<'a> System<'a> for LightingSystem {
@@ -36,5 +38,23 @@ impl<'a> System<'a> for LightingSystem {
                 }
             }
         }
+
+        // Burning tiles light up their surroundings
+        let fire_color = RGB::named(ORANGE);
+        let range_f = FIRE_LIGHT_RANGE as f32;
+        let mut fire_light : Vec<(usize, f32)> = Vec::new();
+        for (idx, fire) in map.fire.iter().enumerate() {
+            if *fire < 1 { continue; }
+            let fire_point = Point::new(idx as i32 % map.width, idx as i32 / map.width);
+            for t in field_of_view(fire_point, FIRE_LIGHT_RANGE, &*map).iter() {
+                if t.x > 0 && t.x < map.width && t.y > 0 && t.y < map.height {
+                    let distance = DistanceAlg::Pythagoras.distance2d(fire_point, *t);
+                    fire_light.push((map.xy_idx(t.x, t.y), (range_f - distance) / range_f));
+                }
+            }
+        }
+        for (idx, intensity) in fire_light.iter() {
+            map.light[*idx] = map.light[*idx] + (fire_color * *intensity);
+        }
     }
 }
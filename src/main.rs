@@ -21,6 +21,7 @@ use melee_combat_system::MeleeCombatSystem;
 mod damage_system;
 mod gui;
 mod gamelog;
+use gamelog::LogCategory;
 mod spawner;
 mod inventory_system;
 use inventory_system::{ ItemCollectionSystem, ItemUseSystem, SpellUseSystem, ItemDropSystem, ItemRemoveSystem };
@@ -28,6 +29,10 @@ pub mod saveload_system;
 pub mod random_table;
 pub mod particle_system;
 pub mod hunger_system;
+pub mod fire_system;
+pub mod gas_system;
+pub mod water_system;
+pub mod pressure_plate_system;
 pub mod rex_assets;
 pub mod trigger_system;
 pub mod map_builders;
@@ -42,6 +47,8 @@ pub mod effects;
 #[macro_use]
 extern crate lazy_static;
 pub mod spatial;
+pub mod keybindings;
+pub mod travel;
 
 const SHOW_MAPGEN_VISUALIZER : bool = false;
 
@@ -67,9 +74,20 @@ pub enum RunState {
     MapGeneration,
     ShowCheatMenu,
     ShowVendor { vendor: Entity, mode : VendorMode },
-    TeleportingToOtherLevel { x: i32, y: i32, depth: i32 },
+    TeleportingToOtherLevel { x: i32, y: i32, level: LevelId },
     ShowRemoveCurse,
-    ShowIdentify
+    ShowIdentify,
+    ShowDungeonOverview,
+    ShowMessageLog { scroll : i32, filter : Option<LogCategory> },
+    LookMode { index : usize },
+    ShowCharacterSheet,
+    ShowEquipment { slot : usize },
+    ShowEquipmentSwap { slot : usize, selected : usize },
+    KeyBindings { selected : usize, listening : bool },
+    ShowHelp,
+    ContextMenu { x : i32, y : i32 },
+    WaitPrompt { turns : i32 },
+    ShowLevelMap
 }
 
 pub struct State {
@@ -88,16 +106,22 @@ impl State {
         vis.run_now(&self.ecs);
         let mut encumbrance = ai::EncumbranceSystem{};
         encumbrance.run_now(&self.ecs);
+        let mut dijkstra = ai::DijkstraMapSystem{};
+        dijkstra.run_now(&self.ecs);
         let mut initiative = ai::InitiativeSystem{};
         initiative.run_now(&self.ecs);
         let mut turnstatus = ai::TurnStatusSystem{};
         turnstatus.run_now(&self.ecs);
         let mut quipper = ai::QuipSystem{};
         quipper.run_now(&self.ecs);
+        let mut itemuser = ai::ItemUseAI{};
+        itemuser.run_now(&self.ecs);
         let mut adjacent = ai::AdjacentAI{};
         adjacent.run_now(&self.ecs);
         let mut visible = ai::VisibleAI{};
         visible.run_now(&self.ecs);
+        let mut pack = ai::PackAI{};
+        pack.run_now(&self.ecs);
         let mut approach = ai::ApproachAI{};
         approach.run_now(&self.ecs);
         let mut flee = ai::FleeAI{};
@@ -128,6 +152,14 @@ impl State {
         item_remove.run_now(&self.ecs);
         let mut hunger = hunger_system::HungerSystem{};
         hunger.run_now(&self.ecs);
+        let mut fire = fire_system::FireSystem{};
+        fire.run_now(&self.ecs);
+        let mut gas = gas_system::GasSystem{};
+        gas.run_now(&self.ecs);
+        let mut water = water_system::WaterSystem{};
+        water.run_now(&self.ecs);
+        let mut plates = pressure_plate_system::PressurePlateSystem{};
+        plates.run_now(&self.ecs);
         effects::run_effects_queue(&mut self.ecs);
         let mut particles = particle_system::ParticleSpawnSystem{};
         particles.run_now(&self.ecs);
@@ -148,10 +180,12 @@ impl GameState for State {
         }
 
         ctx.cls();
+        camera::update_mouse_buttons();
         particle_system::cull_dead_particles(&mut self.ecs, ctx);
 
         match newrunstate {
             RunState::MainMenu{..} => {}
+            RunState::KeyBindings{..} => {}
             RunState::GameOver{..} => {}
             _ => {
                 camera::render_camera(&self.ecs, ctx);
@@ -194,7 +228,7 @@ impl GameState for State {
                         RunState::AwaitingInput => newrunstate = RunState::AwaitingInput,
                         RunState::MagicMapReveal{ .. } => newrunstate = RunState::MagicMapReveal{ row: 0 },
                         RunState::TownPortal => newrunstate = RunState::TownPortal,
-                        RunState::TeleportingToOtherLevel{ x, y, depth } => newrunstate = RunState::TeleportingToOtherLevel{ x, y, depth },
+                        RunState::TeleportingToOtherLevel{ x, y, level } => newrunstate = RunState::TeleportingToOtherLevel{ x, y, level },
                         RunState::ShowRemoveCurse => newrunstate = RunState::ShowRemoveCurse,
                         RunState::ShowIdentify => newrunstate = RunState::ShowIdentify,
                         _ => newrunstate = RunState::Ticking
@@ -226,9 +260,14 @@ impl GameState for State {
                     gui::CheatMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::CheatMenuResult::NoResponse => {}
                     gui::CheatMenuResult::TeleportToExit => {
-                        self.goto_level(1);
-                        self.mapgen_next_state = Some(RunState::PreRun);
-                        newrunstate = RunState::MapGeneration;
+                        let below = self.ecs.fetch::<Map>().level_id().deeper();
+                        if let Some(below) = below {
+                            self.goto_level(below);
+                            self.mapgen_next_state = Some(RunState::PreRun);
+                            newrunstate = RunState::MapGeneration;
+                        } else {
+                            newrunstate = RunState::AwaitingInput;
+                        }
                     }
                     gui::CheatMenuResult::Heal => {
                         let player = self.ecs.fetch::<Entity>();
@@ -279,6 +318,91 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowMessageLog{ scroll, filter } => {
+                let result = gui::show_message_log(self, ctx, scroll, filter);
+                match result {
+                    gui::MessageLogResult::NoResponse => {}
+                    gui::MessageLogResult::Close => newrunstate = RunState::AwaitingInput,
+                    gui::MessageLogResult::Scroll(scroll) => newrunstate = RunState::ShowMessageLog{ scroll, filter },
+                    gui::MessageLogResult::Filter(filter) => newrunstate = RunState::ShowMessageLog{ scroll: 0, filter }
+                }
+            }
+            RunState::LookMode{ index } => {
+                let result = gui::show_look_mode(self, ctx, index);
+                match result {
+                    gui::LookResult::NoResponse => {}
+                    gui::LookResult::Close => newrunstate = RunState::AwaitingInput,
+                    gui::LookResult::Select(index) => newrunstate = RunState::LookMode{ index }
+                }
+            }
+            RunState::ShowCharacterSheet => {
+                let result = gui::show_character_sheet(self, ctx);
+                if result == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowEquipment{ slot } => {
+                let result = gui::show_equipment(self, ctx, slot);
+                match result {
+                    gui::EquipmentResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::EquipmentResult::Move(slot) => newrunstate = RunState::ShowEquipment{ slot },
+                    gui::EquipmentResult::Open => newrunstate = RunState::ShowEquipmentSwap{ slot, selected: 0 },
+                    gui::EquipmentResult::Unequip(item_entity) => {
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToRemoveItem{ item: item_entity }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                    _ => {}
+                }
+            }
+            RunState::ShowEquipmentSwap{ slot, selected } => {
+                let result = gui::show_equipment_swap(self, ctx, slot, selected);
+                match result {
+                    gui::EquipmentResult::Cancel => newrunstate = RunState::ShowEquipment{ slot },
+                    gui::EquipmentResult::Move(selected) => newrunstate = RunState::ShowEquipmentSwap{ slot, selected },
+                    gui::EquipmentResult::Equip(item_entity) => {
+                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item: item_entity, target: None }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                    _ => {}
+                }
+            }
+            RunState::ShowHelp => {
+                let result = gui::show_help(ctx);
+                if result == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ContextMenu{ x, y } => {
+                let result = gui::show_context_menu(self, ctx, Point::new(x, y));
+                match result {
+                    gui::ContextMenuResult::NoResponse => {}
+                    gui::ContextMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ContextMenuResult::Selected(action) => newrunstate = self.context_action(action)
+                }
+            }
+            RunState::WaitPrompt{ turns } => {
+                let result = gui::show_wait_prompt(ctx, turns);
+                match result {
+                    gui::WaitPromptResult::NoResponse => {}
+                    gui::WaitPromptResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::WaitPromptResult::Edit(turns) => newrunstate = RunState::WaitPrompt{ turns },
+                    gui::WaitPromptResult::Confirm(turns) => newrunstate = rest(&mut self.ecs, Some(turns))
+                }
+            }
+            RunState::ShowLevelMap => {
+                let result = gui::show_level_map(self, ctx);
+                if result == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowDungeonOverview => {
+                let result = gui::show_dungeon_overview(self, ctx);
+                if result == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
             RunState::ShowRemoveCurse => {
                 let result = gui::remove_curse_menu(self, ctx);
                 match result.0 {
@@ -370,11 +494,36 @@ impl GameState for State {
                                 newrunstate = RunState::AwaitingInput;
                                 saveload_system::delete_save();
                             }
+                            gui::MainMenuSelection::KeyBindings => newrunstate = RunState::KeyBindings{ selected: 0, listening: false },
                             gui::MainMenuSelection::Quit => { ::std::process::exit(0); }
                         }
                     }
                 }
             }
+            RunState::KeyBindings{ selected, listening } => {
+                let result = gui::show_keybindings(ctx, selected, listening);
+                let action = keybindings::ACTIONS[selected];
+                match result {
+                    gui::KeyBindingResult::NoResponse => {}
+                    gui::KeyBindingResult::Close => newrunstate = RunState::MainMenu{ menu_selection: gui::MainMenuSelection::KeyBindings },
+                    gui::KeyBindingResult::Select(selected) => newrunstate = RunState::KeyBindings{ selected, listening: false },
+                    gui::KeyBindingResult::Listen => newrunstate = RunState::KeyBindings{ selected, listening: true },
+                    gui::KeyBindingResult::StopListening => newrunstate = RunState::KeyBindings{ selected, listening: false },
+                    gui::KeyBindingResult::Bind(key) => {
+                        keybindings::KEYBINDINGS.lock().unwrap().bind(action, key);
+                        keybindings::save_keybindings();
+                        newrunstate = RunState::KeyBindings{ selected, listening: false };
+                    }
+                    gui::KeyBindingResult::Clear => {
+                        keybindings::KEYBINDINGS.lock().unwrap().clear(action);
+                        keybindings::save_keybindings();
+                    }
+                    gui::KeyBindingResult::Reset => {
+                        keybindings::KEYBINDINGS.lock().unwrap().reset(action);
+                        keybindings::save_keybindings();
+                    }
+                }
+            }
             RunState::GameOver => {
                 let result = gui::game_over(ctx);
                 match result {
@@ -391,28 +540,42 @@ impl GameState for State {
                 newrunstate = RunState::MainMenu{ menu_selection : gui::MainMenuSelection::LoadGame };
             }
             RunState::NextLevel => {
-                self.goto_level(1);
-                self.mapgen_next_state = Some(RunState::PreRun);
-                newrunstate = RunState::MapGeneration;
+                let below = {
+                    let map = self.ecs.fetch::<Map>();
+                    let player_pos = self.ecs.fetch::<bracket_lib::prelude::Point>();
+                    map.level_below(map.xy_idx(player_pos.x, player_pos.y))
+                };
+                if let Some(below) = below {
+                    self.goto_level(below);
+                    self.mapgen_next_state = Some(RunState::PreRun);
+                    newrunstate = RunState::MapGeneration;
+                } else {
+                    self.ecs.fetch_mut::<gamelog::GameLog>().log(LogCategory::System, "The way down goes no further.");
+                    newrunstate = RunState::AwaitingInput;
+                }
             }
             RunState::PreviousLevel => {
-                self.goto_level(-1);
-                self.mapgen_next_state = Some(RunState::PreRun);
-                newrunstate = RunState::MapGeneration;
+                let above = self.ecs.fetch::<Map>().level_id().shallower();
+                if let Some(above) = above {
+                    self.goto_level(above);
+                    self.mapgen_next_state = Some(RunState::PreRun);
+                    newrunstate = RunState::MapGeneration;
+                } else {
+                    self.ecs.fetch_mut::<gamelog::GameLog>().log(LogCategory::System, "The way up goes no further.");
+                    newrunstate = RunState::AwaitingInput;
+                }
             }
             RunState::TownPortal => {
                 // Spawn the portal
                 spawner::spawn_town_portal(&mut self.ecs);
 
                 // Transition
-                let map_depth = self.ecs.fetch::<Map>().depth;
-                let destination_offset = 0 - (map_depth-1);
-                self.goto_level(destination_offset);
+                self.goto_level(LevelId::main(1));
                 self.mapgen_next_state = Some(RunState::PreRun);
                 newrunstate = RunState::MapGeneration;
             }
-            RunState::TeleportingToOtherLevel{x, y, depth} => {
-                self.goto_level(depth-1);
+            RunState::TeleportingToOtherLevel{x, y, level} => {
+                self.goto_level(level);
                 let player_entity = self.ecs.fetch::<Entity>();
                 if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(*player_entity) {
                     pos.x = x;
@@ -447,16 +610,54 @@ impl GameState for State {
 }
 
 impl State {
-    fn goto_level(&mut self, offset: i32) {
+    fn goto_level(&mut self, level: LevelId) {
         freeze_level_entities(&mut self.ecs);
 
+        // Notify the player
+        {
+            let current_branch = self.ecs.fetch::<Map>().branch;
+            let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+            if level.branch != current_branch && level.branch == Branch::Main {
+                gamelog.log(LogCategory::System, format!("You leave the {}.", current_branch.name()));
+            } else if level.branch != current_branch {
+                gamelog.log(LogCategory::System, format!("You enter the {}.", level.branch.name()));
+            } else {
+                gamelog.log(LogCategory::System, "You change level.");
+            }
+        }
+
         // Build a new map and place the player
-        let current_depth = self.ecs.fetch::<Map>().depth;
-        self.generate_world_map(current_depth + offset, offset);
+        self.generate_world_map(level);
+    }
 
-        // Notify the player
-        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
-        gamelog.entries.push("You change level.".to_string());
+    /// Carries out whatever was picked from a right-click menu.
+    fn context_action(&mut self, action : gui::ContextAction) -> RunState {
+        let player_entity = *self.ecs.fetch::<Entity>();
+        match action {
+            gui::ContextAction::Examine(entity) => {
+                let index = gui::look_targets(&self.ecs).iter().position(|e| *e == entity).unwrap_or(0);
+                RunState::LookMode{ index }
+            }
+            gui::ContextAction::Travel(target) => travel::travel_to(&mut self.ecs, target, false),
+            gui::ContextAction::PickUp(target) => pick_up_at(&mut self.ecs, target),
+            gui::ContextAction::Attack(target) => {
+                let mut intent = self.ecs.write_storage::<WantsToMelee>();
+                intent.insert(player_entity, WantsToMelee{ target }).expect("Unable to insert intent");
+                RunState::Ticking
+            }
+            gui::ContextAction::Throw(item, target) => {
+                let name = gui::get_item_display_name(&self.ecs, item);
+                self.ecs.fetch_mut::<gamelog::GameLog>().log(LogCategory::Combat, format!("You throw the {}.", name));
+                let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                intent.insert(player_entity, WantsToUseItem{ item, target: Some(target) }).expect("Unable to insert intent");
+                RunState::Ticking
+            }
+            gui::ContextAction::UseOn(item, target) => {
+                let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                intent.insert(player_entity, WantsToUseItem{ item, target: Some(target) }).expect("Unable to insert intent");
+                RunState::Ticking
+            }
+        }
     }
 
     fn game_over_cleanup(&mut self) {
@@ -480,16 +681,21 @@ impl State {
         self.ecs.insert(map::MasterDungeonMap::new());
 
         // Build a new map and place the player
-        self.generate_world_map(1, 0);
+        self.generate_world_map(LevelId::main(1));
     }
 
-    fn generate_world_map(&mut self, new_depth : i32, offset: i32) {
+    fn generate_world_map(&mut self, new_level : LevelId) {
         self.mapgen_index = 0;
         self.mapgen_timer = 0.0;
         self.mapgen_history.clear();
-        let map_building_info = map::level_transition(&mut self.ecs, new_depth, offset);
+        let map_building_info = map::level_transition(&mut self.ecs, new_level);
         if let Some(history) = map_building_info {
             self.mapgen_history = history;
+
+            // First time here, so tell the player what sort of place it is
+            if let Some(theme) = self.ecs.fetch::<Map>().theme {
+                self.ecs.fetch_mut::<gamelog::GameLog>().log(LogCategory::World, theme.feeling());
+            }
         } else {
             map::thaw_level_entities(&mut self.ecs);
         }
@@ -587,10 +793,49 @@ fn main() -> bracket_lib::prelude::BError {
     gs.ecs.register::<Slow>();
     gs.ecs.register::<DamageOverTime>();
     gs.ecs.register::<SpecialAbilities>();
+    gs.ecs.register::<Stealth>();
+    gs.ecs.register::<Behavior>();
+    gs.ecs.register::<PackLeader>();
+    gs.ecs.register::<PackMember>();
+    gs.ecs.register::<Routed>();
+    gs.ecs.register::<ItemUser>();
+    gs.ecs.register::<Ignites>();
+    gs.ecs.register::<OnFire>();
+    gs.ecs.register::<ReleasesGas>();
+    gs.ecs.register::<Paralysis>();
+    gs.ecs.register::<Swimmer>();
+    gs.ecs.register::<Flying>();
+    gs.ecs.register::<Levitation>();
+    gs.ecs.register::<InDeepWater>();
+    gs.ecs.register::<DiggingTool>();
+    gs.ecs.register::<Tunneler>();
+    gs.ecs.register::<DestroysTerrain>();
+    gs.ecs.register::<Perception>();
+    gs.ecs.register::<Searching>();
+    gs.ecs.register::<SecretDoor>();
+    gs.ecs.register::<Lock>();
+    gs.ecs.register::<Key>();
+    gs.ecs.register::<Portcullis>();
+    gs.ecs.register::<Lever>();
+    gs.ecs.register::<PressurePlate>();
+    gs.ecs.register::<Mechanism>();
+    gs.ecs.register::<RandomTeleport>();
+    gs.ecs.register::<Alarm>();
+    gs.ecs.register::<Entangles>();
+    gs.ecs.register::<Entangled>();
+    gs.ecs.register::<Disarmable>();
+    gs.ecs.register::<Camouflaged>();
+    gs.ecs.register::<PlacesTrap>();
+    gs.ecs.register::<Description>();
+    gs.ecs.register::<Throwable>();
+    gs.ecs.register::<Travelling>();
+    gs.ecs.register::<Resting>();
+    gs.ecs.register::<Remembered>();
 
     gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
     raws::load_raws();
+    keybindings::load_keybindings();
 
     gs.ecs.insert(map::MasterDungeonMap::new());
     gs.ecs.insert(Map::new(1, 64, 64, "New Map"));
@@ -599,11 +844,14 @@ fn main() -> bracket_lib::prelude::BError {
     let player_entity = spawner::player(&mut gs.ecs, 0, 0);
     gs.ecs.insert(player_entity);
     gs.ecs.insert(RunState::MapGeneration{} );
-    gs.ecs.insert(gamelog::GameLog{ entries : vec!["Welcome to Rusty Roguelike".to_string()] });
+    let mut gamelog = gamelog::GameLog::new();
+    gamelog.log(LogCategory::System, "Welcome to Rusty Roguelike");
+    gs.ecs.insert(gamelog);
     gs.ecs.insert(particle_system::ParticleBuilder::new());
+    gs.ecs.insert(ai::DijkstraMaps::new());
     gs.ecs.insert(rex_assets::RexAssets::new());
 
-    gs.generate_world_map(1, 0);
+    gs.generate_world_map(LevelId::main(1));
 
     bracket_lib::prelude::main_loop(context, gs)
 }
@@ -0,0 +1,118 @@
+use serde::{Serialize, Deserialize};
+
+/// The dungeon proper, and the side branches that split off from it.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug, Default)]
+pub enum Branch { #[default] Main, Mines, FloodedTemple, Crypt }
+
+pub const BRANCHES : [Branch; 4] = [Branch::Main, Branch::Mines, Branch::FloodedTemple, Branch::Crypt];
+
+impl Branch {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Branch::Main => "Dungeon",
+            Branch::Mines => "Old Mines",
+            Branch::FloodedTemple => "Flooded Temple",
+            Branch::Crypt => "Crypt"
+        }
+    }
+
+    /// Where a side branch splits off from its parent; the main dungeon has no parent.
+    pub fn entrance(&self) -> Option<LevelId> {
+        match self {
+            Branch::Main => None,
+            Branch::Mines => Some(LevelId::main(3)),
+            Branch::FloodedTemple => Some(LevelId::main(4)),
+            Branch::Crypt => Some(LevelId::main(6))
+        }
+    }
+
+    /// The depth of the branch's first level.
+    pub fn first_depth(&self) -> i32 {
+        match self.entrance() {
+            Some(entrance) => entrance.depth + 1,
+            None => 1
+        }
+    }
+
+    /// The depth of the branch's bottom level, beyond which there are no stairs down.
+    pub fn last_depth(&self) -> Option<i32> {
+        match self {
+            Branch::Main => None,
+            Branch::Mines => Some(6),
+            Branch::FloodedTemple => Some(6),
+            Branch::Crypt => Some(9)
+        }
+    }
+
+    pub fn color(&self) -> (f32, f32, f32) {
+        match self {
+            Branch::Main => (0.0, 1.0, 1.0),
+            Branch::Mines => (0.8, 0.6, 0.2),
+            Branch::FloodedTemple => (0.2, 0.5, 1.0),
+            Branch::Crypt => (0.7, 0.4, 0.9)
+        }
+    }
+}
+
+/// Names a level uniquely: the branch it is in, and how deep it is.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct LevelId {
+    pub branch : Branch,
+    pub depth : i32
+}
+
+impl LevelId {
+    pub fn new(branch : Branch, depth : i32) -> LevelId {
+        LevelId{ branch, depth }
+    }
+
+    pub fn main(depth : i32) -> LevelId {
+        LevelId{ branch: Branch::Main, depth }
+    }
+
+    /// The next level down the same branch, if the branch goes any deeper.
+    pub fn deeper(&self) -> Option<LevelId> {
+        match self.branch.last_depth() {
+            Some(last) if self.depth >= last => None,
+            _ => Some(LevelId::new(self.branch, self.depth + 1))
+        }
+    }
+
+    /// The level above; climbing out of a branch's first level takes you back to its parent.
+    pub fn shallower(&self) -> Option<LevelId> {
+        if self.depth <= self.branch.first_depth() {
+            self.branch.entrance()
+        } else {
+            Some(LevelId::new(self.branch, self.depth - 1))
+        }
+    }
+
+    /// Which side branches have their way in on this level.
+    pub fn branch_entrances(&self) -> Vec<Branch> {
+        BRANCHES.iter()
+            .filter(|b| b.entrance() == Some(*self))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deeper_stops_at_the_bottom_of_a_branch() {
+        assert_eq!(LevelId::main(3).deeper(), Some(LevelId::main(4)));
+        assert_eq!(LevelId::new(Branch::Mines, 5).deeper(), Some(LevelId::new(Branch::Mines, 6)));
+        assert_eq!(LevelId::new(Branch::Mines, 6).deeper(), None);
+        assert_eq!(LevelId::new(Branch::Crypt, 9).deeper(), None);
+    }
+
+    #[test]
+    fn shallower_climbs_out_into_the_parent() {
+        assert_eq!(LevelId::main(4).shallower(), Some(LevelId::main(3)));
+        assert_eq!(LevelId::new(Branch::Mines, 4).shallower(), Some(LevelId::main(3)));
+        assert_eq!(LevelId::new(Branch::FloodedTemple, 6).shallower(), Some(LevelId::new(Branch::FloodedTemple, 5)));
+        assert_eq!(LevelId::main(1).shallower(), None);
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
-use super::{Map, TileType};
+use super::{Map, TileType, LevelId};
 use crate::components::{Position, Viewshed, OtherLevelPosition};
 use crate::map_builders::level_builder;
 use specs::prelude::*;
@@ -8,7 +8,7 @@ use bracket_lib::prelude::{Point, RandomNumberGenerator};
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct MasterDungeonMap {
-    maps : HashMap<i32, Map>,
+    maps : Vec<Map>,
     pub identified_items : HashSet<String>,
     pub scroll_mappings : HashMap<String, String>,
     pub potion_mappings : HashMap<String, String>
@@ -17,7 +17,7 @@ pub struct MasterDungeonMap {
 impl MasterDungeonMap {
     pub fn new() -> MasterDungeonMap {
         let mut dm = MasterDungeonMap{
-            maps: HashMap::new() ,
+            maps: Vec::new(),
             identified_items : HashSet::new(),
             scroll_mappings : HashMap::new(),
             potion_mappings : HashMap::new()
@@ -38,18 +38,26 @@ impl MasterDungeonMap {
         dm
     }
 
+    // Kept as a list, since level ids can't be used as keys once serialized
     pub fn store_map(&mut self, map : &Map) {
-        self.maps.insert(map.depth, map.clone());
-    }
-
-    pub fn get_map(&self, depth : i32) -> Option<Map> {
-        if self.maps.contains_key(&depth) {
-            let result = self.maps[&depth].clone();
-            Some(result)
+        let level = map.level_id();
+        if let Some(existing) = self.maps.iter_mut().find(|m| m.level_id() == level) {
+            *existing = map.clone();
         } else {
-            None
+            self.maps.push(map.clone());
         }
     }
+
+    pub fn get_map(&self, level : LevelId) -> Option<Map> {
+        let mut map = self.maps.iter().find(|m| m.level_id() == level).cloned()?;
+        map.fill_missing_layers();
+        Some(map)
+    }
+
+    /// Every level the player has been to, with its name.
+    pub fn visited_levels(&self) -> Vec<(LevelId, String)> {
+        self.maps.iter().map(|m| (m.level_id(), m.name.clone())).collect()
+    }
 }
 
 fn make_scroll_name(rng: &mut RandomNumberGenerator) -> String {
@@ -112,11 +120,13 @@ fn make_potion_name(rng: &mut RandomNumberGenerator, used_names : &mut HashSet<S
     }
 }
 
-fn transition_to_new_map(ecs : &mut World, new_depth: i32) -> Vec<Map> {
+fn transition_to_new_map(ecs : &mut World, new_level: LevelId) -> Vec<Map> {
     let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-    let mut builder = level_builder(new_depth, &mut rng, 80, 50);
+    let mut builder = level_builder(new_level, &mut rng, 80, 50);
     builder.build_map(&mut rng);
-    if new_depth > 1 {
+    builder.build_data.map.branch = new_level.branch;
+    builder.build_data.map.depth = new_level.depth;
+    if new_level.depth > 1 {
         if let Some(pos) = &builder.build_data.starting_position {
             let up_idx = builder.build_data.map.xy_idx(pos.x, pos.y);
             builder.build_data.map.tiles[up_idx] = TileType::UpStairs;
@@ -160,17 +170,27 @@ fn transition_to_new_map(ecs : &mut World, new_depth: i32) -> Vec<Map> {
     mapgen_history
 }
 
-fn transition_to_existing_map(ecs: &mut World, new_depth: i32, offset: i32) {
+fn transition_to_existing_map(ecs: &mut World, new_level: LevelId, from: LevelId) {
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
-    let map = dungeon_master.get_map(new_depth).unwrap();
+    let map = dungeon_master.get_map(new_level).unwrap();
     let mut worldmap_resource = ecs.write_resource::<Map>();
     let player_entity = ecs.fetch::<Entity>();
 
-    // Find the down stairs and place the player
+    // Climbing out of a branch puts us on its stairs; otherwise we arrive on whichever
+    // stairs lead back the way we came
+    let branch_exit = map.branch_exits.iter()
+        .find(|(_, branch)| **branch == from.branch && from.branch != new_level.branch)
+        .map(|(idx, _)| *idx);
+    let stair_type = if from.depth > new_level.depth { TileType::DownStairs } else { TileType::UpStairs };
+
+    // Find the stairs and place the player
     let w = map.width;
-    let stair_type = if offset < 0 { TileType::DownStairs } else { TileType::UpStairs };
     for (idx, tt) in map.tiles.iter().enumerate() {
-        if *tt == stair_type {
+        let arrival = match branch_exit {
+            Some(exit_idx) => idx == exit_idx,
+            None => *tt == stair_type && !map.branch_exits.contains_key(&idx)
+        };
+        if arrival {
             let mut player_position = ecs.write_resource::<Point>();
             *player_position = Point::new(idx as i32 % w, idx as i32 / w);
             let mut position_components = ecs.write_storage::<Position>();
@@ -178,7 +198,7 @@ fn transition_to_existing_map(ecs: &mut World, new_depth: i32, offset: i32) {
             if let Some(player_pos_comp) = player_pos_comp {
                 player_pos_comp.x = idx as i32 % w;
                 player_pos_comp.y = idx as i32 / w;
-                if new_depth == 1 {
+                if new_level.depth == 1 {
                     player_pos_comp.x -= 1;
                 }
             }
@@ -201,13 +221,16 @@ pub fn freeze_level_entities(ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
     let player_entity = ecs.fetch::<Entity>();
-    let map_depth = ecs.fetch::<Map>().depth;
+    let map_level = ecs.fetch::<Map>().level_id();
+
+    // Terrain can change while we're here, so remember the level as we left it
+    ecs.write_resource::<MasterDungeonMap>().store_map(&ecs.fetch::<Map>());
 
     // Find positions and make OtherLevelPosition
     let mut pos_to_delete : Vec<Entity> = Vec::new();
     for (entity, pos) in (&entities, &positions).join() {
         if entity != *player_entity {
-            other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, depth: map_depth }).expect("Insert fail");
+            other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, level: map_level }).expect("Insert fail");
             pos_to_delete.push(entity);
         }
     }
@@ -224,12 +247,12 @@ pub fn thaw_level_entities(ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
     let player_entity = ecs.fetch::<Entity>();
-    let map_depth = ecs.fetch::<Map>().depth;
+    let map_level = ecs.fetch::<Map>().level_id();
 
     // Find OtherLevelPosition
     let mut pos_to_delete : Vec<Entity> = Vec::new();
     for (entity, pos) in (&entities, &other_level_positions).join() {
-        if entity != *player_entity && pos.depth == map_depth {
+        if entity != *player_entity && pos.level == map_level {
             positions.insert(entity, Position{ x: pos.x, y: pos.y }).expect("Insert fail");
             pos_to_delete.push(entity);
         }
@@ -241,17 +264,18 @@ pub fn thaw_level_entities(ecs: &mut World) {
     }
 }
 
-pub fn level_transition(ecs : &mut World, new_depth: i32, offset: i32) -> Option<Vec<Map>> {
+pub fn level_transition(ecs : &mut World, new_level: LevelId) -> Option<Vec<Map>> {
     // Obtain the master dungeon map
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
+    let from = ecs.fetch::<Map>().level_id();
 
     // Do we already have a map?
-    if dungeon_master.get_map(new_depth).is_some() {
+    if dungeon_master.get_map(new_level).is_some() {
         std::mem::drop(dungeon_master);
-        transition_to_existing_map(ecs, new_depth, offset);
+        transition_to_existing_map(ecs, new_level, from);
         None
     } else {
         std::mem::drop(dungeon_master);
-        Some(transition_to_new_map(ecs, new_depth))
+        Some(transition_to_new_map(ecs, new_level))
     }
 }
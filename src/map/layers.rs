@@ -0,0 +1,102 @@
+use serde::{Serialize, Deserialize};
+
+/// What a terrain layer does to the tile it sits on.
+#[derive(Copy, Clone)]
+pub struct LayerFlags {
+    pub walkable : bool,
+    pub opaque : bool,
+    pub cost : f32,
+    pub fuel : i32
+}
+
+/// Liquids pool on top of the base tile.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Liquid { Mud }
+
+/// Things lying on the ground: they don't change what the tile is, just what it's like to cross.
+/// Water is part of the tile itself, as shallow or deep water.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Surface { Blood, Grass, Web, Rubble, Ash }
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum GasType { Smoke, Poison, Confusion, Paralysis, Methane }
+
+/// A cloud of gas, with volume being how thick it is on this tile.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct Gas {
+    pub gas_type : GasType,
+    pub volume : i32
+}
+
+/// A tile with nothing on top of it.
+pub const NO_LAYER : LayerFlags = LayerFlags{ walkable: true, opaque: false, cost: 1.0, fuel: 0 };
+
+impl LayerFlags {
+    /// Stacks another layer on top: any layer can block or hide a tile, costs multiply and
+    /// fuel adds up.
+    pub fn combine(self, other : LayerFlags) -> LayerFlags {
+        LayerFlags{
+            walkable : self.walkable && other.walkable,
+            opaque : self.opaque || other.opaque,
+            cost : self.cost * other.cost,
+            fuel : self.fuel + other.fuel
+        }
+    }
+}
+
+/// Flames are passable, but nobody in their right mind walks through them.
+pub const FIRE_FLAGS : LayerFlags = LayerFlags{ walkable: true, opaque: false, cost: 8.0, fuel: 0 };
+
+/// Gas this thick can't be seen through; smoke is rather worse.
+pub const OPAQUE_GAS_VOLUME : i32 = 50;
+pub const OPAQUE_SMOKE_VOLUME : i32 = 20;
+
+impl Liquid {
+    pub fn flags(&self) -> LayerFlags {
+        match self {
+            Liquid::Mud => LayerFlags{ walkable: true, opaque: false, cost: 1.8, fuel: 0 }
+        }
+    }
+}
+
+impl Surface {
+    pub fn flags(&self) -> LayerFlags {
+        match self {
+            Surface::Blood => LayerFlags{ walkable: true, opaque: false, cost: 1.0, fuel: 0 },
+            Surface::Grass => LayerFlags{ walkable: true, opaque: false, cost: 1.1, fuel: 4 },
+            Surface::Web => LayerFlags{ walkable: true, opaque: false, cost: 3.0, fuel: 1 },
+            Surface::Rubble => LayerFlags{ walkable: true, opaque: false, cost: 1.5, fuel: 0 },
+            Surface::Ash => LayerFlags{ walkable: true, opaque: false, cost: 1.0, fuel: 0 }
+        }
+    }
+}
+
+impl Gas {
+    pub fn flags(&self) -> LayerFlags {
+        let opaque = match self.gas_type {
+            GasType::Smoke => self.volume >= OPAQUE_SMOKE_VOLUME,
+            _ => self.volume >= OPAQUE_GAS_VOLUME
+        };
+        // Monsters know better than to wander into anything harmful
+        let cost = match self.gas_type {
+            GasType::Poison => 5.0,
+            GasType::Confusion => 3.0,
+            GasType::Paralysis => 8.0,
+            GasType::Smoke | GasType::Methane => 1.0
+        };
+        LayerFlags{ walkable: true, opaque, cost, fuel: 0 }
+    }
+}
+
+impl GasType {
+    /// How much volume a cloud of this gas loses each turn.
+    pub fn dissipation(&self) -> i32 {
+        match self {
+            GasType::Smoke => 3,
+            GasType::Poison => 2,
+            GasType::Confusion => 3,
+            GasType::Paralysis => 4,
+            GasType::Methane => 1
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Serialize, Deserialize};
+use bracket_lib::prelude::RandomNumberGenerator;
+use super::LevelId;
+
+/// Some levels have a story to them, which shapes what lives there and what is lying about.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum LevelTheme { MonsterLair, TreasureHoard, Flooded, Haunted }
+
+impl LevelTheme {
+    /// Themes don't start turning up until the player is past the woods, and most levels
+    /// don't get one at all.
+    pub fn roll(level : LevelId, rng : &mut RandomNumberGenerator) -> Option<LevelTheme> {
+        if level.depth < 3 || rng.roll_dice(1, 4) > 1 { return None; }
+        match rng.roll_dice(1, 4) {
+            1 => Some(LevelTheme::MonsterLair),
+            2 => Some(LevelTheme::TreasureHoard),
+            3 if level.branch != super::Branch::FloodedTemple => Some(LevelTheme::Flooded),
+            _ => Some(LevelTheme::Haunted)
+        }
+    }
+
+    /// The name the spawn table uses to weight entries for this theme.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LevelTheme::MonsterLair => "Monster Lair",
+            LevelTheme::TreasureHoard => "Treasure Hoard",
+            LevelTheme::Flooded => "Flooded",
+            LevelTheme::Haunted => "Haunted"
+        }
+    }
+
+    /// Added to the spawn weight of every item that doesn't list the theme itself.
+    pub fn item_bonus(&self) -> i32 {
+        match self {
+            LevelTheme::TreasureHoard => 4,
+            _ => 0
+        }
+    }
+
+    /// What the player gets told on first setting foot on the level.
+    pub fn feeling(&self) -> &'static str {
+        match self {
+            LevelTheme::MonsterLair => "The air reeks of beasts. Something has made its den here.",
+            LevelTheme::TreasureHoard => "You have a feeling there is treasure to be found here.",
+            LevelTheme::Flooded => "Water drips from every surface; this level has flooded.",
+            LevelTheme::Haunted => "A chill runs down your spine. This place is haunted."
+        }
+    }
+
+    /// Props scattered about the level to set the scene.
+    pub fn props(&self) -> &'static [&'static str] {
+        match self {
+            LevelTheme::MonsterLair => &["Dead Thing", "Hide Rack"],
+            LevelTheme::TreasureHoard => &["Weapon Rack", "Armor Stand", "Cabinet"],
+            LevelTheme::Flooded => &[],
+            LevelTheme::Haunted => &["Gravestone", "Candle"]
+        }
+    }
+
+    /// Extra mouths to feed in every spawn region.
+    pub fn extra_spawns(&self) -> i32 {
+        match self {
+            LevelTheme::MonsterLair => 2,
+            _ => 0
+        }
+    }
+}
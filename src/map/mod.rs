@@ -1,11 +1,17 @@
 use bracket_lib::prelude::{ BaseMap, Algorithm2D, Point, RGB, SmallVec, DistanceAlg };
 use serde::{Serialize, Deserialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 mod tiletype;
-pub use tiletype::{TileType, tile_walkable, tile_opaque, tile_cost};
+pub use tiletype::{TileType, tile_walkable, tile_opaque, tile_cost, tile_fuel};
+mod layers;
+pub use layers::*;
 mod themes;
 pub use themes::*;
 mod dungeon;
+mod branches;
+pub use branches::*;
+mod level_theme;
+pub use level_theme::LevelTheme;
 pub use dungeon::{MasterDungeonMap, level_transition, freeze_level_entities, thaw_level_entities};
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -16,11 +22,24 @@ pub struct Map {
     pub revealed_tiles : Vec<bool>,
     pub visible_tiles : Vec<bool>,
     pub depth : i32,
-    pub bloodstains : HashSet<usize>,
+    pub branch : Branch,
+    pub branch_exits : HashMap<usize, Branch>,
+    pub theme : Option<LevelTheme>,
+    #[serde(default)]
+    pub liquid : Vec<Option<Liquid>>,
+    #[serde(default)]
+    pub surface : Vec<Option<Surface>>,
+    #[serde(default)]
+    pub gas : Vec<Option<Gas>>,
+    #[serde(default)]
+    pub fire : Vec<i32>,
     pub view_blocked : HashSet<usize>,
     pub name : String,
     pub outdoors : bool,
     pub light : Vec<RGB>,
+    /// Goes up whenever the terrain or what blocks it changes mid-level.
+    #[serde(default)]
+    pub revision : u32,
 }
 
 impl Map {
@@ -42,6 +61,86 @@ impl Map {
         crate::spatial::clear();
     }
 
+    /// What all the terrain layers sitting on top of the base tile add up to.
+    fn layer_flags(&self, idx : usize) -> LayerFlags {
+        let mut flags = NO_LAYER;
+        if let Some(liquid) = self.liquid[idx] { flags = flags.combine(liquid.flags()); }
+        if let Some(surface) = self.surface[idx] { flags = flags.combine(surface.flags()); }
+        if let Some(gas) = self.gas[idx] { flags = flags.combine(gas.flags()); }
+        if self.fire[idx] > 0 { flags = flags.combine(FIRE_FLAGS); }
+        flags
+    }
+
+    pub fn walkable(&self, idx : usize) -> bool {
+        tile_walkable(self.tiles[idx]) && self.layer_flags(idx).walkable
+    }
+
+    pub fn opaque(&self, idx : usize) -> bool {
+        tile_opaque(self.tiles[idx]) || self.layer_flags(idx).opaque
+    }
+
+    pub fn cost(&self, idx : usize) -> f32 {
+        tile_cost(self.tiles[idx]) * self.layer_flags(idx).cost
+    }
+
+    /// How long the tile would burn for if set alight; zero if it won't catch.
+    pub fn fuel(&self, idx : usize) -> i32 {
+        tile_fuel(self.tiles[idx]) + self.layer_flags(idx).fuel
+    }
+
+    /// Maps saved before the terrain layers existed come back without them.
+    pub fn fill_missing_layers(&mut self) {
+        let tile_count = self.tiles.len();
+        self.liquid.resize(tile_count, None);
+        self.surface.resize(tile_count, None);
+        self.gas.resize(tile_count, None);
+        self.fire.resize(tile_count, 0);
+    }
+
+    /// Rock that can be dug through or blown apart; the edge of the map is always solid.
+    pub fn diggable(&self, idx : usize) -> bool {
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        if x < 1 || x > self.width-2 || y < 1 || y > self.height-2 { return false; }
+        matches!(self.tiles[idx], TileType::Wall | TileType::Stalactite | TileType::Stalagmite)
+    }
+
+    /// Marks the terrain as changed, so anything worked out from it gets worked out again.
+    pub fn terrain_changed(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    /// Brings the rock down, leaving a rubble-strewn floor behind.
+    pub fn collapse(&mut self, idx : usize) {
+        self.tiles[idx] = TileType::Floor;
+        self.surface[idx] = Some(Surface::Rubble);
+        self.terrain_changed();
+    }
+
+    /// Whatever could burn has burned, leaving bare floor covered in ash.
+    pub fn burn_out(&mut self, idx : usize) {
+        self.fire[idx] = 0;
+        let burned_tile = tile_fuel(self.tiles[idx]) > 0;
+        if burned_tile { self.tiles[idx] = TileType::Floor; }
+        let burned_surface = matches!(self.surface[idx], Some(Surface::Grass) | Some(Surface::Web));
+        if burned_tile || burned_surface {
+            self.surface[idx] = Some(Surface::Ash);
+        }
+        self.terrain_changed();
+    }
+
+    pub fn level_id(&self) -> LevelId {
+        LevelId::new(self.branch, self.depth)
+    }
+
+    /// Where the down stairs on a tile lead: into a side branch, or further down this one.
+    pub fn level_below(&self, idx : usize) -> Option<LevelId> {
+        match self.branch_exits.get(&idx) {
+            Some(branch) => Some(LevelId::new(*branch, branch.first_depth())),
+            None => self.level_id().deeper()
+        }
+    }
+
     /// Generates an empty map, consisting entirely of solid walls
     pub fn new<S : ToString>(new_depth : i32, width: i32, height: i32, name: S) -> Map {
         let map_tile_count = (width*height) as usize;
@@ -53,11 +152,18 @@ impl Map {
             revealed_tiles : vec![false; map_tile_count],
             visible_tiles : vec![false; map_tile_count],
             depth: new_depth,
-            bloodstains: HashSet::new(),
+            branch : Branch::Main,
+            branch_exits : HashMap::new(),
+            theme : None,
+            liquid : vec![None; map_tile_count],
+            surface : vec![None; map_tile_count],
+            gas : vec![None; map_tile_count],
+            fire : vec![0; map_tile_count],
             view_blocked : HashSet::new(),
             name : name.to_string(),
             outdoors : true,
-            light: vec![RGB::from_f32(0.0, 0.0, 0.0); map_tile_count]
+            light: vec![RGB::from_f32(0.0, 0.0, 0.0); map_tile_count],
+            revision : 0
         }
     }
 }
@@ -65,7 +171,7 @@ impl Map {
 impl BaseMap for Map {
     fn is_opaque(&self, idx:usize) -> bool {
         if idx > 0 && idx < self.tiles.len() {
-            tile_opaque(self.tiles[idx]) || self.view_blocked.contains(&idx)
+            self.opaque(idx) || self.view_blocked.contains(&idx)
         } else {
             true
         }
@@ -76,20 +182,20 @@ impl BaseMap for Map {
         let mut exits = SmallVec::new();
         let x = idx as i32 % self.width;
         let y = idx as i32 / self.width;
-        let tt = self.tiles[idx as usize];
+        let cost = self.cost(idx);
         let w = self.width as usize;
 
         // Cardinal directions
-        if self.is_exit_valid(x-1, y) { exits.push((idx-1, tile_cost(tt))) };
-        if self.is_exit_valid(x+1, y) { exits.push((idx+1, tile_cost(tt))) };
-        if self.is_exit_valid(x, y-1) { exits.push((idx-w, tile_cost(tt))) };
-        if self.is_exit_valid(x, y+1) { exits.push((idx+w, tile_cost(tt))) };
+        if self.is_exit_valid(x-1, y) { exits.push((idx-1, cost)) };
+        if self.is_exit_valid(x+1, y) { exits.push((idx+1, cost)) };
+        if self.is_exit_valid(x, y-1) { exits.push((idx-w, cost)) };
+        if self.is_exit_valid(x, y+1) { exits.push((idx+w, cost)) };
 
         // Diagonals
-        if self.is_exit_valid(x-1, y-1) { exits.push(((idx-w)-1, tile_cost(tt) * DIAGONAL_COST)); }
-        if self.is_exit_valid(x+1, y-1) { exits.push(((idx-w)+1, tile_cost(tt) * DIAGONAL_COST)); }
-        if self.is_exit_valid(x-1, y+1) { exits.push(((idx+w)-1, tile_cost(tt) * DIAGONAL_COST)); }
-        if self.is_exit_valid(x+1, y+1) { exits.push(((idx+w)+1, tile_cost(tt) * DIAGONAL_COST)); }
+        if self.is_exit_valid(x-1, y-1) { exits.push(((idx-w)-1, cost * DIAGONAL_COST)); }
+        if self.is_exit_valid(x+1, y-1) { exits.push(((idx-w)+1, cost * DIAGONAL_COST)); }
+        if self.is_exit_valid(x-1, y+1) { exits.push(((idx+w)-1, cost * DIAGONAL_COST)); }
+        if self.is_exit_valid(x+1, y+1) { exits.push(((idx+w)+1, cost * DIAGONAL_COST)); }
 
         exits
     }
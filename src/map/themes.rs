@@ -1,9 +1,10 @@
-use super::{Map, TileType};
+use super::{Map, TileType, Liquid, Surface, GasType, Branch};
 use bracket_lib::prelude::*;
 
 pub fn tile_glyph(idx: usize, map : &Map) -> (FontCharType, RGB, RGB) {
-    let (glyph, mut fg, mut bg) = match map.depth {
-        5 => {
+    let (mut glyph, mut fg, mut bg) = match (map.branch, map.depth) {
+        (Branch::Mines, _) => get_limestone_cavern_glyph(idx, map),
+        (Branch::Main, 5) => {
             let x = idx as i32 % map.width;
             if x < map.width/2 {
                 get_limestone_cavern_glyph(idx, map)
@@ -11,13 +12,52 @@ pub fn tile_glyph(idx: usize, map : &Map) -> (FontCharType, RGB, RGB) {
                 get_tile_glyph_default(idx, map)
             }
         }
-        4 => get_limestone_cavern_glyph(idx, map),
-        3 => get_limestone_cavern_glyph(idx, map),
-        2 => get_forest_glyph(idx, map),
+        (Branch::Main, 4) => get_limestone_cavern_glyph(idx, map),
+        (Branch::Main, 3) => get_limestone_cavern_glyph(idx, map),
+        (Branch::Main, 2) => get_forest_glyph(idx, map),
         _ => get_tile_glyph_default(idx, map)
     };
 
-    if map.bloodstains.contains(&idx) { bg = RGB::from_f32(0.75, 0., 0.); }
+    // Stairs into a side branch take on the branch's color
+    if let Some(branch) = map.branch_exits.get(&idx) {
+        let (r, g, b) = branch.color();
+        fg = RGB::from_f32(r, g, b);
+    }
+
+    // Layers are drawn lowest first, so whatever is on top wins
+    if let Some(liquid) = map.liquid[idx] {
+        let (g, f) = liquid_glyph(liquid);
+        glyph = g;
+        fg = f;
+    }
+    if let Some(surface) = map.surface[idx] {
+        match surface {
+            Surface::Blood => bg = RGB::from_f32(0.75, 0., 0.),
+            _ => {
+                let (g, f) = surface_glyph(surface);
+                glyph = g;
+                fg = f;
+            }
+        }
+    }
+    if map.fire[idx] > 0 {
+        // Flicker between flame colors as the fire burns down
+        glyph = to_cp437('^');
+        fg = match (idx + map.fire[idx] as usize) % 3 {
+            0 => RGB::named(RED),
+            1 => RGB::named(ORANGE),
+            _ => RGB::named(YELLOW)
+        };
+        bg = RGB::from_f32(0.4, 0.1, 0.);
+    }
+    if let Some(gas) = map.gas[idx] {
+        let tint = gas_color(gas.gas_type);
+        if gas.flags().opaque {
+            glyph = to_cp437('▒');
+            fg = tint;
+        }
+        bg = bg.lerp(tint, f32::min(gas.volume as f32 / 100.0, 0.8));
+    }
     if !map.visible_tiles[idx] {
         fg = fg.to_greyscale();
         bg = RGB::from_f32(0., 0., 0.); // Don't show stains out of visual range
@@ -29,6 +69,32 @@ pub fn tile_glyph(idx: usize, map : &Map) -> (FontCharType, RGB, RGB) {
     (glyph, fg, bg)
 }
 
+fn liquid_glyph(liquid : Liquid) -> (FontCharType, RGB) {
+    match liquid {
+        Liquid::Mud => (to_cp437('~'), RGB::named(SADDLEBROWN))
+    }
+}
+
+fn surface_glyph(surface : Surface) -> (FontCharType, RGB) {
+    match surface {
+        Surface::Blood => (to_cp437('.'), RGB::from_f32(0.75, 0., 0.)),
+        Surface::Grass => (to_cp437('"'), RGB::named(GREEN)),
+        Surface::Web => (to_cp437('%'), RGB::named(LIGHT_GRAY)),
+        Surface::Rubble => (to_cp437(','), RGB::from_f32(0.6, 0.6, 0.6)),
+        Surface::Ash => (to_cp437('.'), RGB::from_f32(0.3, 0.3, 0.3))
+    }
+}
+
+fn gas_color(gas : GasType) -> RGB {
+    match gas {
+        GasType::Smoke => RGB::from_f32(0.5, 0.5, 0.5),
+        GasType::Poison => RGB::from_f32(0.2, 0.8, 0.2),
+        GasType::Confusion => RGB::from_f32(0.6, 0.3, 0.9),
+        GasType::Paralysis => RGB::from_f32(1.0, 0.5, 0.8),
+        GasType::Methane => RGB::from_f32(0.5, 0.45, 0.3)
+    }
+}
+
 fn get_forest_glyph(idx:usize, map: &Map) -> (FontCharType, RGB, RGB) {
     let glyph;
     let fg;
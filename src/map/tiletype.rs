@@ -20,8 +20,8 @@ pub enum TileType {
 pub fn tile_walkable(tt : TileType) -> bool {
     match tt {
         TileType::Floor | TileType::DownStairs | TileType::Road | TileType::Grass |
-        TileType::ShallowWater | TileType::WoodFloor | TileType::Bridge | TileType::Gravel |
-        TileType::UpStairs
+        TileType::ShallowWater | TileType::DeepWater | TileType::WoodFloor | TileType::Bridge |
+        TileType::Gravel | TileType::UpStairs
             => true,
         _ => false
     }
@@ -39,6 +39,16 @@ pub fn tile_cost(tt : TileType) -> f32 {
         TileType::Road => 0.8,
         TileType::Grass => 1.1,
         TileType::ShallowWater => 1.2,
+        TileType::DeepWater => 4.0,
         _ => 1.0
     }
 }
+
+/// How many turns this kind of tile will burn for, if it burns at all.
+pub fn tile_fuel(tt : TileType) -> i32 {
+    match tt {
+        TileType::Grass => 4,
+        TileType::WoodFloor => 8,
+        _ => 0
+    }
+}
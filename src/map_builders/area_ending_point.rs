@@ -44,7 +44,7 @@ impl AreaEndingPosition {
 
         let mut available_floors : Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if map::tile_walkable(*tiletype) {
+            if map::tile_walkable(*tiletype) && *tiletype != TileType::DeepWater {
                 available_floors.push(
                     (
                         idx,
@@ -1,4 +1,4 @@
-use super::{MetaMapBuilder, BuilderMap, Position};
+use super::{MetaMapBuilder, BuilderMap, Position, TileType};
 use crate::map;
 use bracket_lib::prelude::{RandomNumberGenerator, DistanceAlg, Point };
 #[allow(dead_code)]
@@ -44,7 +44,7 @@ impl AreaStartingPosition {
 
         let mut available_floors : Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if map::tile_walkable(*tiletype) {
+            if map::tile_walkable(*tiletype) && *tiletype != TileType::DeepWater {
                 available_floors.push(
                     (
                         idx,
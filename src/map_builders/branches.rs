@@ -0,0 +1,189 @@
+use super::{BuilderChain, DrunkardsWalkBuilder, XStart, YStart, AreaStartingPosition,
+    CullUnreachable, VoronoiSpawning, MetaMapBuilder, BuilderMap, TileType, DistantExit,
+    CellularAutomataBuilder, BspInteriorBuilder, RoomBasedStartingPosition, RoomBasedStairs,
+    RoomBasedSpawner, DoorPlacement, PrefabBuilder, CaveDecorator};
+use bracket_lib::prelude::{DijkstraMap, RandomNumberGenerator, Point, DistanceAlg};
+use crate::map::{Branch, Surface};
+
+pub fn mines_builder(new_depth: i32, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    let mut chain = BuilderChain::new(new_depth, width, height, "The Old Mines");
+    match rng.roll_dice(1, 2) {
+        1 => chain.start_with(DrunkardsWalkBuilder::fat_passages()),
+        _ => chain.start_with(DrunkardsWalkBuilder::open_halls())
+    }
+    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
+    chain.with(CullUnreachable::new());
+    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
+    chain.with(VoronoiSpawning::new());
+    chain.with(DistantExit::new());
+    chain.with(CaveDecorator::new());
+    chain
+}
+
+pub fn flooded_temple_builder(new_depth: i32, _rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    let mut chain = BuilderChain::new(new_depth, width, height, "The Flooded Temple");
+    chain.start_with(CellularAutomataBuilder::new());
+    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
+    chain.with(CullUnreachable::new());
+    chain.with(AreaStartingPosition::new(XStart::RIGHT, YStart::CENTER));
+    chain.with(VoronoiSpawning::new());
+    chain.with(DistantExit::new());
+    chain.with(Flooding::new());
+    chain
+}
+
+pub fn crypt_builder(new_depth: i32, _rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    let mut chain = BuilderChain::new(new_depth, width, height, "The Crypt");
+    chain.start_with(BspInteriorBuilder::new());
+    chain.with(RoomBasedStartingPosition::new());
+    chain.with(RoomBasedStairs::new());
+    chain.with(RoomBasedSpawner::new());
+    chain.with(DoorPlacement::new());
+    chain.with(PrefabBuilder::vaults());
+    chain.with(CryptDecorator::new());
+    chain
+}
+
+/// Lets the water in: the floor is left awash, and the walls crumble into deep pools.
+pub struct Flooding {}
+
+impl MetaMapBuilder for Flooding {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl Flooding {
+    #[allow(dead_code)]
+    pub fn new() -> Box<Flooding> {
+        Box::new(Flooding{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let old_map = build_data.map.clone();
+        let width = old_map.width;
+        let height = old_map.height;
+        for idx in 0..old_map.tiles.len() {
+            let x = idx as i32 % width;
+            let y = idx as i32 / width;
+            if x < 1 || x > width-2 || y < 1 || y > height-2 { continue; }
+            match old_map.tiles[idx] {
+                TileType::Floor if rng.roll_dice(1, 3) > 1 => build_data.map.tiles[idx] = TileType::ShallowWater,
+                TileType::Wall => {
+                    // Only walls that border open floor give way, so no passage gets cut off
+                    let open = [idx-1, idx+1, idx-width as usize, idx+width as usize].iter()
+                        .filter(|n| old_map.tiles[**n] != TileType::Wall)
+                        .count();
+                    if open >= 2 && rng.roll_dice(1, 3) == 1 {
+                        build_data.map.tiles[idx] = TileType::DeepWater;
+                    }
+                }
+                _ => {}
+            }
+        }
+        build_data.take_snapshot();
+        build_data.map.outdoors = false;
+    }
+}
+
+/// Dust and old bones lie over everything in the crypt.
+pub struct CryptDecorator {}
+
+impl MetaMapBuilder for CryptDecorator {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl CryptDecorator {
+    #[allow(dead_code)]
+    pub fn new() -> Box<CryptDecorator> {
+        Box::new(CryptDecorator{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        for idx in 0..build_data.map.tiles.len() {
+            if build_data.map.tiles[idx] == TileType::Floor && rng.roll_dice(1, 8) == 1 {
+                build_data.map.surface[idx] = Some(Surface::Rubble);
+            }
+        }
+        build_data.take_snapshot();
+        build_data.map.outdoors = false;
+    }
+}
+
+/// Adds a second way down, into a side branch, somewhere well away from both the start and
+/// the level's own stairs.
+pub struct BranchEntrance {
+    branch : Branch
+}
+
+impl MetaMapBuilder for BranchEntrance {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl BranchEntrance {
+    #[allow(dead_code)]
+    pub fn new(branch : Branch) -> Box<BranchEntrance> {
+        Box::new(BranchEntrance{ branch })
+    }
+
+    fn build(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
+        let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
+        let width = build_data.map.width;
+        let stairs : Vec<Point> = build_data.map.tiles.iter().enumerate()
+            .filter(|(_, tt)| **tt == TileType::DownStairs || **tt == TileType::UpStairs)
+            .map(|(idx, _)| Point::new(idx as i32 % width, idx as i32 / width))
+            .collect();
+
+        build_data.map.populate_blocked();
+        let dijkstra_map = DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &[start_idx], &build_data.map, 3000.0);
+        let mut best : Option<(usize, f32)> = None;
+        for (idx, tile) in build_data.map.tiles.iter().enumerate() {
+            if *tile != TileType::Floor || dijkstra_map.map[idx] == f32::MAX { continue; }
+            let pt = Point::new(idx as i32 % width, idx as i32 / width);
+            // Score by the distance to whichever of the start and the other stairs is nearest
+            let nearest_stairs = stairs.iter()
+                .map(|s| DistanceAlg::Pythagoras.distance2d(*s, pt))
+                .fold(f32::MAX, f32::min);
+            let score = f32::min(dijkstra_map.map[idx], nearest_stairs);
+            if best.is_none() || score > best.unwrap().1 {
+                best = Some((idx, score));
+            }
+        }
+
+        if let Some((idx, _)) = best {
+            build_data.map.tiles[idx] = TileType::DownStairs;
+            build_data.map.branch_exits.insert(idx, self.branch);
+            build_data.take_snapshot();
+        }
+    }
+}
+
+/// The bottom of a branch: whatever stairs the builders dug are filled back in.
+pub struct BranchBottom {}
+
+impl MetaMapBuilder for BranchBottom {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl BranchBottom {
+    #[allow(dead_code)]
+    pub fn new() -> Box<BranchBottom> {
+        Box::new(BranchBottom{})
+    }
+
+    fn build(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        for (idx, tt) in build_data.map.tiles.iter_mut().enumerate() {
+            if *tt == TileType::DownStairs && !build_data.map.branch_exits.contains_key(&idx) {
+                *tt = TileType::Floor;
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
@@ -1,5 +1,5 @@
 use super::{MetaMapBuilder, BuilderMap, TileType };
-use bracket_lib::prelude::RandomNumberGenerator;
+use bracket_lib::prelude::{DijkstraMap, RandomNumberGenerator};
 pub struct DoorPlacement {}
 
 impl MetaMapBuilder for DoorPlacement {
@@ -54,7 +54,7 @@ impl DoorPlacement {
             for hall in halls.iter() {
                 if hall.len() > 2 { // We aren't interested in tiny corridors
                     if self.door_possible(build_data, hall[0]) {
-                        build_data.spawn_list.push((hall[0], "Door".to_string()));
+                        self.place_door(rng, build_data, hall[0]);
                     }
                 }
             }
@@ -63,9 +63,35 @@ impl DoorPlacement {
             let tiles = build_data.map.tiles.clone();
             for (i, tile) in tiles.iter().enumerate() {
                 if *tile == TileType::Floor && self.door_possible(build_data, i) && rng.roll_dice(1,3)==1 {
-                    build_data.spawn_list.push((i, "Door".to_string()));
+                    self.place_door(rng, build_data, i);
                 }
             }
         }
     }
+
+    /// Every so often a door is hidden in the wall, so long as that doesn't cut the exit off.
+    fn place_door(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, idx : usize) {
+        if rng.roll_dice(1, 8)==1 {
+            build_data.map.tiles[idx] = TileType::Wall;
+            if self.exit_reachable(build_data) {
+                build_data.spawn_list.push((idx, "Secret Door".to_string()));
+                return;
+            }
+            build_data.map.tiles[idx] = TileType::Floor;
+        }
+        build_data.spawn_list.push((idx, "Door".to_string()));
+    }
+
+    fn exit_reachable(&self, build_data : &mut BuilderMap) -> bool {
+        let start = if let Some(start) = &build_data.starting_position { start.clone() } else { return false; };
+        let exit = build_data.map.tiles.iter().position(|tt| *tt == TileType::DownStairs);
+        if let Some(exit) = exit {
+            let start_idx = build_data.map.xy_idx(start.x, start.y);
+            build_data.map.populate_blocked();
+            let dijkstra_map = DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &[start_idx], &build_data.map, 1000.0);
+            dijkstra_map.map[exit] < f32::MAX
+        } else {
+            false
+        }
+    }
 }
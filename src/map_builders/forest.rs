@@ -2,6 +2,7 @@ use super::{BuilderChain, CellularAutomataBuilder, XStart, YStart, AreaStartingP
     CullUnreachable, VoronoiSpawning, MetaMapBuilder, BuilderMap, TileType};
 use bracket_lib::prelude::{RandomNumberGenerator, Point, DistanceAlg, a_star_search};
 use crate::map;
+use crate::map::Surface;
 
 pub fn forest_builder(new_depth: i32, _rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
     let mut chain = BuilderChain::new(new_depth, width, height, "Into the Woods");
@@ -11,9 +12,34 @@ pub fn forest_builder(new_depth: i32, _rng: &mut RandomNumberGenerator, width: i
     chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
     chain.with(VoronoiSpawning::new());
     chain.with(YellowBrickRoad::new());
+    chain.with(Undergrowth::new());
     chain
 }
 
+/// Long grass growing between the trees, everywhere but the road.
+pub struct Undergrowth {}
+
+impl MetaMapBuilder for Undergrowth {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl Undergrowth {
+    pub fn new() -> Box<Undergrowth> {
+        Box::new(Undergrowth{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        for idx in 0 .. build_data.map.tiles.len() {
+            if build_data.map.tiles[idx] == TileType::Floor && rng.roll_dice(1, 3) == 1 {
+                build_data.map.surface[idx] = Some(Surface::Grass);
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
+
 pub struct YellowBrickRoad {}
 
 impl MetaMapBuilder for YellowBrickRoad {
@@ -31,7 +57,7 @@ impl YellowBrickRoad {
     fn find_exit(&self, build_data : &mut BuilderMap, seed_x : i32, seed_y: i32) -> (i32, i32) {
         let mut available_floors : Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if map::tile_walkable(*tiletype) {
+            if map::tile_walkable(*tiletype) && *tiletype != TileType::DeepWater {
                 available_floors.push(
                     (
                         idx,
@@ -4,6 +4,7 @@ use super::{BuilderChain, DrunkardsWalkBuilder, XStart, YStart, AreaStartingPosi
     BspDungeonBuilder, RoomSorter, RoomSort, NearestCorridors, RoomExploder, RoomDrawer,
     RoomBasedSpawner, XEnd, YEnd};
 use bracket_lib::prelude::RandomNumberGenerator;
+use crate::map::{Surface, Liquid};
 pub fn limestone_cavern_builder(new_depth: i32, _rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
     let mut chain = BuilderChain::new(new_depth, width, height, "Limestone Caverns");
     chain.start_with(DrunkardsWalkBuilder::winding_passages());
@@ -86,9 +87,44 @@ impl CaveDecorator {
                 }
             }
         }
+        self.surfaces(rng, build_data);
         build_data.take_snapshot();
         build_data.map.outdoors = false;
     }
+
+    fn surfaces(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let width = build_data.map.width;
+        let height = build_data.map.height;
+        for idx in 0..build_data.map.tiles.len() {
+            let x = idx as i32 % width;
+            let y = idx as i32 / width;
+            if x < 1 || x > width-2 || y < 1 || y > height-2 { continue; }
+            let tt = build_data.map.tiles[idx];
+            if tt != TileType::Floor && tt != TileType::Gravel { continue; }
+
+            let mut walls = 0;
+            let mut columns = 0;
+            for ny in y-1 ..= y+1 {
+                for nx in x-1 ..= x+1 {
+                    match build_data.map.tiles[build_data.map.xy_idx(nx, ny)] {
+                        TileType::Wall => walls += 1,
+                        TileType::Stalactite | TileType::Stalagmite => columns += 1,
+                        _ => {}
+                    }
+                }
+            }
+
+            if columns > 0 && rng.roll_dice(1, 3) == 1 {
+                // Bits of rock that have fallen off the formations
+                build_data.map.surface[idx] = Some(Surface::Rubble);
+            } else if walls >= 5 && rng.roll_dice(1, 4) == 1 {
+                // Something has been spinning webs in the nooks
+                build_data.map.surface[idx] = Some(Surface::Web);
+            } else if tt == TileType::Floor && rng.roll_dice(1, 30) == 1 {
+                build_data.map.liquid[idx] = Some(Liquid::Mud);
+            }
+        }
+    }
 }
 
 pub struct CaveTransition {}
@@ -1,5 +1,6 @@
 use bracket_lib::prelude::{console, RandomNumberGenerator};
-use super::{Map, Rect, TileType, Position, spawner, SHOW_MAPGEN_VISUALIZER};
+use super::{Map, Rect, TileType, Position, Lever, PressurePlate, Mechanism, spawner, SHOW_MAPGEN_VISUALIZER};
+use crate::map::{Branch, LevelId, LevelTheme};
 use specs::prelude::*;
 mod simple_map;
 mod bsp_dungeon;
@@ -33,6 +34,8 @@ mod town;
 mod forest;
 mod limestone_cavern;
 mod area_ending_point;
+mod branches;
+mod theme_decorator;
 use forest::forest_builder;
 use limestone_cavern::*;
 use distant_exit::DistantExit;
@@ -65,6 +68,8 @@ use room_corridor_spawner::CorridorSpawner;
 use door_placement::DoorPlacement;
 use town::town_builder;
 use area_ending_point::*;
+use branches::*;
+use theme_decorator::ThemeDecorator;
 
 pub struct BuilderMap {
     pub spawn_list : Vec<(usize, String)>,
@@ -72,6 +77,7 @@ pub struct BuilderMap {
     pub starting_position : Option<Position>,
     pub rooms: Option<Vec<Rect>>,
     pub corridors: Option<Vec<Vec<usize>>>,
+    pub wiring : Vec<(usize, i32)>,
     pub history : Vec<Map>,
     pub width: i32,
     pub height: i32
@@ -106,6 +112,7 @@ impl BuilderChain {
                 starting_position: None,
                 rooms: None,
                 corridors: None,
+                wiring: Vec::new(),
                 history : Vec::new(),
                 width,
                 height
@@ -143,6 +150,28 @@ impl BuilderChain {
         for entity in self.build_data.spawn_list.iter() {
             spawner::spawn_entity(ecs, &(&entity.0, &entity.1));
         }
+        if !self.build_data.wiring.is_empty() {
+            wire_mechanisms(ecs, &self.build_data.wiring);
+        }
+    }
+}
+
+/// Puts every lever, pressure plate and mechanism on the channel the builder wired its tile to.
+fn wire_mechanisms(ecs : &mut World, wiring : &[(usize, i32)]) {
+    let map = ecs.fetch::<Map>();
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let mut levers = ecs.write_storage::<Lever>();
+    let mut plates = ecs.write_storage::<PressurePlate>();
+    let mut mechanisms = ecs.write_storage::<Mechanism>();
+
+    for (entity, pos) in (&entities, &positions).join() {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if let Some((_, channel)) = wiring.iter().find(|(wired_idx, _)| *wired_idx == idx) {
+            if let Some(lever) = levers.get_mut(entity) { lever.channel = *channel; }
+            if let Some(plate) = plates.get_mut(entity) { plate.channel = *channel; }
+            if let Some(mechanism) = mechanisms.get_mut(entity) { mechanism.channel = *channel; }
+        }
     }
 }
 
@@ -301,14 +330,33 @@ pub fn random_builder(new_depth: i32, rng: &mut RandomNumberGenerator, width: i3
     builder
 }
 
-pub fn level_builder(new_depth: i32, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
-    console::log(format!("Depth: {}", new_depth));
-    match new_depth {
-        1 => town_builder(new_depth, rng, width, height),
-        2 => forest_builder(new_depth, rng, width, height),
-        3 => limestone_cavern_builder(new_depth, rng, width, height),
-        4 => limestone_deep_cavern_builder(new_depth, rng, width, height),
-        5 => limestone_transition_builder(new_depth, rng, width, height),
-        _ => random_builder(new_depth, rng, width, height)
+pub fn level_builder(level: LevelId, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    console::log(format!("Level: {} {}", level.branch.name(), level.depth));
+    let new_depth = level.depth;
+    let mut chain = match level.branch {
+        Branch::Main => match new_depth {
+            1 => town_builder(new_depth, rng, width, height),
+            2 => forest_builder(new_depth, rng, width, height),
+            3 => limestone_cavern_builder(new_depth, rng, width, height),
+            4 => limestone_deep_cavern_builder(new_depth, rng, width, height),
+            5 => limestone_transition_builder(new_depth, rng, width, height),
+            _ => random_builder(new_depth, rng, width, height)
+        }
+        Branch::Mines => mines_builder(new_depth, rng, width, height),
+        Branch::FloodedTemple => flooded_temple_builder(new_depth, rng, width, height),
+        Branch::Crypt => crypt_builder(new_depth, rng, width, height)
+    };
+
+    chain.build_data.map.theme = LevelTheme::roll(level, rng);
+    if chain.build_data.map.theme.is_some() {
+        chain.with(ThemeDecorator::new());
+    }
+
+    if level.deeper().is_none() {
+        chain.with(BranchBottom::new());
+    }
+    for branch in level.branch_entrances() {
+        chain.with(BranchEntrance::new(branch));
     }
+    chain
 }
@@ -90,6 +90,38 @@ impl PrefabBuilder {
                 build_data.starting_position = Some(Position{ x:x as i32, y:y as i32 });
             }
             '>' => build_data.map.tiles[idx] = TileType::DownStairs,
+            '+' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Door".to_string()));
+            }
+            '=' => {
+                build_data.map.tiles[idx] = TileType::Wall;
+                build_data.spawn_list.push((idx, "Secret Door".to_string()));
+            }
+            '-' => {
+                build_data.map.tiles[idx] = TileType::Wall;
+                build_data.spawn_list.push((idx, "Secret Passage".to_string()));
+            }
+            'D' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Locked Door".to_string()));
+            }
+            '|' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Portcullis".to_string()));
+            }
+            'L' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Lever".to_string()));
+            }
+            '_' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Pressure Plate".to_string()));
+            }
+            'B' => {
+                build_data.map.tiles[idx] = TileType::DeepWater;
+                build_data.spawn_list.push((idx, "Drawbridge".to_string()));
+            }
             'g' => {
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data.spawn_list.push((idx, "Goblin".to_string()));
@@ -114,6 +146,18 @@ impl PrefabBuilder {
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data.spawn_list.push((idx, "Health Potion".to_string()));
             }
+            's' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Skeleton".to_string()));
+            }
+            'x' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Dead Thing".to_string()));
+            }
+            'T' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Gravestone".to_string()));
+            }
             '☼' => {
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data.spawn_list.push((idx, "Watch Fire".to_string()));
@@ -226,22 +270,32 @@ impl PrefabBuilder {
         if vault_roll < 4 { return; }
 
         // Note that this is a place-holder and will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
+        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE, HIDDEN_CLOSET, SECRET_CACHE,
+            PORTCULLIS_VAULT, WEIGHTED_VAULT, MOAT_VAULT, LOCKED_VAULT, LAIR_DEN, HOARD_ROOM, FLOODED_SHRINE, OSSUARY];
 
-        // Filter the vault list down to ones that are applicable to the current depth
+        // Filter the vault list down to ones that are applicable to the current depth and theme
         let mut possible_vaults : Vec<&PrefabRoom> = master_vault_list
             .iter()
             .filter(|v| { build_data.map.depth >= v.first_depth && build_data.map.depth <= v.last_depth })
+            .filter(|v| v.theme.is_none() || v.theme == build_data.map.theme)
             .collect();
 
         if possible_vaults.is_empty() { return; } // Bail out if there's nothing to build
 
         let n_vaults = i32::min(rng.roll_dice(1, 3), possible_vaults.len() as i32);
         let mut used_tiles : HashSet<usize> = HashSet::new();
+        let mut keys_needed = 0;
 
         for _i in 0..n_vaults {
 
-            let vault_index = if possible_vaults.len() == 1 { 0 } else { (rng.roll_dice(1, possible_vaults.len() as i32)-1) as usize };
+            // A themed level gets its own vaults before anything else
+            let themed : Vec<usize> = possible_vaults.iter().enumerate()
+                .filter(|(_, v)| v.theme.is_some())
+                .map(|(i, _)| i)
+                .collect();
+            let vault_index = if !themed.is_empty() {
+                themed[(rng.roll_dice(1, themed.len() as i32)-1) as usize]
+            } else if possible_vaults.len() == 1 { 0 } else { (rng.roll_dice(1, possible_vaults.len() as i32)-1) as usize };
             let vault = possible_vaults[vault_index];
 
             // We'll make a list of places in which the vault could fit
@@ -301,11 +355,20 @@ impl PrefabBuilder {
                 });
 
                 let string_vec = PrefabBuilder::read_ascii_to_vec(vault.template);
+                // Everything that signals or moves within a vault shares the same channel
+                let channel = build_data.map.xy_idx(chunk_x, chunk_y) as i32;
                 let mut i = 0;
                 for ty in 0..vault.height {
                     for tx in 0..vault.width {
                         let idx = build_data.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                        if i < string_vec.len() { self.char_to_map(string_vec[i], idx, build_data); }
+                        if i < string_vec.len() {
+                            self.char_to_map(string_vec[i], idx, build_data);
+                            match string_vec[i] {
+                                'L' | '_' | '|' | 'B' => build_data.wiring.push((idx, channel)),
+                                'D' => keys_needed += 1,
+                                _ => {}
+                            }
+                        }
                         used_tiles.insert(idx);
                         i += 1;
                     }
@@ -315,5 +378,19 @@ impl PrefabBuilder {
                 possible_vaults.remove(vault_index);
             }
         }
+
+        // Locked vaults get their key dropped somewhere else on the level
+        if keys_needed > 0 {
+            let spawn_tiles : HashSet<usize> = build_data.spawn_list.iter().map(|s| s.0).collect();
+            let free_tiles : Vec<usize> = build_data.map.tiles.iter().enumerate()
+                .filter(|(idx, tt)| **tt == TileType::Floor && !used_tiles.contains(idx) && !spawn_tiles.contains(idx))
+                .map(|(idx, _)| idx)
+                .collect();
+            for _i in 0..keys_needed {
+                if free_tiles.is_empty() { break; }
+                let key_idx = free_tiles[(rng.roll_dice(1, free_tiles.len() as i32)-1) as usize];
+                build_data.spawn_list.push((key_idx, "Iron Key".to_string()));
+            }
+        }
     }
 }
@@ -1,3 +1,5 @@
+use crate::map::LevelTheme;
+
 #[allow(dead_code)]
 #[derive(PartialEq, Copy, Clone)]
 pub struct PrefabRoom {
@@ -5,7 +7,8 @@ pub struct PrefabRoom {
     pub width : usize,
     pub height: usize,
     pub first_depth: i32,
-    pub last_depth: i32
+    pub last_depth: i32,
+    pub theme: Option<LevelTheme>
 }
 
 #[allow(dead_code)]
@@ -14,7 +17,8 @@ pub const TOTALLY_NOT_A_TRAP : PrefabRoom = PrefabRoom{
     width: 5,
     height: 5,
     first_depth: 0,
-    last_depth: 100
+    last_depth: 100,
+    theme: None
 };
 
 #[allow(dead_code)]
@@ -32,7 +36,8 @@ pub const SILLY_SMILE : PrefabRoom = PrefabRoom{
     width: 6,
     height: 6,
     first_depth: 0,
-    last_depth: 100
+    last_depth: 100,
+    theme: None
 };
 
 #[allow(dead_code)]
@@ -51,7 +56,8 @@ pub const CHECKERBOARD : PrefabRoom = PrefabRoom{
     width: 6,
     height: 6,
     first_depth: 0,
-    last_depth: 100
+    last_depth: 100,
+    theme: None
 };
 
 #[allow(dead_code)]
@@ -63,3 +69,204 @@ const CHECKERBOARD_MAP : &str = "
  ^# # 
       
 ";
+
+#[allow(dead_code)]
+pub const HIDDEN_CLOSET : PrefabRoom = PrefabRoom{
+    template : HIDDEN_CLOSET_MAP,
+    width: 7,
+    height: 6,
+    first_depth: 2,
+    last_depth: 100,
+    theme: None
+};
+
+#[allow(dead_code)]
+const HIDDEN_CLOSET_MAP : &str = "
+       
+ ##### 
+ #!%!# 
+ #   # 
+ ##=## 
+       
+";
+
+#[allow(dead_code)]
+pub const SECRET_CACHE : PrefabRoom = PrefabRoom{
+    template : SECRET_CACHE_MAP,
+    width: 7,
+    height: 7,
+    first_depth: 3,
+    last_depth: 100,
+    theme: None
+};
+
+#[allow(dead_code)]
+const SECRET_CACHE_MAP : &str = "
+       
+ ##-## 
+ #^ ^# 
+ - ! - 
+ #^ ^# 
+ ##-## 
+       
+";
+
+#[allow(dead_code)]
+pub const PORTCULLIS_VAULT : PrefabRoom = PrefabRoom{
+    template : PORTCULLIS_VAULT_MAP,
+    width: 9,
+    height: 6,
+    first_depth: 2,
+    last_depth: 100,
+    theme: None
+};
+
+#[allow(dead_code)]
+const PORTCULLIS_VAULT_MAP : &str = "
+         
+ ####### 
+ #!% %!# 
+ ###|### 
+         
+  L      
+";
+
+#[allow(dead_code)]
+pub const WEIGHTED_VAULT : PrefabRoom = PrefabRoom{
+    template : WEIGHTED_VAULT_MAP,
+    width: 9,
+    height: 7,
+    first_depth: 3,
+    last_depth: 100,
+    theme: None
+};
+
+#[allow(dead_code)]
+const WEIGHTED_VAULT_MAP : &str = "
+         
+ ####### 
+ #%   !# 
+ ###|### 
+         
+    _    
+         
+";
+
+#[allow(dead_code)]
+pub const MOAT_VAULT : PrefabRoom = PrefabRoom{
+    template : MOAT_VAULT_MAP,
+    width: 9,
+    height: 8,
+    first_depth: 4,
+    last_depth: 100,
+    theme: None
+};
+
+#[allow(dead_code)]
+const MOAT_VAULT_MAP : &str = "
+         
+ ≈≈≈≈≈≈≈ 
+ ≈#####≈ 
+ ≈#!%!#≈ 
+ ≈## ##≈ 
+ ≈≈≈B≈≈≈ 
+         
+  L      
+";
+
+#[allow(dead_code)]
+pub const LOCKED_VAULT : PrefabRoom = PrefabRoom{
+    template : LOCKED_VAULT_MAP,
+    width: 7,
+    height: 6,
+    first_depth: 2,
+    last_depth: 100,
+    theme: None
+};
+
+#[allow(dead_code)]
+const LOCKED_VAULT_MAP : &str = "
+       
+ ##### 
+ #!%!# 
+ #   # 
+ ##D## 
+       
+";
+
+#[allow(dead_code)]
+pub const LAIR_DEN : PrefabRoom = PrefabRoom{
+    template : LAIR_DEN_MAP,
+    width: 7,
+    height: 5,
+    first_depth: 3,
+    last_depth: 100,
+    theme: Some(LevelTheme::MonsterLair)
+};
+
+#[allow(dead_code)]
+const LAIR_DEN_MAP : &str = "
+       
+ x o x 
+  oOo  
+ x o x 
+       
+";
+
+#[allow(dead_code)]
+pub const HOARD_ROOM : PrefabRoom = PrefabRoom{
+    template : HOARD_ROOM_MAP,
+    width: 7,
+    height: 6,
+    first_depth: 3,
+    last_depth: 100,
+    theme: Some(LevelTheme::TreasureHoard)
+};
+
+#[allow(dead_code)]
+const HOARD_ROOM_MAP : &str = "
+       
+ ##### 
+ #!%!# 
+ #%^%# 
+ ##=## 
+       
+";
+
+#[allow(dead_code)]
+pub const FLOODED_SHRINE : PrefabRoom = PrefabRoom{
+    template : FLOODED_SHRINE_MAP,
+    width: 7,
+    height: 5,
+    first_depth: 3,
+    last_depth: 100,
+    theme: Some(LevelTheme::Flooded)
+};
+
+#[allow(dead_code)]
+const FLOODED_SHRINE_MAP : &str = "
+       
+ ≈≈≈≈≈ 
+ ≈ ! ≈ 
+ ≈≈ ≈≈ 
+       
+";
+
+#[allow(dead_code)]
+pub const OSSUARY : PrefabRoom = PrefabRoom{
+    template : OSSUARY_MAP,
+    width: 7,
+    height: 5,
+    first_depth: 3,
+    last_depth: 100,
+    theme: Some(LevelTheme::Haunted)
+};
+
+#[allow(dead_code)]
+const OSSUARY_MAP : &str = "
+       
+ T s T 
+ x   x 
+ T s T 
+       
+";
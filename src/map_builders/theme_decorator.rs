@@ -0,0 +1,55 @@
+use super::{MetaMapBuilder, BuilderMap, TileType};
+use bracket_lib::prelude::RandomNumberGenerator;
+use crate::map::LevelTheme;
+use std::collections::HashSet;
+
+/// Dresses a themed level: water for the flooded ones, and a scattering of props for the rest.
+pub struct ThemeDecorator {}
+
+impl MetaMapBuilder for ThemeDecorator {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl ThemeDecorator {
+    #[allow(dead_code)]
+    pub fn new() -> Box<ThemeDecorator> {
+        Box::new(ThemeDecorator{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let theme = match build_data.map.theme {
+            Some(theme) => theme,
+            None => return
+        };
+
+        let start_idx = build_data.starting_position.as_ref().map(|pos| build_data.map.xy_idx(pos.x, pos.y));
+        let taken : HashSet<usize> = build_data.spawn_list.iter().map(|s| s.0).collect();
+        let mut free_tiles : Vec<usize> = build_data.map.tiles.iter().enumerate()
+            .filter(|(idx, tt)| **tt == TileType::Floor && !taken.contains(idx) && Some(*idx) != start_idx)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if theme == LevelTheme::Flooded {
+            for idx in free_tiles.iter() {
+                if rng.roll_dice(1, 2) == 1 {
+                    build_data.map.tiles[*idx] = TileType::ShallowWater;
+                }
+            }
+            build_data.map.outdoors = false;
+        }
+
+        let props = theme.props();
+        if !props.is_empty() {
+            let n_props = 4 + rng.roll_dice(1, 6);
+            for _i in 0..n_props {
+                if free_tiles.is_empty() { break; }
+                let tile = free_tiles.remove((rng.roll_dice(1, free_tiles.len() as i32)-1) as usize);
+                let prop = props[(rng.roll_dice(1, props.len() as i32)-1) as usize];
+                build_data.spawn_list.push((tile, prop.to_string()));
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
@@ -33,6 +33,7 @@ impl WaveformCollapseBuilder {
         let old_map = build_data.map.clone();
 
         build_data.map = Map::new(build_data.map.depth, build_data.width, build_data.height, &build_data.map.name);
+        build_data.map.theme = old_map.theme;
         build_data.spawn_list.clear();
         build_data.rooms = None;
         build_data.corridors = None;
@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use super::{Map, Position, BlocksTile, Pools, spatial};
+use super::{Map, Position, BlocksTile, Pools, EntryTrigger, Hidden, Camouflaged, spatial};
 
 pub struct MapIndexingSystem {}
 
@@ -8,10 +8,13 @@ impl<'a> System<'a> for MapIndexingSystem {
                         ReadStorage<'a, Position>,
                         ReadStorage<'a, BlocksTile>,
                         ReadStorage<'a, Pools>,
-                        Entities<'a>,);
+                        Entities<'a>,
+                        ReadStorage<'a, EntryTrigger>,
+                        ReadStorage<'a, Hidden>,
+                        ReadStorage<'a, Camouflaged>);
 
     fn run(&mut self, data : Self::SystemData) {
-        let (map, position, blockers, pools, entities) = data;
+        let (map, position, blockers, pools, entities, triggers, hidden, camouflaged) = data;
 
         spatial::clear();
         spatial::populate_blocked_from_map(&*map);
@@ -26,6 +29,15 @@ impl<'a> System<'a> for MapIndexingSystem {
                 let idx = map.xy_idx(position.x, position.y);
                 spatial::index_entity(entity, idx, blockers.get(entity).is_some());
             }
+            // Monsters steer clear of any trap that's out in the open, unless the player set it
+            if triggers.get(entity).is_some() && hidden.get(entity).is_none() {
+                let idx = map.xy_idx(position.x, position.y);
+                if camouflaged.get(entity).is_some() {
+                    spatial::index_camouflaged_trap(idx);
+                } else {
+                    spatial::index_known_trap(idx);
+                }
+            }
         }
     }
 }
\ No newline at end of file
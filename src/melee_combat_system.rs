@@ -1,9 +1,13 @@
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 use crate::effects::{add_effect, EffectType, Targets};
-use super::{Attributes, Skills, WantsToMelee, Name, gamelog::GameLog,
+use super::{Attributes, Skills, WantsToMelee, Name, gamelog::{GameLog, LogCategory},
     HungerClock, HungerState, Pools, skill_bonus,
-    Skill, Equipped, MeleeWeapon, EquipmentSlot, WeaponAttribute, Wearable, NaturalAttackDefense};
+    Skill, Equipped, MeleeWeapon, EquipmentSlot, WeaponAttribute, Wearable, NaturalAttackDefense, Chasing, Stealth,
+    Perception, Behavior, AIState, Viewshed, Position};
+
+/// How much easier it is to land a blow on someone who doesn't see it coming.
+const SNEAK_HIT_BONUS : i32 = 4;
 
 pub struct MeleeCombatSystem {}
 
@@ -21,12 +25,19 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         ReadStorage<'a, Equipped>,
                         ReadStorage<'a, MeleeWeapon>,
                         ReadStorage<'a, Wearable>,
-                        ReadStorage<'a, NaturalAttackDefense>
+                        ReadStorage<'a, NaturalAttackDefense>,
+                        ReadStorage<'a, Chasing>,
+                        ReadStorage<'a, Stealth>,
+                        ReadStorage<'a, Perception>,
+                        ReadStorage<'a, Behavior>,
+                        ReadStorage<'a, Viewshed>,
+                        ReadStorage<'a, Position>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (entities, mut log, mut wants_melee, names, attributes, skills,
-            hunger_clock, pools, mut rng, equipped_items, meleeweapons, wearables, natural) = data;
+            hunger_clock, pools, mut rng, equipped_items, meleeweapons, wearables, natural, chasing, stealth,
+            perception, behaviors, viewsheds, positions) = data;
         for (entity, wants_melee, name, attacker_attributes, attacker_skills, attacker_pools) in (&entities, &wants_melee, &names, &attributes, &skills, &pools).join() {
             // Are the attacker and defender alive? Only attack if they are
             let target_pools = pools.get(wants_melee.target).unwrap();
@@ -77,6 +88,30 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         status_hit_bonus += 1;
                     }
                 }
+
+                // A target that is asleep, can't see us or hasn't taken us for a threat can be
+                // caught off guard, if we move quietly enough
+                let target_aware = match behaviors.get(wants_melee.target) {
+                    Some(behavior) if behavior.state == AIState::Asleep => false,
+                    behavior => {
+                        let sees_us = match (viewsheds.get(wants_melee.target), positions.get(entity)) {
+                            (Some(viewshed), Some(pos)) => viewshed.visible_tiles.contains(&Point::new(pos.x, pos.y)),
+                            _ => true
+                        };
+                        let hostile = chasing.get(wants_melee.target).is_some_and(|chase| chase.target == entity)
+                            || behavior.is_none_or(|b| matches!(b.state, AIState::Hunting | AIState::Fleeing));
+                        sees_us && hostile
+                    }
+                };
+                let sneak_attack = match stealth.get(entity) {
+                    Some(stealth) if !target_aware => {
+                        let target_perception = perception.get(wants_melee.target).map_or(0, |p| p.value);
+                        rng.roll_dice(1, 20) + stealth.value > 10 + target_perception
+                    }
+                    _ => false
+                };
+                if sneak_attack { status_hit_bonus += SNEAK_HIT_BONUS; }
+
                 let modified_hit_roll = natural_roll + attribute_hit_bonus + skill_hit_bonus
                     + weapon_hit_bonus + status_hit_bonus;
 
@@ -96,6 +131,9 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 let armor_class = base_armor_class + armor_quickness_bonus + armor_skill_bonus
                     + armor_item_bonus;
 
+                // Fighting makes a racket
+                add_effect(Some(entity), EffectType::Noise{ radius : 6 }, Targets::Single{ target : entity });
+
                 if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
                     // Target hit! Until we support weapons, we're going with 1d4
                     let base_damage = rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
@@ -103,15 +141,24 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
                     let weapon_damage_bonus = weapon_info.damage_bonus;
 
-                    let damage = i32::max(0, base_damage + attr_damage_bonus + skill_hit_bonus +
+                    let mut damage = i32::max(0, base_damage + attr_damage_bonus + skill_hit_bonus +
                         skill_damage_bonus + weapon_damage_bonus);
+                    if sneak_attack { damage *= 2; }
                     add_effect(
                         Some(entity),
                         EffectType::Damage{ amount: damage },
                         Targets::Single{ target: wants_melee.target }
                     );
 
-                    log.entries.push(format!("{} hits {}, for {} hp.", &name.name, &target_name.name, damage));
+                    if sneak_attack {
+                        log.line(LogCategory::Combat).colored(&name.name, RGB::named(YELLOW)).append(" sneak attacks ")
+                            .colored(&target_name.name, RGB::named(YELLOW)).append(", for ")
+                            .colored(format!("{} hp", damage), RGB::named(RED)).append("!").log();
+                    } else {
+                        log.line(LogCategory::Combat).colored(&name.name, RGB::named(YELLOW)).append(" hits ")
+                            .colored(&target_name.name, RGB::named(YELLOW)).append(", for ")
+                            .colored(format!("{} hp", damage), RGB::named(RED)).append(".").log();
+                    }
 
                     // Proc effects
                     if let Some(chance) = &weapon_info.proc_chance {
@@ -130,7 +177,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     }
                 } else  if natural_roll == 1 {
                     // Natural 1 miss
-                    log.entries.push(format!("{} considers attacking {}, but misjudges the timing.", name.name, target_name.name));
+                    log.log(LogCategory::Combat, format!("{} considers attacking {}, but misjudges the timing.", name.name, target_name.name));
                     add_effect(
                         None,
                         EffectType::Particle{ glyph: to_cp437('‼'), fg: RGB::named(BLUE), bg : RGB::named(BLACK), lifespan: 200.0 },
@@ -138,7 +185,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     );
                 } else {
                     // Miss
-                    log.entries.push(format!("{} attacks {}, but can't connect.", name.name, target_name.name));
+                    log.log(LogCategory::Combat, format!("{} attacks {}, but can't connect.", name.name, target_name.name));
                     add_effect(
                         None,
                         EffectType::Particle{ glyph: to_cp437('‼'), fg: RGB::named(CYAN), bg : RGB::named(BLACK), lifespan: 200.0 },
@@ -1,7 +1,8 @@
 #![allow(deprecated)]
 use specs::prelude::*;
 use super::{Map, Position, BlocksTile, ApplyMove, ApplyTeleport, OtherLevelPosition, EntityMoved,
-    Viewshed, RunState};
+    Viewshed, RunState, StatusEffect, Entangled};
+use std::collections::HashSet;
 
 pub struct MovementSystem {}
 
@@ -17,20 +18,28 @@ impl<'a> System<'a> for MovementSystem {
                         WriteStorage<'a, EntityMoved>,
                         WriteStorage<'a, Viewshed>,
                         ReadExpect<'a, Entity>,
-                        WriteExpect<'a, RunState>);
+                        WriteExpect<'a, RunState>,
+                        ReadStorage<'a, StatusEffect>,
+                        ReadStorage<'a, Entangled>);
 
     fn run(&mut self, data : Self::SystemData) {
         let (map, mut position, _blockers, entities, mut apply_move,
             mut apply_teleport, mut other_level, mut moved,
-            mut viewsheds, player_entity, mut runstate) = data;
+            mut viewsheds, player_entity, mut runstate, statuses, entangled) = data;
+
+        // Anyone caught in a net isn't walking anywhere, though magic can still whisk them away
+        let mut stuck : HashSet<Entity> = (&statuses, &entangled).join()
+            .map(|(status, _)| status.target)
+            .collect();
 
         // Apply teleports
         for (entity, teleport) in (&entities, &apply_teleport).join() {
-            if teleport.dest_depth == map.depth {
+            if teleport.dest_level == map.level_id() {
+                stuck.remove(&entity);
                 apply_move.insert(entity, ApplyMove{ dest_idx: map.xy_idx(teleport.dest_x, teleport.dest_y) })
                     .expect("Unable to insert");
             } else if entity == *player_entity {
-                *runstate = RunState::TeleportingToOtherLevel{ x: teleport.dest_x, y: teleport.dest_y, depth: teleport.dest_depth };
+                *runstate = RunState::TeleportingToOtherLevel{ x: teleport.dest_x, y: teleport.dest_y, level: teleport.dest_level };
             } else if let Some(pos) = position.get(entity) {
                 let idx = map.xy_idx(pos.x, pos.y);
                 let dest_idx = map.xy_idx(teleport.dest_x, teleport.dest_y);
@@ -38,7 +47,7 @@ impl<'a> System<'a> for MovementSystem {
                 other_level.insert(entity, OtherLevelPosition{
                     x: teleport.dest_x,
                     y: teleport.dest_y,
-                    depth: teleport.dest_depth })
+                    level: teleport.dest_level })
                     .expect("Unable to insert");
                 position.remove(entity);
             }
@@ -47,6 +56,7 @@ impl<'a> System<'a> for MovementSystem {
 
         // Apply broad movement
         for (entity, movement, mut pos) in (&entities, &apply_move, &mut position).join() {
+            if stuck.contains(&entity) { continue; }
             let start_idx = map.xy_idx(pos.x, pos.y);
             let dest_idx = movement.dest_idx as usize;
             crate::spatial::move_entity(entity, start_idx, dest_idx);
@@ -2,12 +2,22 @@ use bracket_lib::prelude::{VirtualKeyCode, Point, to_cp437, BTerm, RandomNumberG
 use specs::prelude::*;
 use std::cmp::{max, min};
 use crate::WantsToCastSpell;
+use crate::effects::{add_effect, aoe_tiles, EffectType, Targets};
 use super::{Position, Player, Viewshed, State, Map, RunState, Attributes, WantsToMelee, Item,
-    gamelog::GameLog, WantsToPickupItem, TileType, HungerClock, HungerState,
+    gamelog::{GameLog, LogCategory}, WantsToPickupItem, TileType, HungerClock, HungerState,
     EntityMoved, Door, BlocksTile, BlocksVisibility, Renderable, Pools, Faction,
-    raws::Reaction, Vendor, VendorMode};
+    raws::Reaction, Vendor, VendorMode, Equipped, DiggingTool, Hidden, Searching, Lock, Key, InBackpack,
+    Portcullis, Lever, Name, EquipmentChanged, StatusEffect, Entangled, Disarmable, AreaOfEffect,
+    Skills, Skill, skill_bonus, keybindings::{KEYBINDINGS, Action}, Travelling, Resting, camera};
+
+const SEARCH_TURNS : i32 = 5;
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState {
+    if is_entangled(ecs) {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::Status, "You struggle against the net.");
+        return RunState::Ticking;
+    }
+
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.read_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -23,6 +33,15 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let factions = ecs.read_storage::<Faction>();
     let mut result = RunState::AwaitingInput;
     let vendors = ecs.read_storage::<Vendor>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let digging_tools = ecs.read_storage::<DiggingTool>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let mut locks = ecs.write_storage::<Lock>();
+    let keys = ecs.read_storage::<Key>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let portcullises = ecs.read_storage::<Portcullis>();
+    let mut levers = ecs.write_storage::<Lever>();
+    let names = ecs.read_storage::<Name>();
 
     let mut swap_entities : Vec<(Entity, i32, i32)> = Vec::new();
 
@@ -66,19 +85,61 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                     return Some(RunState::Ticking);
                 }
             }
+            if let Some(lever) = levers.get_mut(potential_target) {
+                lever.pulled = !lever.pulled;
+                if let Some(glyph) = renderables.get_mut(potential_target) {
+                    glyph.glyph = to_cp437(if lever.pulled { '\\' } else { '/' });
+                }
+                ecs.fetch_mut::<GameLog>().log(LogCategory::World, "You pull the lever.");
+                add_effect(Some(entity), EffectType::Signal{ channel : lever.channel }, Targets::Tile{ tile_idx : destination_idx as i32 });
+                add_effect(Some(entity), EffectType::Noise{ radius : 4 }, Targets::Tile{ tile_idx : destination_idx as i32 });
+                return Some(RunState::Ticking);
+            }
+            if blocks_movement.get(potential_target).is_some() {
+                if portcullises.get(potential_target).is_some() {
+                    ecs.fetch_mut::<GameLog>().log(LogCategory::World, "The portcullis won't budge.");
+                    return Some(RunState::AwaitingInput);
+                }
+                if let Some(lock) = locks.get(potential_target) {
+                    let key = (&entities, &backpack, &keys).join()
+                        .find(|(_, pack, key)| pack.owner == entity && key.id == lock.key)
+                        .map(|(key_entity, _, _)| key_entity);
+                    if let Some(key) = key {
+                        let key_name = names.get(key).map(|n| n.name.clone()).unwrap_or_else(|| "key".to_string());
+                        ecs.fetch_mut::<GameLog>().log(LogCategory::World, format!("You unlock the door with the {}.", key_name));
+                        entities.delete(key).expect("Delete failed");
+                        ecs.write_storage::<EquipmentChanged>().insert(entity, EquipmentChanged{}).expect("Unable to insert");
+                        locks.remove(potential_target);
+                    } else {
+                        ecs.fetch_mut::<GameLog>().log(LogCategory::World, "The door is locked.");
+                        return Some(RunState::AwaitingInput);
+                    }
+                }
+            }
             let door = doors.get_mut(potential_target);
-            if let Some(door) = door {
+            if let (Some(door), None) = (door, hidden.get(potential_target)) {
                 door.open = true;
                 blocks_visibility.remove(potential_target);
                 blocks_movement.remove(potential_target);
                 let glyph = renderables.get_mut(potential_target).unwrap();
                 glyph.glyph = to_cp437('/');
                 viewshed.dirty = true;
+                add_effect(Some(entity), EffectType::Noise{ radius : 4 }, Targets::Tile{ tile_idx : destination_idx as i32 });
                 return Some(RunState::Ticking);
             }
             None
         });
 
+        // Bumping into rock with something to dig with tunnels into it
+        if result == RunState::AwaitingInput && map.diggable(destination_idx) &&
+            (&equipped, &digging_tools).join().any(|(eq, _)| eq.owner == entity)
+        {
+            add_effect(Some(entity), EffectType::DestroyTerrain, Targets::Tile{ tile_idx : destination_idx as i32 });
+            add_effect(Some(entity), EffectType::Noise{ radius : 6 }, Targets::Tile{ tile_idx : destination_idx as i32 });
+            ecs.fetch_mut::<GameLog>().log(LogCategory::World, "You dig through the rock.");
+            result = RunState::Ticking;
+        }
+
         if !crate::spatial::is_blocked(destination_idx) {
             let old_idx = map.xy_idx(pos.x, pos.y);
             pos.x = min(map.width-1 , max(0, pos.x + delta_x));
@@ -123,7 +184,7 @@ pub fn try_next_level(ecs: &mut World) -> bool {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
-        gamelog.entries.push("There is no way down from here.".to_string());
+        gamelog.log(LogCategory::System, "There is no way down from here.");
         false
     }
 }
@@ -136,12 +197,12 @@ pub fn try_previous_level(ecs: &mut World) -> bool {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
-        gamelog.entries.push("There is no way up from here.".to_string());
+        gamelog.log(LogCategory::System, "There is no way up from here.");
         false
     }
 }
 
-fn get_item(ecs: &mut World) {
+pub fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
     let entities = ecs.entities();
@@ -157,7 +218,7 @@ fn get_item(ecs: &mut World) {
     }
 
     match target_item {
-        None => gamelog.entries.push("There is nothing here to pick up.".to_string()),
+        None => gamelog.log(LogCategory::Loot, "There is nothing here to pick up."),
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickupItem>();
             pickup.insert(*player_entity, WantsToPickupItem{ collected_by: *player_entity, item }).expect("Unable to insert want to pickup");
@@ -165,14 +226,82 @@ fn get_item(ecs: &mut World) {
     }
 }
 
-fn skip_turn(ecs: &mut World) -> RunState {
+/// Something the player can see lying at a spot.
+pub fn item_at(ecs: &World, target: Point) -> Option<Entity> {
+    let map = ecs.fetch::<Map>();
+    if !map.visible_tiles[map.xy_idx(target.x, target.y)] { return None; }
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    (&entities, &items, &positions, !&hidden).join()
+        .find(|(_, _, pos, _)| pos.x == target.x && pos.y == target.y)
+        .map(|(entity, _, _, _)| entity)
+}
+
+/// Something the player can see at a spot that would attack them.
+pub fn hostile_at(ecs: &World, target: Point) -> Option<Entity> {
+    let map = ecs.fetch::<Map>();
+    let idx = map.xy_idx(target.x, target.y);
+    if !map.visible_tiles[idx] { return None; }
+    let factions = ecs.read_storage::<Faction>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut hostile = None;
+    crate::spatial::for_each_tile_content(idx, |entity_id| {
+        if hidden.get(entity_id).is_some() { return; }
+        if let Some(faction) = factions.get(entity_id) {
+            let reaction = crate::raws::faction_reaction(&faction.name, "Player", &crate::raws::RAWS.lock().unwrap());
+            if reaction == Reaction::Attack {
+                hostile = Some(entity_id);
+            }
+        }
+    });
+    hostile
+}
+
+/// Picks up whatever is at a spot, walking over to it first if need be.
+pub fn pick_up_at(ecs: &mut World, target: Point) -> RunState {
+    if *ecs.fetch::<Point>() == target {
+        get_item(ecs);
+        RunState::Ticking
+    } else {
+        crate::travel::travel_to(ecs, target, true)
+    }
+}
+
+/// A left click on the map: fight whatever hostile is next to the player there, pick up what
+/// is lying there, or otherwise walk over.
+fn click_map(ecs: &mut World, target: Point) -> RunState {
+    let player_pos = *ecs.fetch::<Point>();
+    let (delta_x, delta_y) = (target.x - player_pos.x, target.y - player_pos.y);
+    if delta_x.abs() <= 1 && delta_y.abs() <= 1 && hostile_at(ecs, target).is_some() {
+        return try_move_player(delta_x, delta_y, ecs);
+    }
+    if item_at(ecs, target).is_some() {
+        return pick_up_at(ecs, target);
+    }
+    if target == player_pos {
+        return RunState::AwaitingInput;
+    }
+    crate::travel::travel_to(ecs, target, false)
+}
+
+fn is_entangled(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let entangled = ecs.read_storage::<Entangled>();
+    (&statuses, &entangled).join().any(|(status, _)| status.target == *player_entity)
+}
+
+pub fn hostile_in_view(ecs: &World) -> bool {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let factions = ecs.read_storage::<Faction>();
 
     let worldmap_resource = ecs.fetch::<Map>();
 
-    let mut can_heal = true;
+    let mut hostile = false;
     let viewshed = viewshed_components.get(*player_entity).unwrap();
     for tile in viewshed.visible_tiles.iter() {
         let idx = worldmap_resource.xy_idx(tile.x, tile.y);
@@ -187,12 +316,18 @@ fn skip_turn(ecs: &mut World) -> RunState {
                         &crate::raws::RAWS.lock().unwrap()
                     );
                     if reaction == Reaction::Attack {
-                        can_heal = false;
+                        hostile = true;
                     }
                 }
             }
         });
     }
+    hostile
+}
+
+fn skip_turn(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut can_heal = !hostile_in_view(ecs);
 
     let hunger_clocks = ecs.read_storage::<HungerClock>();
     let hc = hunger_clocks.get(*player_entity);
@@ -217,6 +352,165 @@ fn skip_turn(ecs: &mut World) -> RunState {
     RunState::Ticking
 }
 
+/// Rests until healed, or waits out a number of turns.
+pub fn rest(ecs: &mut World, turns: Option<i32>) -> RunState {
+    if hostile_in_view(ecs) {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::System, "Not with enemies in view!");
+        return RunState::AwaitingInput;
+    }
+    if turns.is_none() {
+        let hungry = ecs.read_storage::<HungerClock>().get(*ecs.fetch::<Entity>())
+            .is_some_and(|h| h.state == HungerState::Hungry || h.state == HungerState::Starving);
+        if hungry {
+            ecs.fetch_mut::<GameLog>().log(LogCategory::System, "You are too hungry to rest.");
+            return RunState::AwaitingInput;
+        }
+        if fully_rested(ecs) {
+            ecs.fetch_mut::<GameLog>().log(LogCategory::System, "You are already fully rested.");
+            return RunState::AwaitingInput;
+        }
+    }
+    let player_entity = *ecs.fetch::<Entity>();
+    let baseline = crate::travel::baseline(ecs);
+    ecs.write_storage::<Resting>().insert(player_entity, Resting{ turns_left : turns, turns_rested : 0, baseline })
+        .expect("Unable to insert");
+    keep_resting(ecs).unwrap_or(RunState::AwaitingInput)
+}
+
+fn fully_rested(ecs: &World) -> bool {
+    let pools = ecs.read_storage::<Pools>();
+    pools.get(*ecs.fetch::<Entity>())
+        .is_none_or(|p| p.hit_points.current >= p.hit_points.max && p.mana.current >= p.mana.max)
+}
+
+/// Stops resting, saying why if something got in the way, and how long it lasted.
+pub fn stop_resting(ecs: &mut World, reason: Option<&str>) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let resting = ecs.write_storage::<Resting>().remove(player_entity);
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    if let Some(reason) = reason {
+        gamelog.log(LogCategory::World, format!("You stop: {}.", reason));
+    }
+    if let Some(resting) = resting {
+        if resting.turns_rested > 0 {
+            let verb = if resting.turns_left.is_none() { "rest" } else { "wait" };
+            let plural = if resting.turns_rested == 1 { "" } else { "s" };
+            gamelog.log(LogCategory::World, format!("You {} for {} turn{}.", verb, resting.turns_rested, plural));
+        }
+    }
+}
+
+fn keep_resting(ecs: &mut World) -> Option<RunState> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut resting = ecs.read_storage::<Resting>().get(player_entity).cloned()?;
+
+    let now = crate::travel::baseline(ecs);
+    let reason = if hostile_in_view(ecs) {
+        Some("there are enemies in view")
+    } else {
+        crate::travel::interruption(&resting.baseline, &now)
+    };
+    let done = match resting.turns_left {
+        Some(turns) => turns < 1,
+        None => fully_rested(ecs)
+    };
+    if reason.is_some() || done {
+        stop_resting(ecs, reason);
+        return None;
+    }
+
+    resting.turns_rested += 1;
+    resting.turns_left = resting.turns_left.map(|t| t - 1);
+    resting.baseline = now;
+    ecs.write_storage::<Resting>().insert(player_entity, resting).expect("Unable to insert");
+    Some(skip_turn(ecs))
+}
+
+fn search(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.write_storage::<Searching>().insert(player_entity, Searching{ turns : SEARCH_TURNS }).expect("Unable to insert");
+    ecs.fetch_mut::<GameLog>().log(LogCategory::World, "You search your surroundings.");
+    keep_searching(ecs).unwrap_or(RunState::AwaitingInput)
+}
+
+/// Spends another turn searching, if there's any searching left to do and nothing is coming for us.
+fn keep_searching(ecs: &mut World) -> Option<RunState> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let hostile = hostile_in_view(ecs);
+    let mut searching = ecs.write_storage::<Searching>();
+    let turns_left = searching.get(player_entity).map(|s| s.turns).unwrap_or(0);
+
+    if hostile || turns_left < 1 {
+        searching.remove(player_entity);
+        if hostile {
+            ecs.fetch_mut::<GameLog>().log(LogCategory::World, "You stop searching.");
+        }
+        return None;
+    }
+
+    if let Some(search) = searching.get_mut(player_entity) {
+        search.turns -= 1;
+    }
+    if let Some(viewshed) = ecs.write_storage::<Viewshed>().get_mut(player_entity) {
+        viewshed.dirty = true;
+    }
+    Some(RunState::Ticking)
+}
+
+/// Has a go at disarming a trap we know about on or next to our tile. Fumbling it sets it off.
+fn disarm_trap(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+
+    let mut trap : Option<(Entity, usize)> = None;
+    {
+        let map = ecs.fetch::<Map>();
+        let disarmable = ecs.read_storage::<Disarmable>();
+        let hidden = ecs.read_storage::<Hidden>();
+        for y in player_pos.y-1 ..= player_pos.y+1 {
+            for x in player_pos.x-1 ..= player_pos.x+1 {
+                if x < 0 || x > map.width-1 || y < 0 || y > map.height-1 { continue; }
+                let idx = map.xy_idx(x, y);
+                crate::spatial::for_each_tile_content(idx, |e| {
+                    if disarmable.get(e).is_some() && hidden.get(e).is_none() { trap = Some((e, idx)); }
+                });
+            }
+        }
+    }
+    let (trap, trap_idx) = if let Some(trap) = trap { trap } else {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::World, "There are no traps nearby that you know of.");
+        return RunState::AwaitingInput;
+    };
+
+    // A steady hand and a sharp eye
+    let difficulty = ecs.read_storage::<Disarmable>().get(trap).unwrap().difficulty;
+    let mut skill = 0;
+    if let Some(attr) = ecs.read_storage::<Attributes>().get(player_entity) {
+        skill += attr.quickness.bonus;
+    }
+    if let Some(skills) = ecs.read_storage::<Skills>().get(player_entity) {
+        skill += skill_bonus(Skill::Perception, skills);
+    }
+    let roll = ecs.fetch_mut::<RandomNumberGenerator>().roll_dice(1, 20) + skill;
+    let trap_name = ecs.read_storage::<Name>().get(trap).map(|n| n.name.clone()).unwrap_or_else(|| "trap".to_string());
+
+    if roll >= difficulty {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::World, format!("You disarm the {}.", trap_name));
+        ecs.entities().delete(trap).expect("Delete failed");
+    } else {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::World, format!("You fumble with the {}, and it goes off!", trap_name));
+        let targets = if let Some(aoe) = ecs.read_storage::<AreaOfEffect>().get(trap) {
+            let map = ecs.fetch::<Map>();
+            let trap_pos = Point::new(trap_idx as i32 % map.width, trap_idx as i32 / map.width);
+            Targets::Tiles{ tiles : aoe_tiles(&map, trap_pos, aoe.radius) }
+        } else {
+            Targets::Single{ target : player_entity }
+        };
+        add_effect(Some(player_entity), EffectType::TriggerFire{ trigger : trap }, targets);
+    }
+    RunState::Ticking
+}
+
 fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
     use super::{Consumable, InBackpack, WantsToUseItem};
 
@@ -272,7 +566,7 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
             }
         } else {
             let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
-            gamelog.entries.push("You don't have enough mana to cast that!".to_string());
+            gamelog.log(LogCategory::Status, "You don't have enough mana to cast that!");
         }
     }
 
@@ -280,6 +574,46 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut BTerm) -> RunState {
+    // A search in progress carries on by itself
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    if gs.ecs.read_storage::<Searching>().get(player_entity).is_some() {
+        if let Some(result) = keep_searching(&mut gs.ecs) {
+            return result;
+        }
+    }
+
+    // So does a journey, until the player presses something
+    if gs.ecs.read_storage::<Travelling>().get(player_entity).is_some() {
+        if ctx.key.is_some() || camera::left_clicked(ctx) {
+            crate::travel::stop_travelling(&mut gs.ecs);
+            return RunState::AwaitingInput;
+        }
+        if let Some(result) = crate::travel::keep_travelling(&mut gs.ecs) {
+            return result;
+        }
+    }
+
+    // And so does resting
+    if gs.ecs.read_storage::<Resting>().get(player_entity).is_some() {
+        if ctx.key.is_some() || camera::left_clicked(ctx) {
+            stop_resting(&mut gs.ecs, None);
+            return RunState::AwaitingInput;
+        }
+        if let Some(result) = keep_resting(&mut gs.ecs) {
+            return result;
+        }
+    }
+
+    // Left click walks, fights or picks up; right click offers everything else
+    if let Some(target) = camera::mouse_map_pos(&gs.ecs, ctx) {
+        if camera::right_clicked() {
+            return RunState::ContextMenu{ x: target.x, y: target.y };
+        }
+        if camera::left_clicked(ctx) {
+            return click_map(&mut gs.ecs, target);
+        }
+    }
+
     // Hotkeys
     if ctx.shift && ctx.key.is_some() {
         let key : Option<i32> =
@@ -319,68 +653,65 @@ pub fn player_input(gs: &mut State, ctx: &mut BTerm) -> RunState {
         }
     }
 
-    // Player movement
-    match ctx.key {
-        None => { return RunState::AwaitingInput } // Nothing happened
-        Some(key) => match key {
-            VirtualKeyCode::Left |
-            VirtualKeyCode::Numpad4 |
-            VirtualKeyCode::H => return try_move_player(-1, 0, &mut gs.ecs),
-
-            VirtualKeyCode::Right |
-            VirtualKeyCode::Numpad6 |
-            VirtualKeyCode::L => return try_move_player(1, 0, &mut gs.ecs),
-
-            VirtualKeyCode::Up |
-            VirtualKeyCode::Numpad8 |
-            VirtualKeyCode::K => return try_move_player(0, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Down |
-            VirtualKeyCode::Numpad2 |
-            VirtualKeyCode::J => return try_move_player(0, 1, &mut gs.ecs),
+    // Everything else goes through the key bindings
+    let action = match ctx.key {
+        None => return RunState::AwaitingInput, // Nothing happened
+        Some(key) => KEYBINDINGS.lock().unwrap().action_for(key)
+    };
+    match action {
+        None => return RunState::AwaitingInput,
+        Some(action) => match action {
+            Action::MoveWest => return try_move_player(-1, 0, &mut gs.ecs),
+            Action::MoveEast => return try_move_player(1, 0, &mut gs.ecs),
+            Action::MoveNorth => return try_move_player(0, -1, &mut gs.ecs),
+            Action::MoveSouth => return try_move_player(0, 1, &mut gs.ecs),
 
             // Diagonals
-            VirtualKeyCode::Numpad9 |
-            VirtualKeyCode::U => return try_move_player(1, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad7 |
-            VirtualKeyCode::Y => return try_move_player(-1, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad3 |
-            VirtualKeyCode::N => return try_move_player(1, 1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad1 |
-            VirtualKeyCode::B => return try_move_player(-1, 1, &mut gs.ecs),
+            Action::MoveNorthEast => return try_move_player(1, -1, &mut gs.ecs),
+            Action::MoveNorthWest => return try_move_player(-1, -1, &mut gs.ecs),
+            Action::MoveSouthEast => return try_move_player(1, 1, &mut gs.ecs),
+            Action::MoveSouthWest => return try_move_player(-1, 1, &mut gs.ecs),
 
             // Skip Turn
-            VirtualKeyCode::Numpad5 |
-            VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
+            Action::Wait => return skip_turn(&mut gs.ecs),
+            Action::Search => return search(&mut gs.ecs),
+            Action::Disarm => return disarm_trap(&mut gs.ecs),
+            Action::Rest => return rest(&mut gs.ecs, None),
+            Action::WaitTurns => return RunState::WaitPrompt{ turns: 0 },
+            Action::Explore => return crate::travel::explore(&mut gs.ecs),
+            Action::TravelDown => return crate::travel::travel_to_stairs(&mut gs.ecs, true),
+            Action::TravelUp => return crate::travel::travel_to_stairs(&mut gs.ecs, false),
 
             // Level changes
-            VirtualKeyCode::Period => {
+            Action::Descend => {
                 if try_next_level(&mut gs.ecs) {
                     return RunState::NextLevel;
                 }
             }
-            VirtualKeyCode::Comma => {
+            Action::Ascend => {
                 if try_previous_level(&mut gs.ecs) {
                     return RunState::PreviousLevel;
                 }
             }
+            Action::DungeonOverview => return RunState::ShowDungeonOverview,
+            Action::MessageLog => return RunState::ShowMessageLog{ scroll: 0, filter: None },
+            Action::Look => return RunState::LookMode{ index: 0 },
+            Action::CharacterSheet => return RunState::ShowCharacterSheet,
+            Action::Help => return RunState::ShowHelp,
+            Action::LevelMap => return RunState::ShowLevelMap,
 
             // Picking up items
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            VirtualKeyCode::D => return RunState::ShowDropItem,
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            Action::PickUp => get_item(&mut gs.ecs),
+            Action::Inventory => return RunState::ShowInventory,
+            Action::Drop => return RunState::ShowDropItem,
+            Action::Remove => return RunState::ShowRemoveItem,
+            Action::Equipment => return RunState::ShowEquipment{ slot: 0 },
 
             // Save and Quit
-            VirtualKeyCode::Escape => return RunState::SaveGame,
+            Action::SaveAndQuit => return RunState::SaveGame,
 
             // Cheating!
-            VirtualKeyCode::Backslash => return RunState::ShowCheatMenu,
-
-            _ => { return RunState::AwaitingInput }
+            Action::Cheat => return RunState::ShowCheatMenu
         },
     }
     RunState::Ticking
@@ -0,0 +1,56 @@
+use specs::prelude::*;
+use crate::{Map, PressurePlate, Position, Pools, Item, Flying, Levitation, StatusEffect, Name, gamelog::{GameLog, LogCategory}};
+use crate::effects::{add_effect, EffectType, Targets};
+
+/// What a creature weighs before counting whatever they're carrying
+const CREATURE_WEIGHT : f32 = 50.0;
+
+pub struct PressurePlateSystem {}
+
+impl<'a> System<'a> for PressurePlateSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+                        Entities<'a>,
+                        ReadExpect<'a, Map>,
+                        WriteExpect<'a, GameLog>,
+                        WriteStorage<'a, PressurePlate>,
+                        ReadStorage<'a, Position>,
+                        ReadStorage<'a, Pools>,
+                        ReadStorage<'a, Item>,
+                        ReadStorage<'a, Flying>,
+                        ReadStorage<'a, Levitation>,
+                        ReadStorage<'a, StatusEffect>,
+                        ReadStorage<'a, Name>
+                      );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (entities, map, mut log, mut plates, positions, pools, items, flying, levitation,
+            statuses, names) = data;
+
+        for (plate_entity, plate, pos) in (&entities, &mut plates, &positions).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            let mut load = 0.0;
+            crate::spatial::for_each_tile_content(idx, |e| {
+                if let Some(pool) = pools.get(e) {
+                    // Anything in the air doesn't press down on anything
+                    let airborne = flying.get(e).is_some() ||
+                        (&statuses, &levitation).join().any(|(status, _)| status.target == e);
+                    if !airborne { load += CREATURE_WEIGHT + pool.total_weight; }
+                } else if let Some(item) = items.get(e) {
+                    load += item.weight_lbs;
+                }
+            });
+
+            let pressed = load >= plate.min_weight;
+            if pressed != plate.pressed {
+                plate.pressed = pressed;
+                add_effect(None, EffectType::Signal{ channel : plate.channel }, Targets::Tile{ tile_idx : idx as i32 });
+                if map.visible_tiles[idx] {
+                    if let Some(name) = names.get(plate_entity) {
+                        log.log(LogCategory::World, format!("The {} clicks {}.", name.name, if pressed { "down" } else { "up" }));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -4,7 +4,8 @@ use std::collections::HashMap;
 #[derive(Deserialize, Debug)]
 pub struct FactionInfo {
     pub name : String,
-    pub responses : HashMap<String, String>
+    pub responses : HashMap<String, String>,
+    pub pack_tactics : Option<bool>
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
@@ -13,7 +13,11 @@ pub struct Item {
     pub base_value : Option<f32>,
     pub vendor_category : Option<String>,
     pub magic : Option<MagicItem>,
-    pub attributes : Option<ItemAttributeBonus>
+    pub attributes : Option<ItemAttributeBonus>,
+    pub digging : Option<bool>,
+    pub key : Option<String>,
+    pub throwable : Option<bool>,
+    pub description : Option<String>
 }
 
 #[derive(Deserialize, Debug)]
@@ -9,6 +9,7 @@ pub struct Mob {
     pub blocks_tile : bool,
     pub vision_range : i32,
     pub movement : String,
+    pub ai_state : Option<String>,
     pub quips : Option<Vec<String>>,
     pub attributes : MobAttributes,
     pub skills : Option<HashMap<String, i32>>,
@@ -23,6 +24,12 @@ pub struct Mob {
     pub gold : Option<String>,
     pub vendor : Option<Vec<String>>,
     pub abilities : Option<Vec<MobAbility>>,
+    pub pack_leader : Option<bool>,
+    pub item_use : Option<Vec<String>>,
+    pub swimmer : Option<bool>,
+    pub flying : Option<bool>,
+    pub tunnels : Option<bool>,
+    pub description : Option<String>
 }
 
 #[derive(Deserialize, Debug)]
@@ -7,11 +7,19 @@ pub struct Prop {
     pub name : String,
     pub renderable : Option<Renderable>,
     pub hidden : Option<bool>,
+    pub secret : Option<bool>,
     pub blocks_tile : Option<bool>,
     pub blocks_visibility : Option<bool>,
     pub door_open : Option<bool>,
+    pub lock : Option<String>,
+    pub portcullis : Option<bool>,
+    pub lever : Option<bool>,
+    pub pressure_plate : Option<f32>,
+    pub mechanism : Option<bool>,
     pub entry_trigger : Option<EntryTrigger>,
+    pub disarm : Option<i32>,
     pub light : Option<super::mob_structs::MobLight>,
+    pub description : Option<String>
 }
 
 #[derive(Deserialize, Debug)]
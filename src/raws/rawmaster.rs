@@ -9,6 +9,7 @@ use regex::Regex;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use bracket_lib::terminal::{console, RGB, to_cp437};
 use crate::raws::Spell;
+use crate::map::{GasType, LevelTheme};
 
 pub fn parse_dice_string(dice : &str) -> (i32, i32, i32) {
     lazy_static! {
@@ -46,6 +47,7 @@ pub struct RawMaster {
     prop_index : HashMap<String, usize>,
     loot_index : HashMap<String, usize>,
     faction_index : HashMap<String, HashMap<String, Reaction>>,
+    pack_factions : HashSet<String>,
     spell_index : HashMap<String, usize>
 }
 
@@ -66,6 +68,7 @@ impl RawMaster {
             prop_index : HashMap::new(),
             loot_index : HashMap::new(),
             faction_index : HashMap::new(),
+            pack_factions : HashSet::new(),
             spell_index : HashMap::new()
         }
     }
@@ -119,6 +122,9 @@ impl RawMaster {
                 );
             }
             self.faction_index.insert(faction.name.clone(), reactions);
+            if faction.pack_tactics.unwrap_or(false) {
+                self.pack_factions.insert(faction.name.clone());
+            }
         }
 
         for (i,spell) in self.raws.spells.iter().enumerate() {
@@ -127,6 +133,10 @@ impl RawMaster {
     }
 }
 
+pub fn faction_hunts_in_packs(faction : &str, raws : &RawMaster) -> bool {
+    raws.pack_factions.contains(faction)
+}
+
 #[inline(always)]
 pub fn faction_reaction(my_faction : &str, their_faction : &str, raws : &RawMaster) -> Reaction {
     //println!("Looking for reaction to [{}] by [{}]", my_faction, their_faction);
@@ -272,6 +282,32 @@ fn parse_particle(n : &str) -> SpawnParticleBurst {
     }
 }
 
+/// How much gas a raw releases if it doesn't say, or says something that isn't a number.
+const DEFAULT_GAS_VOLUME : i32 = 100;
+
+fn parse_gas(n : &str) -> ReleasesGas {
+    let tokens : Vec<_> = n.split(';').collect();
+    let gas_type = match tokens[0] {
+        "smoke" => GasType::Smoke,
+        "poison" => GasType::Poison,
+        "confusion" => GasType::Confusion,
+        "paralysis" => GasType::Paralysis,
+        "methane" => GasType::Methane,
+        _ => {
+            console::log(format!("Warning: unknown gas type {}", tokens[0]));
+            GasType::Smoke
+        }
+    };
+    let volume = match tokens.get(1).map(|v| v.trim().parse::<i32>()) {
+        Some(Ok(volume)) if volume > 0 => volume,
+        _ => {
+            console::log(format!("Warning: bad gas volume in {}", n));
+            DEFAULT_GAS_VOLUME
+        }
+    };
+    ReleasesGas{ gas_type, volume }
+}
+
 macro_rules! apply_effects {
     ( $effects:expr, $eb:expr ) => {
         for effect in $effects.iter() {
@@ -297,6 +333,20 @@ macro_rules! apply_effects {
                 "identify" => $eb = $eb.with(ProvidesIdentification{}),
                 "slow" => $eb = $eb.with(Slow{ initiative_penalty : effect.1.parse::<f32>().unwrap() }),
                 "damage_over_time" => $eb = $eb.with( DamageOverTime { damage : effect.1.parse::<i32>().unwrap() } ),
+                "ignite" => $eb = $eb.with(Ignites{}),
+                "releases_gas" => $eb = $eb.with(parse_gas(&effect.1)),
+                "destroys_terrain" => $eb = $eb.with(DestroysTerrain{}),
+                "levitation" => {
+                    $eb = $eb.with(Levitation{});
+                    $eb = $eb.with(Duration{ turns: effect.1.parse::<i32>().unwrap() });
+                }
+                "random_teleport" => $eb = $eb.with(RandomTeleport{}),
+                "alarm" => $eb = $eb.with(Alarm{ radius : effect.1.parse::<i32>().unwrap() }),
+                "net" => {
+                    $eb = $eb.with(Entangles{});
+                    $eb = $eb.with(Duration{ turns: effect.1.parse::<i32>().unwrap() });
+                }
+                "place_trap" => $eb = $eb.with(PlacesTrap{ trap : effect.1.to_string() }),
                 _ => console::log(format!("Warning: consumable effect {} not implemented.", effect_name))
             }
         }
@@ -323,6 +373,9 @@ pub fn spawn_named_item(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
         }
 
         eb = eb.with(Name{ name : item_template.name.clone() });
+        if let Some(description) = &item_template.description {
+            eb = eb.with(Description{ text : description.clone() });
+        }
 
         eb = eb.with(crate::components::Item{
             initiative_penalty : item_template.initiative_penalty.unwrap_or(0.0),
@@ -400,6 +453,18 @@ pub fn spawn_named_item(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
             });
         }
 
+        if let Some(true) = item_template.digging {
+            eb = eb.with(DiggingTool{});
+        }
+
+        if let Some(true) = item_template.throwable {
+            eb = eb.with(Throwable{});
+        }
+
+        if let Some(key) = &item_template.key {
+            eb = eb.with(Key{ id : key.clone() });
+        }
+
         return Some(eb.build());
     }
     None
@@ -413,6 +478,7 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
         let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
 
         // Spawn in the specified location
+        let home = if let SpawnType::AtPosition{x, y} = pos { Some(bracket_lib::prelude::Point::new(x, y)) } else { None };
         eb = spawn_position(pos, eb, key, raws);
 
         // Initiative of 2
@@ -424,6 +490,9 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
         }
 
         eb = eb.with(Name{ name : mob_template.name.clone() });
+        if let Some(description) = &mob_template.description {
+            eb = eb.with(Description{ text : description.clone() });
+        }
 
         match mob_template.movement.as_ref() {
             "random" => eb = eb.with(MoveMode{ mode: Movement::Random }),
@@ -431,6 +500,22 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
             _ => eb = eb.with(MoveMode{ mode: Movement::Static })
         }
 
+        let default_state = if mob_template.movement == "static" { "guarding" } else { "wandering" };
+        let parse_state = |state_name : &str| match (state_name, home) {
+            ("asleep", _) => AIState::Asleep,
+            ("guarding", Some(post)) => AIState::Guarding{ post },
+            ("patrolling", Some(home)) => AIState::Patrolling{ route: vec![home], next: 0 },
+            ("wandering", _) => AIState::Wandering,
+            (unknown, _) => {
+                console::log(format!("Unknown or unplaceable AI state [{}] for {}", unknown, key));
+                AIState::Wandering
+            }
+        };
+        let state = parse_state(mob_template.ai_state.as_deref().unwrap_or(default_state));
+        // Sleepers go back to their usual routine once they've been woken
+        let resting_state = if state == AIState::Asleep { parse_state(default_state) } else { state.clone() };
+        eb = eb.with(Behavior{ state, resting_state });
+
         if let Some(quips) = &mob_template.quips {
             eb = eb.with(Quips{
                 available: quips.clone()
@@ -492,12 +577,16 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
         skills.skills.insert(Skill::Melee, 1);
         skills.skills.insert(Skill::Defense, 1);
         skills.skills.insert(Skill::Magic, 1);
+        skills.skills.insert(Skill::Stealth, 1);
+        skills.skills.insert(Skill::Perception, 1);
         if let Some(mobskills) = &mob_template.skills {
             for sk in mobskills.iter() {
                 match sk.0.as_str() {
                     "Melee" => { skills.skills.insert(Skill::Melee, *sk.1); }
                     "Defense" => { skills.skills.insert(Skill::Defense, *sk.1); }
                     "Magic" => { skills.skills.insert(Skill::Magic, *sk.1); }
+                    "Stealth" => { skills.skills.insert(Skill::Stealth, *sk.1); }
+                    "Perception" => { skills.skills.insert(Skill::Perception, *sk.1); }
                     _ => { console::log(format!("Unknown skill referenced: [{}]", sk.0)); }
                 }
             }
@@ -541,6 +630,36 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
             eb = eb.with(Faction{ name : "Mindless".to_string() })
         }
 
+        if let Some(true) = mob_template.pack_leader {
+            eb = eb.with(PackLeader{});
+        }
+
+        if let Some(true) = mob_template.swimmer {
+            eb = eb.with(Swimmer{});
+        }
+
+        if let Some(true) = mob_template.flying {
+            eb = eb.with(Flying{});
+        }
+
+        if let Some(true) = mob_template.tunnels {
+            eb = eb.with(Tunneler{});
+        }
+
+        if let Some(item_use) = &mob_template.item_use {
+            let mut user = ItemUser{ pickup: false, equip: false, potions: false, scrolls: false };
+            for usage in item_use.iter() {
+                match usage.as_str() {
+                    "pickup" => user.pickup = true,
+                    "equip" => user.equip = true,
+                    "potions" => user.potions = true,
+                    "scrolls" => user.scrolls = true,
+                    _ => console::log(format!("Unknown item use [{}] for {}", usage, key))
+                }
+            }
+            eb = eb.with(user);
+        }
+
         if let Some(vendor) = &mob_template.vendor {
             eb = eb.with(Vendor{ categories : vendor.clone() });
         }
@@ -589,10 +708,16 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
         }
 
         eb = eb.with(Name{ name : prop_template.name.clone() });
+        if let Some(description) = &prop_template.description {
+            eb = eb.with(Description{ text : description.clone() });
+        }
 
         if let Some(hidden) = prop_template.hidden {
             if hidden { eb = eb.with(Hidden{}) };
         }
+        if let Some(secret) = prop_template.secret {
+            if secret { eb = eb.with(SecretDoor{}) };
+        }
         if let Some(blocks_tile) = prop_template.blocks_tile {
             if blocks_tile { eb = eb.with(BlocksTile{}) };
         }
@@ -602,6 +727,24 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
         if let Some(door_open) = prop_template.door_open {
             eb = eb.with(Door{ open: door_open });
         }
+        if let Some(key) = &prop_template.lock {
+            eb = eb.with(Lock{ key : key.clone() });
+        }
+        if let Some(difficulty) = prop_template.disarm {
+            eb = eb.with(Disarmable{ difficulty });
+        }
+        if let Some(true) = prop_template.portcullis {
+            eb = eb.with(Portcullis{});
+        }
+        if let Some(true) = prop_template.lever {
+            eb = eb.with(Lever{ channel : 0, pulled : false });
+        }
+        if let Some(min_weight) = prop_template.pressure_plate {
+            eb = eb.with(PressurePlate{ channel : 0, min_weight, pressed : false });
+        }
+        if let Some(true) = prop_template.mechanism {
+            eb = eb.with(Mechanism{ channel : 0 });
+        }
         if let Some(entry_trigger) = &prop_template.entry_trigger {
             eb = eb.with(EntryTrigger{});
             apply_effects!(entry_trigger.effects, eb);
@@ -677,7 +820,7 @@ pub fn spawn_named_entity(raws: &RawMaster, ecs : &mut World, key : &str, pos :
     None
 }
 
-pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> RandomTable {
+pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32, theme: Option<LevelTheme>) -> RandomTable {
     use super::SpawnTableEntry;
 
     let available_options : Vec<&SpawnTableEntry> = raws.raws.spawn_table
@@ -691,6 +834,14 @@ pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> RandomTable {
         if e.add_map_depth_to_weight.is_some() {
             weight += depth;
         }
+        // Themed levels lean towards whatever suits them
+        if let Some(theme) = theme {
+            match e.themes.as_ref().and_then(|themes| themes.get(theme.name())) {
+                Some(bonus) => weight += bonus,
+                None if raws.item_index.contains_key(&e.name) => weight += theme.item_bonus(),
+                None => {}
+            }
+        }
         rt = rt.add(e.name.clone(), weight);
     }
 
@@ -709,4 +860,32 @@ pub fn get_item_drop(raws: &RawMaster, rng : &mut RandomNumberGenerator, table:
     }
 
     None
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_gas_reads_type_and_volume() {
+        let gas = parse_gas("poison;12");
+        assert_eq!(gas.gas_type, GasType::Poison);
+        assert_eq!(gas.volume, 12);
+        assert_eq!(parse_gas("methane;3").gas_type, GasType::Methane);
+    }
+
+    #[test]
+    fn parse_gas_falls_back_to_a_default_volume() {
+        assert_eq!(parse_gas("poison").volume, DEFAULT_GAS_VOLUME);
+        assert_eq!(parse_gas("poison;x").volume, DEFAULT_GAS_VOLUME);
+        assert_eq!(parse_gas("poison;").volume, DEFAULT_GAS_VOLUME);
+        assert_eq!(parse_gas("poison;x").gas_type, GasType::Poison);
+    }
+
+    #[test]
+    fn parse_gas_falls_back_to_smoke() {
+        let gas = parse_gas("laughing;5");
+        assert_eq!(gas.gas_type, GasType::Smoke);
+        assert_eq!(gas.volume, 5);
+    }
+}
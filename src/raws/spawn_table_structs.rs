@@ -1,4 +1,5 @@
 use serde::{Deserialize};
+use std::collections::HashMap;
 
 #[derive(Deserialize, Debug)]
 pub struct SpawnTableEntry {
@@ -6,5 +7,6 @@ pub struct SpawnTableEntry {
     pub weight : i32,
     pub min_depth: i32,
     pub max_depth: i32,
-    pub add_map_depth_to_weight : Option<bool>
+    pub add_map_depth_to_weight : Option<bool>,
+    pub themes : Option<HashMap<String, i32>>
 }
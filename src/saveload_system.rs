@@ -59,7 +59,12 @@ pub fn save_game(ecs : &mut World) {
             TeleportTo, ApplyMove, ApplyTeleport, MagicItem, ObfuscatedName, IdentifiedItem,
             SpawnParticleBurst, SpawnParticleLine, CursedItem, ProvidesRemoveCurse, ProvidesIdentification,
             AttributeBonus, StatusEffect, Duration, KnownSpells, SpellTemplate, WantsToCastSpell,
-            ProvidesMana, TeachesSpell, Slow, DamageOverTime, SpecialAbilities
+            ProvidesMana, TeachesSpell, Slow, DamageOverTime, SpecialAbilities, Stealth, Behavior,
+            PackLeader, PackMember, Routed, ItemUser, Ignites, OnFire, ReleasesGas, Paralysis,
+            Swimmer, Flying, Levitation, InDeepWater, DiggingTool, Tunneler, DestroysTerrain,
+            Perception, Searching, SecretDoor, Lock, Key, Portcullis, Lever, PressurePlate, Mechanism,
+            RandomTeleport, Alarm, Entangles, Entangled, Disarmable, Camouflaged, PlacesTrap, Description,
+            Throwable, Travelling, Resting, Remembered
         );
     }
 
@@ -117,7 +122,12 @@ pub fn load_game(ecs: &mut World) {
             TeleportTo, ApplyMove, ApplyTeleport, MagicItem, ObfuscatedName, IdentifiedItem,
             SpawnParticleBurst, SpawnParticleLine, CursedItem, ProvidesRemoveCurse, ProvidesIdentification,
             AttributeBonus, StatusEffect, Duration, KnownSpells, SpellTemplate, WantsToCastSpell,
-            ProvidesMana, TeachesSpell, Slow, DamageOverTime, SpecialAbilities
+            ProvidesMana, TeachesSpell, Slow, DamageOverTime, SpecialAbilities, Stealth, Behavior,
+            PackLeader, PackMember, Routed, ItemUser, Ignites, OnFire, ReleasesGas, Paralysis,
+            Swimmer, Flying, Levitation, InDeepWater, DiggingTool, Tunneler, DestroysTerrain,
+            Perception, Searching, SecretDoor, Lock, Key, Portcullis, Lever, PressurePlate, Mechanism,
+            RandomTeleport, Alarm, Entangles, Entangled, Disarmable, Camouflaged, PlacesTrap, Description,
+            Throwable, Travelling, Resting, Remembered
         );
     }
 
@@ -132,6 +142,7 @@ pub fn load_game(ecs: &mut World) {
         for (e,h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
+            worldmap.fill_missing_layers();
             crate::spatial::set_size((worldmap.height * worldmap.width) as usize);
             deleteme = Some(e);
         }
@@ -1,17 +1,23 @@
 use std::sync::Mutex;
 use specs::prelude::*;
-use crate::{ Map, tile_walkable, RunState };
+use crate::{ Map, RunState };
 
 struct SpatialMap {
     blocked : Vec<(bool, bool)>,
-    tile_content : Vec<Vec<(Entity, bool)>>
+    tile_content : Vec<Vec<(Entity, bool)>>,
+    known_traps : Vec<bool>,
+    known_trap_list : Vec<usize>,
+    camouflaged_traps : Vec<bool>
 }
 
 impl SpatialMap {
     fn new() -> Self {
         Self {
             blocked: Vec::new(),
-            tile_content: Vec::new()
+            tile_content: Vec::new(),
+            known_traps: Vec::new(),
+            known_trap_list: Vec::new(),
+            camouflaged_traps: Vec::new()
         }
     }
 }
@@ -24,6 +30,9 @@ pub fn set_size(map_tile_count: usize) {
     let mut lock = SPATIAL_MAP.lock().unwrap();
     lock.blocked = vec![(false, false); map_tile_count];
     lock.tile_content = vec![Vec::new(); map_tile_count];
+    lock.known_traps = vec![false; map_tile_count];
+    lock.known_trap_list.clear();
+    lock.camouflaged_traps = vec![false; map_tile_count];
 }
 
 pub fn clear() {
@@ -32,12 +41,15 @@ pub fn clear() {
     for content in lock.tile_content.iter_mut() {
         content.clear();
     }
+    lock.known_traps.iter_mut().for_each(|t| *t = false);
+    lock.known_trap_list.clear();
+    lock.camouflaged_traps.iter_mut().for_each(|t| *t = false);
 }
 
 pub fn populate_blocked_from_map(map: &Map) {
     let mut lock = SPATIAL_MAP.lock().unwrap();
-    for (i,tile) in map.tiles.iter().enumerate() {
-        lock.blocked[i].0 = !tile_walkable(*tile);
+    for i in 0..map.tiles.len() {
+        lock.blocked[i].0 = !map.walkable(i);
     }
 }
 
@@ -49,6 +61,37 @@ pub fn index_entity(entity: Entity, idx: usize, blocks_tile: bool) {
     }
 }
 
+/// Marks a tile as holding a trap that monsters know to stay away from.
+pub fn index_known_trap(idx: usize) {
+    let mut lock = SPATIAL_MAP.lock().unwrap();
+    if !lock.known_traps[idx] {
+        lock.known_traps[idx] = true;
+        lock.known_trap_list.push(idx);
+    }
+}
+
+/// Marks a tile as holding a trap the player set, which only the player knows to avoid.
+pub fn index_camouflaged_trap(idx: usize) {
+    let mut lock = SPATIAL_MAP.lock().unwrap();
+    lock.camouflaged_traps[idx] = true;
+}
+
+/// Whether the player knows of a trap on the tile, including the ones they set themselves.
+pub fn player_knows_trap(idx: usize) -> bool {
+    let lock = SPATIAL_MAP.lock().unwrap();
+    lock.known_traps[idx] || lock.camouflaged_traps[idx]
+}
+
+pub fn is_known_trap(idx: usize) -> bool {
+    let lock = SPATIAL_MAP.lock().unwrap();
+    lock.known_traps[idx]
+}
+
+pub fn known_traps() -> Vec<usize> {
+    let lock = SPATIAL_MAP.lock().unwrap();
+    lock.known_trap_list.clone()
+}
+
 pub fn is_blocked(idx: usize) -> bool {
     let lock = SPATIAL_MAP.lock().unwrap();
     lock.blocked[idx].0 || lock.blocked[idx].1
@@ -3,7 +3,7 @@ use specs::prelude::*;
 use super::{Pools, Pool, Player, Renderable, Name, Position, Viewshed, Rect,
     SerializeMe, random_table::RandomTable, HungerClock, HungerState, Map, TileType, raws::*,
     Attribute, Attributes, Skills, Skill, LightSource, Initiative, Faction, EquipmentChanged,
-    OtherLevelPosition, MasterDungeonMap, EntryTrigger, TeleportTo, SingleActivation };
+    OtherLevelPosition, MasterDungeonMap, LevelId, LevelTheme, EntryTrigger, TeleportTo, SingleActivation, Stealth, Perception };
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use std::collections::HashMap;
 use crate::{attr_bonus, player_hp_at_level, mana_at_level, StatusEffect, Duration, AttributeBonus, KnownSpells};
@@ -15,6 +15,8 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
     skills.skills.insert(Skill::Melee, 1);
     skills.skills.insert(Skill::Defense, 1);
     skills.skills.insert(Skill::Magic, 1);
+    skills.skills.insert(Skill::Stealth, 1);
+    skills.skills.insert(Skill::Perception, 1);
 
     let player = ecs
         .create_entity()
@@ -57,6 +59,8 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
         .with(Initiative{current: 0})
         .with(Faction{name : "Player".to_string() })
         .with(KnownSpells{ spells : Vec::new() })
+        .with(Stealth{ value : 1 })
+        .with(Perception{ value : 1 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -88,8 +92,8 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
 
 const MAX_MONSTERS : i32 = 4;
 
-fn room_table(map_depth: i32) -> RandomTable {
-    get_spawn_table_for_depth(&RAWS.lock().unwrap(), map_depth)
+fn room_table(map_depth: i32, theme: Option<LevelTheme>) -> RandomTable {
+    get_spawn_table_for_depth(&RAWS.lock().unwrap(), map_depth, theme)
 }
 
 /// Fills a room with stuff!
@@ -110,14 +114,15 @@ pub fn spawn_room(map: &Map, rng: &mut RandomNumberGenerator, room : &Rect, map_
 }
 
 /// Fills a region with stuff!
-pub fn spawn_region(_map: &Map, rng: &mut RandomNumberGenerator, area : &[usize], map_depth: i32, spawn_list : &mut Vec<(usize, String)>) {
-    let spawn_table = room_table(map_depth);
+pub fn spawn_region(map: &Map, rng: &mut RandomNumberGenerator, area : &[usize], map_depth: i32, spawn_list : &mut Vec<(usize, String)>) {
+    let spawn_table = room_table(map_depth, map.theme);
+    let extra_spawns = map.theme.map(|t| t.extra_spawns()).unwrap_or(0);
     let mut spawn_points : HashMap<usize, String> = HashMap::new();
     let mut areas : Vec<usize> = Vec::from(area);
 
     // Scope to keep the borrow checker happy
     {
-        let num_spawns = i32::min(areas.len() as i32, rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3);
+        let num_spawns = i32::min(areas.len() as i32, rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3 + extra_spawns);
         if num_spawns == 0 { return; }
 
         for _i in 0 .. num_spawns {
@@ -154,7 +159,7 @@ pub fn spawn_entity(ecs: &mut World, spawn : &(&usize, &String)) {
 pub fn spawn_town_portal(ecs: &mut World) {
     // Get current position & depth
     let map = ecs.fetch::<Map>();
-    let player_depth = map.depth;
+    let player_level = map.level_id();
     let player_pos = ecs.fetch::<Point>();
     let player_x = player_pos.x;
     let player_y = player_pos.y;
@@ -163,7 +168,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
 
     // Find part of the town for the portal
     let dm = ecs.fetch::<MasterDungeonMap>();
-    let town_map = dm.get_map(1).unwrap();
+    let town_map = dm.get_map(LevelId::main(1)).unwrap();
     let mut stairs_idx = 0;
     for (idx, tt) in town_map.tiles.iter().enumerate() {
         if *tt == TileType::DownStairs {
@@ -177,7 +182,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
 
     // Spawn the portal itself
     ecs.create_entity()
-        .with(OtherLevelPosition { x: portal_x, y: portal_y, depth: 1 })
+        .with(OtherLevelPosition { x: portal_x, y: portal_y, level: LevelId::main(1) })
         .with(Renderable {
             glyph: to_cp437('♥'),
             fg: RGB::named(CYAN),
@@ -185,7 +190,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
             render_order: 0
         })
         .with(EntryTrigger{})
-        .with(TeleportTo{ x: player_x, y: player_y, depth: player_depth, player_only: true })
+        .with(TeleportTo{ x: player_x, y: player_y, level: player_level, player_only: true })
         .with(SingleActivation{})
         .with(Name{ name : "Town Portal".to_string() })
         .build();
@@ -0,0 +1,369 @@
+use bracket_lib::prelude::{a_star_search, Algorithm2D, BaseMap, DijkstraMap, DistanceAlg, Point, SmallVec};
+use specs::prelude::*;
+use std::collections::HashSet;
+use crate::{Door, Map, RunState, Travelling, Baseline, Pools, HungerClock, HungerState, Item, Position, Hidden,
+    TileType, StatusEffect, gamelog::{GameLog, LogCategory}};
+
+/// The map as the player remembers it: paths only run over tiles they have seen, and steer
+/// clear of any traps they know about and anything standing in the way other than doors.
+struct RevealedMap<'a> {
+    map : &'a Map,
+    doors : &'a HashSet<usize>,
+    destination : usize
+}
+
+impl<'a> RevealedMap<'a> {
+    fn is_exit_valid(&self, x : i32, y : i32) -> bool {
+        if x < 1 || x > self.map.width-1 || y < 1 || y > self.map.height-1 { return false; }
+        let idx = self.map.xy_idx(x, y);
+        self.map.revealed_tiles[idx] && self.map.walkable(idx)
+            && (idx == self.destination || (!crate::spatial::player_knows_trap(idx)
+                && (self.doors.contains(&idx) || !crate::spatial::is_blocked(idx))))
+    }
+}
+
+impl<'a> BaseMap for RevealedMap<'a> {
+    fn get_available_exits(&self, idx : usize) -> SmallVec<[(usize, f32); 10]> {
+        let mut exits = SmallVec::new();
+        let x = idx as i32 % self.map.width;
+        let y = idx as i32 / self.map.width;
+        let cost = self.map.cost(idx);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)].iter() {
+            if self.is_exit_valid(x + dx, y + dy) {
+                let step = if *dx != 0 && *dy != 0 { 1.5 } else { 1.0 };
+                exits.push((self.map.xy_idx(x + dx, y + dy), cost * step));
+            }
+        }
+        exits
+    }
+
+    fn get_pathing_distance(&self, idx1 : usize, idx2 : usize) -> f32 {
+        let w = self.map.width as usize;
+        let p1 = Point::new(idx1 % w, idx1 / w);
+        let p2 = Point::new(idx2 % w, idx2 / w);
+        DistanceAlg::Pythagoras.distance2d(p1, p2)
+    }
+}
+
+impl<'a> Algorithm2D for RevealedMap<'a> {
+    fn dimensions(&self) -> Point {
+        Point::new(self.map.width, self.map.height)
+    }
+}
+
+/// Doors are in the way, but only until they are opened.
+fn door_tiles(ecs : &World) -> HashSet<usize> {
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let doors = ecs.read_storage::<Door>();
+    (&positions, &doors).join().map(|(pos, _)| map.xy_idx(pos.x, pos.y)).collect()
+}
+
+fn find_path(map : &Map, doors : &HashSet<usize>, start : usize, end : usize) -> Option<Vec<usize>> {
+    let revealed = RevealedMap{ map, doors, destination : end };
+    let path = a_star_search(start, end, &revealed);
+    if path.success && path.steps.len() > 1 {
+        Some(path.steps[1..].to_vec())
+    } else {
+        None
+    }
+}
+
+/// Finds a way from the player to a tile, over ground the player has already seen. The
+/// player's own tile isn't part of the path.
+pub fn path_to(ecs : &World, destination : Point) -> Option<Vec<usize>> {
+    let doors = door_tiles(ecs);
+    let map = ecs.fetch::<Map>();
+    if destination.x < 1 || destination.x > map.width-1 || destination.y < 1 || destination.y > map.height-1 {
+        return None;
+    }
+    let player_pos = ecs.fetch::<Point>();
+    let start = map.xy_idx(player_pos.x, player_pos.y);
+    let end = map.xy_idx(destination.x, destination.y);
+    if start == end || !map.revealed_tiles[end] { return None; }
+    find_path(&map, &doors, start, end)
+}
+
+/// Items lying where the player can see them, with the tile each is on.
+fn items_in_view(ecs : &World) -> Vec<(Entity, usize)> {
+    let map = ecs.fetch::<Map>();
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    (&entities, &items, &positions, !&hidden).join()
+        .map(|(entity, _, pos, _)| (entity, map.xy_idx(pos.x, pos.y)))
+        .filter(|(_, idx)| map.visible_tiles[*idx])
+        .collect()
+}
+
+/// Takes stock of everything that should stop the player in their tracks if it changes.
+pub fn baseline(ecs : &World) -> Baseline {
+    let player_entity = *ecs.fetch::<Entity>();
+    let hit_points = ecs.read_storage::<Pools>().get(player_entity).map_or(0, |p| p.hit_points.current);
+    let hunger = ecs.read_storage::<HungerClock>().get(player_entity).map_or(HungerState::Normal, |h| h.state);
+    let statuses = (&ecs.entities(), &ecs.read_storage::<StatusEffect>()).join()
+        .filter(|(_, s)| s.target == player_entity)
+        .map(|(entity, _)| entity)
+        .collect();
+    let items = items_in_view(ecs).into_iter().map(|(entity, _)| entity).collect();
+    Baseline{ hit_points, hunger, items_in_view : Some(items), statuses : Some(statuses) }
+}
+
+/// Whether anything in the second set wasn't in the first. A set we don't know, as after
+/// loading a save, never counts as a change.
+fn appeared(before : &Option<HashSet<Entity>>, now : &Option<HashSet<Entity>>) -> bool {
+    match (before, now) {
+        (Some(before), Some(now)) => now.iter().any(|e| !before.contains(e)),
+        _ => false
+    }
+}
+
+/// Why the player ought to stop, if anything has changed since the last turn.
+pub fn interruption(before : &Baseline, now : &Baseline) -> Option<&'static str> {
+    if now.hit_points < before.hit_points {
+        Some("you are hurt")
+    } else if now.hunger != before.hunger {
+        Some("you are getting hungry")
+    } else if appeared(&before.statuses, &now.statuses) {
+        Some("something has come over you")
+    } else if appeared(&now.statuses, &before.statuses) {
+        Some("an effect has worn off")
+    } else if appeared(&before.items_in_view, &now.items_in_view) {
+        Some("you spot something new")
+    } else {
+        None
+    }
+}
+
+/// Tiles on the edge of the known map: seen, but with something unseen next to them.
+fn is_frontier(map : &Map, idx : usize) -> bool {
+    let x = idx as i32 % map.width;
+    let y = idx as i32 / map.width;
+    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)].iter() {
+        let (nx, ny) = (x + dx, y + dy);
+        if nx < 0 || nx >= map.width || ny < 0 || ny >= map.height { continue; }
+        if !map.revealed_tiles[map.xy_idx(nx, ny)] { return true; }
+    }
+    false
+}
+
+/// The way to the nearest thing worth exploring: an item in plain sight, or the edge of the
+/// known map. The flag says whether there is something to pick up at the end.
+fn explore_path(ecs : &World) -> Option<(Vec<usize>, bool)> {
+    let items = items_in_view(ecs);
+    let doors = door_tiles(ecs);
+    let map = ecs.fetch::<Map>();
+    let player_pos = ecs.fetch::<Point>();
+    let start = map.xy_idx(player_pos.x, player_pos.y);
+
+    let revealed = RevealedMap{ map : &map, doors : &doors, destination : start };
+    let dijkstra_map = DijkstraMap::new(map.width, map.height, &[start], &revealed, 2000.0);
+    let mut best : Option<(usize, f32, bool)> = None;
+    for (idx, distance) in dijkstra_map.map.iter().enumerate() {
+        if idx == start || *distance == f32::MAX { continue; }
+        let item = items.iter().any(|(_, item_idx)| *item_idx == idx);
+        if !item && !is_frontier(&map, idx) { continue; }
+        if best.is_none() || *distance < best.unwrap().1 {
+            best = Some((idx, *distance, item));
+        }
+    }
+
+    let (target, _, item) = best?;
+    find_path(&map, &doors, start, target).map(|path| (path, item))
+}
+
+fn set_off(ecs : &mut World, path : Vec<usize>, exploring : bool, pick_up : bool) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let baseline = baseline(ecs);
+    ecs.write_storage::<Travelling>()
+        .insert(player_entity, Travelling{ path, pick_up, blocked : false, exploring, baseline })
+        .expect("Unable to insert");
+    keep_travelling(ecs).unwrap_or(RunState::AwaitingInput)
+}
+
+/// Sets the player off along a path. With enemies about, only the first step is taken.
+pub fn start_travel(ecs : &mut World, mut path : Vec<usize>, pick_up : bool) -> RunState {
+    if crate::player::hostile_in_view(ecs) {
+        path.truncate(1);
+    }
+    set_off(ecs, path, false, pick_up)
+}
+
+pub fn travel_to(ecs : &mut World, destination : Point, pick_up : bool) -> RunState {
+    match path_to(ecs, destination) {
+        Some(path) => start_travel(ecs, path, pick_up),
+        None => {
+            ecs.fetch_mut::<GameLog>().log(LogCategory::System, "You don't know a way there.");
+            RunState::AwaitingInput
+        }
+    }
+}
+
+/// Wanders off towards whatever hasn't been seen yet, picking up anything lying about on
+/// the way, until something needs the player's attention.
+pub fn explore(ecs : &mut World) -> RunState {
+    if crate::player::hostile_in_view(ecs) {
+        ecs.fetch_mut::<GameLog>().log(LogCategory::System, "Not with enemies in view!");
+        return RunState::AwaitingInput;
+    }
+    set_off(ecs, Vec::new(), true, false)
+}
+
+/// Heads for the nearest stairs the player knows about, going up or down.
+pub fn travel_to_stairs(ecs : &mut World, down : bool) -> RunState {
+    let wanted = if down { TileType::DownStairs } else { TileType::UpStairs };
+    let direction = if down { "down" } else { "up" };
+    let doors = door_tiles(ecs);
+    let nearest = {
+        let map = ecs.fetch::<Map>();
+        let player_pos = ecs.fetch::<Point>();
+        let start = map.xy_idx(player_pos.x, player_pos.y);
+        if map.tiles[start] == wanted {
+            drop(map);
+            ecs.fetch_mut::<GameLog>().log(LogCategory::System, format!("You are already on the way {}.", direction));
+            return RunState::AwaitingInput;
+        }
+        map.tiles.iter().enumerate()
+            .filter(|(idx, tile)| **tile == wanted && map.revealed_tiles[*idx])
+            .filter_map(|(idx, _)| find_path(&map, &doors, start, idx))
+            .min_by_key(|path| path.len())
+    };
+    match nearest {
+        Some(path) => start_travel(ecs, path, false),
+        None => {
+            ecs.fetch_mut::<GameLog>().log(LogCategory::System, format!("You don't know a way {} from here.", direction));
+            RunState::AwaitingInput
+        }
+    }
+}
+
+pub fn stop_travelling(ecs : &mut World) {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.write_storage::<Travelling>().remove(player_entity);
+}
+
+/// Takes the next step along the path, unless something has come up. Returns None once the
+/// journey is over.
+pub fn keep_travelling(ecs : &mut World) -> Option<RunState> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut travel = ecs.read_storage::<Travelling>().get(player_entity).cloned()?;
+
+    let now = baseline(ecs);
+    if let Some(reason) = interruption(&travel.baseline, &now) {
+        stop_travelling(ecs);
+        ecs.fetch_mut::<GameLog>().log(LogCategory::World, format!("You stop: {}.", reason));
+        return None;
+    }
+    travel.baseline = now;
+
+    if travel.path.is_empty() && travel.pick_up {
+        crate::player::get_item(ecs);
+        if travel.exploring {
+            travel.pick_up = false;
+            ecs.write_storage::<Travelling>().insert(player_entity, travel).expect("Unable to insert");
+        } else {
+            stop_travelling(ecs);
+        }
+        return Some(RunState::Ticking);
+    }
+    if (travel.exploring || travel.path.len() > 1) && crate::player::hostile_in_view(ecs) {
+        stop_travelling(ecs);
+        ecs.fetch_mut::<GameLog>().log(LogCategory::World, "You stop: there are enemies in view.");
+        return None;
+    }
+    if travel.exploring {
+        match explore_path(ecs) {
+            Some((path, pick_up)) => {
+                travel.path = path;
+                travel.pick_up = pick_up;
+            }
+            None => {
+                stop_travelling(ecs);
+                ecs.fetch_mut::<GameLog>().log(LogCategory::World, "There is nowhere left to explore.");
+                return None;
+            }
+        }
+    }
+    if travel.path.is_empty() {
+        stop_travelling(ecs);
+        return None;
+    }
+
+    let player_pos = *ecs.fetch::<Point>();
+    let (next_x, next_y) = {
+        let map = ecs.fetch::<Map>();
+        (travel.path[0] as i32 % map.width, travel.path[0] as i32 / map.width)
+    };
+    let result = crate::player::try_move_player(next_x - player_pos.x, next_y - player_pos.y, ecs);
+
+    // Opening a door costs a turn without moving, so the step is tried again; being stuck
+    // twice running means something is in the way for good
+    let moved = *ecs.fetch::<Point>() != player_pos;
+    if result != RunState::Ticking || (!moved && travel.blocked) {
+        stop_travelling(ecs);
+        // Whatever the last bump led to, such as a vendor's wares, is passed on
+        return if result == RunState::AwaitingInput { None } else { Some(result) };
+    }
+    if moved { travel.path.remove(0); }
+    travel.blocked = !moved;
+    ecs.write_storage::<Travelling>().insert(player_entity, travel).expect("Unable to insert");
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(count : usize) -> Vec<Entity> {
+        let mut world = World::new();
+        (0 .. count).map(|_| world.create_entity().build()).collect()
+    }
+
+    fn stock(items : &[Entity], statuses : &[Entity]) -> Baseline {
+        Baseline{
+            hit_points : 10,
+            hunger : HungerState::Normal,
+            items_in_view : Some(items.iter().cloned().collect()),
+            statuses : Some(statuses.iter().cloned().collect())
+        }
+    }
+
+    #[test]
+    fn nothing_changed_is_no_interruption() {
+        let e = entities(2);
+        assert_eq!(interruption(&stock(&[e[0]], &[e[1]]), &stock(&[e[0]], &[e[1]])), None);
+    }
+
+    #[test]
+    fn getting_hurt_or_hungry_interrupts() {
+        let before = stock(&[], &[]);
+        let hurt = Baseline{ hit_points : 8, ..before.clone() };
+        assert_eq!(interruption(&before, &hurt), Some("you are hurt"));
+        let healed = Baseline{ hit_points : 12, ..before.clone() };
+        assert_eq!(interruption(&before, &healed), None);
+        let hungry = Baseline{ hunger : HungerState::Hungry, ..before.clone() };
+        assert_eq!(interruption(&before, &hungry), Some("you are getting hungry"));
+    }
+
+    #[test]
+    fn a_new_item_interrupts_even_if_another_left_view() {
+        let e = entities(2);
+        assert_eq!(interruption(&stock(&[e[0]], &[]), &stock(&[e[1]], &[])), Some("you spot something new"));
+        assert_eq!(interruption(&stock(&[e[0], e[1]], &[]), &stock(&[e[1]], &[])), None);
+    }
+
+    #[test]
+    fn swapping_one_status_for_another_interrupts() {
+        let e = entities(2);
+        assert_eq!(interruption(&stock(&[], &[e[0]]), &stock(&[], &[e[1]])), Some("something has come over you"));
+        assert_eq!(interruption(&stock(&[], &[e[0]]), &stock(&[], &[])), Some("an effect has worn off"));
+    }
+
+    #[test]
+    fn an_unknown_baseline_does_not_interrupt() {
+        let e = entities(1);
+        let loaded = Baseline{ items_in_view : None, statuses : None, ..stock(&[], &[]) };
+        assert_eq!(interruption(&loaded, &stock(&[e[0]], &[e[0]])), None);
+    }
+}
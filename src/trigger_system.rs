@@ -1,6 +1,6 @@
 use bracket_lib::geometry::Point;
 use specs::prelude::*;
-use super::{EntityMoved, Position, EntryTrigger, Map, Name, gamelog::GameLog,
+use super::{EntityMoved, Position, EntryTrigger, Map, Name, gamelog::{GameLog, LogCategory},
             effects::*, AreaOfEffect};
 
 pub struct TriggerSystem {}
@@ -32,7 +32,7 @@ impl<'a> System<'a> for TriggerSystem {
                             // We triggered it
                             let name = names.get(entity_id);
                             if let Some(name) = name {
-                                log.entries.push(format!("{} triggers!", &name.name));
+                                log.log(LogCategory::World, format!("{} triggers!", &name.name));
                             }
 
                             // Call the effects system
@@ -1,6 +1,11 @@
 use specs::prelude::*;
-use super::{Viewshed, Position, Map, Player, Hidden, BlocksVisibility, gamelog::GameLog, Name};
-use bracket_lib::prelude::{field_of_view, Point, RandomNumberGenerator};
+use super::{Viewshed, Position, Map, Player, Hidden, BlocksVisibility, gamelog::{GameLog, LogCategory}, Name,
+    Perception, Searching, SecretDoor, Door, TileType, Item, Remembered, Vendor};
+use bracket_lib::prelude::{field_of_view, Point, RandomNumberGenerator, DistanceAlg};
+
+/// Searching is a good deal more thorough than just looking around.
+const SEARCH_BONUS : i32 = 8;
+const SEARCH_RADIUS : f32 = 2.5;
 
 pub struct VisibilitySystem {}
 
@@ -15,11 +20,19 @@ impl<'a> System<'a> for VisibilitySystem {
                         WriteExpect<'a, RandomNumberGenerator>,
                         WriteExpect<'a, GameLog>,
                         ReadStorage<'a, Name>,
-                        ReadStorage<'a, BlocksVisibility>);
+                        ReadStorage<'a, BlocksVisibility>,
+                        ReadStorage<'a, Perception>,
+                        ReadStorage<'a, Searching>,
+                        WriteStorage<'a, SecretDoor>,
+                        ReadStorage<'a, Door>,
+                        ReadStorage<'a, Item>,
+                        ReadStorage<'a, Vendor>,
+                        WriteStorage<'a, Remembered>);
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut map, entities, mut viewshed, pos, player,
-            mut hidden, mut rng, mut log, names, blocks_visibility) = data;
+            mut hidden, mut rng, mut log, names, blocks_visibility, perception,
+            searching, mut secret_doors, doors, items, vendors, mut remembered) = data;
 
         map.view_blocked.clear();
         for (block_pos, _block) in (&pos, &blocks_visibility).join() {
@@ -36,6 +49,10 @@ impl<'a> System<'a> for VisibilitySystem {
                 // If this is the player, reveal what they can see
                 let _p : Option<&Player> = player.get(ent);
                 if let Some(_p) = _p {
+                    let perception = perception.get(ent).map(|p| p.value).unwrap_or(0);
+                    let searching = searching.get(ent).is_some();
+                    let mut found : Vec<(Entity, usize)> = Vec::new();
+                    let mut seen : Vec<Entity> = Vec::new();
                     for t in map.visible_tiles.iter_mut() { *t = false };
                     for vis in viewshed.visible_tiles.iter() {
                         if vis.x > 0 && vis.x < map.width-1 && vis.y > 0 && vis.y < map.height-1 {
@@ -43,21 +60,43 @@ impl<'a> System<'a> for VisibilitySystem {
                             map.revealed_tiles[idx] = true;
                             map.visible_tiles[idx] = true;
 
-                            // Chance to reveal hidden things
+                            // Chance to reveal hidden things, better if we're up close and searching
+                            let bonus = if searching && DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *vis) < SEARCH_RADIUS {
+                                SEARCH_BONUS
+                            } else {
+                                0
+                            };
                             crate::spatial::for_each_tile_content(idx, |e| {
-                                let maybe_hidden = hidden.get(e);
-                                if let Some(_maybe_hidden) = maybe_hidden {
-                                    if rng.roll_dice(1,24)==1 {
-                                        let name = names.get(e);
-                                        if let Some(name) = name {
-                                            log.entries.push(format!("You spotted a {}.", &name.name));
-                                        }
-                                        hidden.remove(e);
-                                    }
+                                if hidden.get(e).is_some() && rng.roll_dice(1,24) + perception + bonus > 24 {
+                                    found.push((e, idx));
+                                }
+                                if (items.get(e).is_some() || vendors.get(e).is_some()) && hidden.get(e).is_none() {
+                                    seen.push(e);
                                 }
                             });
                         }
                     }
+
+                    for e in seen.iter() {
+                        remembered.insert(*e, Remembered{}).expect("Unable to insert");
+                    }
+
+                    for (e, idx) in found.iter() {
+                        hidden.remove(*e);
+                        if let Some(name) = names.get(*e) {
+                            log.log(LogCategory::World, format!("You spotted a {}.", &name.name));
+                        }
+                        // The wall a secret door sits in opens up; a passage doesn't leave a door behind
+                        if secret_doors.get(*e).is_some() {
+                            secret_doors.remove(*e);
+                            map.tiles[*idx] = TileType::Floor;
+                            map.terrain_changed();
+                            if doors.get(*e).is_none() {
+                                entities.delete(*e).expect("Delete failed");
+                            }
+                            viewshed.dirty = true;
+                        }
+                    }
                 }
             }
         }
@@ -0,0 +1,93 @@
+use specs::prelude::*;
+use bracket_lib::prelude::RandomNumberGenerator;
+use crate::{Map, TileType, MyTurn, Position, Pools, Attributes, Swimmer, Flying, Levitation, StatusEffect,
+    InDeepWater, InBackpack, Item, Name, EquipmentChanged, gamelog::{GameLog, LogCategory}};
+use crate::effects::{add_effect, EffectType, Targets};
+
+/// How many turns a non-swimmer can tread water before they start to drown
+const DROWNING_GRACE : i32 = 4;
+
+pub struct WaterSystem {}
+
+impl<'a> System<'a> for WaterSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+                        Entities<'a>,
+                        ReadExpect<'a, Map>,
+                        ReadExpect<'a, Entity>, // The player
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteExpect<'a, GameLog>,
+                        ReadStorage<'a, MyTurn>,
+                        WriteStorage<'a, Position>,
+                        ReadStorage<'a, Pools>,
+                        ReadStorage<'a, Attributes>,
+                        ReadStorage<'a, Swimmer>,
+                        ReadStorage<'a, Flying>,
+                        ReadStorage<'a, Levitation>,
+                        ReadStorage<'a, StatusEffect>,
+                        WriteStorage<'a, InDeepWater>,
+                        WriteStorage<'a, InBackpack>,
+                        ReadStorage<'a, Item>,
+                        ReadStorage<'a, Name>,
+                        WriteStorage<'a, EquipmentChanged>
+                      );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (entities, map, player_entity, mut rng, mut log, turns, mut positions, pools, attributes,
+            swimmers, flying, levitation, statuses, mut in_water, mut backpacks, items, names,
+            mut dirty) = data;
+
+        let mut swimming : Vec<(Entity, Position)> = Vec::new();
+        for (entity, pos, _pools, _turn) in (&entities, &positions, &pools, &turns).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            let airborne = flying.get(entity).is_some() ||
+                (&statuses, &levitation).join().any(|(status, _)| status.target == entity);
+            if map.tiles[idx] == TileType::DeepWater && !airborne {
+                swimming.push((entity, pos.clone()));
+            } else {
+                in_water.remove(entity);
+            }
+        }
+
+        for (entity, pos) in swimming.iter() {
+            let is_player = *entity == *player_entity;
+            let turns_in_water = in_water.get(*entity).map_or(0, |w| w.turns) + 1;
+            in_water.insert(*entity, InDeepWater{ turns : turns_in_water }).expect("Unable to insert");
+            if is_player && turns_in_water == 1 {
+                log.log(LogCategory::Status, "You are swimming.");
+            }
+            if swimmers.get(*entity).is_some() { continue; }
+
+            // Heavy loads drag you under, and something has to give
+            let pool = pools.get(*entity).unwrap();
+            let capacity = attributes.get(*entity).map_or(0, |a| (a.might.base + a.might.modifiers) * 15);
+            if pool.total_weight as i32 > capacity / 2 && rng.roll_dice(1, 3) == 1 {
+                let carried : Vec<Entity> = (&entities, &backpacks, &items).join()
+                    .filter(|(_, pack, _)| pack.owner == *entity)
+                    .map(|(item, _, _)| item)
+                    .collect();
+                if !carried.is_empty() {
+                    let lost = carried[rng.roll_dice(1, carried.len() as i32) as usize - 1];
+                    let item_name = names.get(lost).map_or("something".to_string(), |n| n.name.clone());
+                    backpacks.remove(lost);
+                    dirty.insert(*entity, EquipmentChanged{}).expect("Unable to insert");
+                    // Half the time it floats off somewhere nearby, otherwise it's gone for good
+                    let drift_x = i32::max(1, i32::min(map.width-2, pos.x + rng.roll_dice(1, 5) - 3));
+                    let drift_y = i32::max(1, i32::min(map.height-2, pos.y + rng.roll_dice(1, 5) - 3));
+                    if rng.roll_dice(1, 2) == 1 && map.walkable(map.xy_idx(drift_x, drift_y)) {
+                        positions.insert(lost, Position{ x: drift_x, y: drift_y }).expect("Unable to insert");
+                        if is_player { log.log(LogCategory::Loot, format!("Your {} drifts away.", item_name)); }
+                    } else {
+                        entities.delete(lost).expect("Unable to delete");
+                        if is_player { log.log(LogCategory::Loot, format!("Your {} sinks out of sight.", item_name)); }
+                    }
+                }
+            }
+
+            if turns_in_water > DROWNING_GRACE {
+                add_effect(None, EffectType::Damage{ amount: turns_in_water - DROWNING_GRACE }, Targets::Single{ target: *entity });
+                if is_player { log.log(LogCategory::Status, "You are drowning!"); }
+            }
+        }
+    }
+}